@@ -0,0 +1,326 @@
+#![windows_subsystem = "windows"]
+
+mod supervisor;
+
+use std::ffi::OsString;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::{mpsc, Arc, OnceLock};
+use std::time::Duration;
+
+use hotspot_lib::launcher_service::{self, LauncherConfig};
+use log::{error, info};
+use simplelog::{Config, LevelFilter, WriteLogger};
+use windows_service::{
+    define_windows_service,
+    service::{
+        PowerEventParam, ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState,
+        ServiceStatus, ServiceType, SessionChangeReason,
+    },
+    service_control_handler::{self, ServiceControlHandlerResult},
+    service_dispatcher,
+};
+
+use supervisor::Supervisor;
+
+const SERVICE_TYPE: ServiceType = ServiceType::OWN_PROCESS;
+
+const USAGE: &str =
+    "Usage: hotspot-launcher [--config <path>] <install|uninstall|status|run> [-- <app args>]
+
+  install     Register and start the launcher service; app args replace
+              the ones stored in the config
+  uninstall   Stop and remove the launcher service
+  status      Show the service state
+  run         Run as a service (default, used by the service manager)";
+
+static CONFIG: OnceLock<LauncherConfig> = OnceLock::new();
+
+define_windows_service!(ffi_service_main, service_main);
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let app_args = args
+        .iter()
+        .position(|a| a == "--")
+        .map(|i| args.split_off(i).split_off(1));
+
+    let config_path = match args.iter().position(|a| a == "--config") {
+        Some(i) if i + 1 < args.len() => {
+            let path = PathBuf::from(args.remove(i + 1));
+            args.remove(i);
+            path
+        }
+        Some(_) => return usage_error("--config expects a path"),
+        None => LauncherConfig::default_path(),
+    };
+
+    let command = args.first().cloned().unwrap_or_else(|| "run".to_string());
+    if command == "run" {
+        init_logging();
+    } else {
+        hotspot_lib::cli::attach_console();
+    }
+
+    let config = match LauncherConfig::load(&config_path) {
+        Ok(config) => config,
+        Err(e) if command == "run" => {
+            error!("{}, using defaults", e);
+            LauncherConfig::default()
+        }
+        Err(e) if command == "install" => {
+            eprintln!("{}, replacing it with the defaults", e);
+            LauncherConfig::default()
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match command.as_str() {
+        "run" => {
+            info!("Launcher starting with config {:?}", config_path);
+            let service_name = config.service_name.clone();
+            let _ = CONFIG.set(config);
+            match service_dispatcher::start(&service_name, ffi_service_main) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    error!("Failed to start service dispatcher: {:?}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        "install" => install(config, &config_path, app_args),
+        "uninstall" => match launcher_service::uninstall(&config.service_name) {
+            Ok(true) => {
+                println!("Service {} removed", config.service_name);
+                ExitCode::SUCCESS
+            }
+            Ok(false) => {
+                println!("Service {} is not installed", config.service_name);
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Uninstall failed: {}", e);
+                ExitCode::FAILURE
+            }
+        },
+        "status" => match launcher_service::query(&config.service_name) {
+            Ok(Some(summary)) => {
+                println!("Service:    {}", config.service_name);
+                println!("State:      {}", summary.state);
+                if let Some(pid) = summary.process_id {
+                    println!("PID:        {}", pid);
+                }
+                println!("Executable: {}", summary.executable_path.display());
+                println!("Config:     {}", config_path.display());
+                if summary.running {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitCode::from(3)
+                }
+            }
+            Ok(None) => {
+                println!("Service {} is not installed", config.service_name);
+                ExitCode::from(4)
+            }
+            Err(e) => {
+                eprintln!("Status query failed: {}", e);
+                ExitCode::FAILURE
+            }
+        },
+        "help" | "--help" => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        other => usage_error(&format!("Unknown command: {}", other)),
+    }
+}
+
+fn usage_error(message: &str) -> ExitCode {
+    hotspot_lib::cli::attach_console();
+    eprintln!("{}\n\n{}", message, USAGE);
+    ExitCode::from(2)
+}
+
+fn install(
+    mut config: LauncherConfig,
+    config_path: &Path,
+    app_args: Option<Vec<String>>,
+) -> ExitCode {
+    if let Some(args) = app_args {
+        config.args = args;
+    }
+    // Always rewritten, which also locks down the folder of older installs
+    if let Err(e) = config.save(config_path) {
+        eprintln!("{}", e);
+        return ExitCode::FAILURE;
+    }
+    println!("Wrote config to {}", config_path.display());
+
+    let launcher_exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            eprintln!("Could not resolve launcher path: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match launcher_service::install(&config, &launcher_exe, config_path) {
+        Ok(()) => {
+            println!("Service {} installed and started", config.service_name);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Install failed: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn init_logging() {
+    let mut path = std::env::temp_dir();
+    path.push("hotspot_launcher.log");
+
+    if let Ok(file) = File::create(&path) {
+        let _ = WriteLogger::init(LevelFilter::Info, Config::default(), file);
+        info!("Logging initialized at {:?}", path);
+    }
+}
+
+fn service_main(_arguments: Vec<OsString>) {
+    info!("Service main entry point");
+    if let Err(e) = run_service() {
+        error!("Service error: {:?}", e);
+    }
+}
+
+fn run_service() -> Result<(), windows_service::Error> {
+    info!("Running service logic");
+    let config = CONFIG.get().cloned().unwrap_or_default();
+    let service_name = config.service_name.clone();
+    let (shutdown_tx, shutdown_rx) = mpsc::channel();
+    let supervisor = Supervisor::new(config);
+    let handler_supervisor = Arc::clone(&supervisor);
+
+    let event_handler = move |control_event| -> ServiceControlHandlerResult {
+        match control_event {
+            ServiceControl::Stop => {
+                info!("Received Stop event");
+                handler_supervisor.shutdown();
+                let _ = shutdown_tx.send(());
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::SessionChange(param) => {
+                let session_id = param.notification.session_id;
+                info!(
+                    "Received SessionChange event: reason={:?} session={}",
+                    param.reason, session_id
+                );
+                let supervisor = Arc::clone(&handler_supervisor);
+                match param.reason {
+                    SessionChangeReason::SessionLogon
+                    | SessionChangeReason::ConsoleConnect
+                    | SessionChangeReason::RemoteConnect => {
+                        std::thread::spawn(move || supervisor.ensure_running(session_id));
+                    }
+                    SessionChangeReason::SessionLogoff => {
+                        std::thread::spawn(move || supervisor.stop_session(session_id));
+                    }
+                    SessionChangeReason::SessionUnlock => {
+                        std::thread::spawn(move || supervisor.request_reconnect(session_id));
+                    }
+                    SessionChangeReason::SessionLock
+                    | SessionChangeReason::ConsoleDisconnect
+                    | SessionChangeReason::RemoteDisconnect => {
+                        info!("Session {} is no longer interactive", session_id);
+                    }
+                    _ => {}
+                }
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::PowerEvent(event) => {
+                info!("Received PowerEvent: {:?}", event);
+                if matches!(
+                    event,
+                    PowerEventParam::ResumeAutomatic | PowerEventParam::ResumeSuspend
+                ) {
+                    let supervisor = Arc::clone(&handler_supervisor);
+                    std::thread::spawn(move || {
+                        for session_id in resume_targets(&supervisor) {
+                            supervisor.request_reconnect(session_id);
+                        }
+                    });
+                }
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            _ => ServiceControlHandlerResult::NotImplemented,
+        }
+    };
+
+    let status_handle = service_control_handler::register(&service_name, event_handler)?;
+
+    status_handle.set_service_status(ServiceStatus {
+        service_type: SERVICE_TYPE,
+        current_state: ServiceState::Running,
+        controls_accepted: ServiceControlAccept::STOP
+            | ServiceControlAccept::SESSION_CHANGE
+            | ServiceControlAccept::POWER_EVENT,
+        exit_code: ServiceExitCode::Win32(0),
+        checkpoint: 0,
+        wait_hint: Duration::default(),
+        process_id: None,
+    })?;
+
+    info!("Service status set to RUNNING");
+
+    // Spawn app immediately for every interactive session (in case users are already logged in)
+    spawn_for_interactive_sessions(&supervisor);
+
+    loop {
+        match shutdown_rx.recv_timeout(Duration::from_secs(1)) {
+            Ok(_) | Err(mpsc::RecvTimeoutError::Disconnected) => {
+                info!("Shutdown signal received or channel disconnected");
+                break;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+        }
+    }
+
+    status_handle.set_service_status(ServiceStatus {
+        service_type: SERVICE_TYPE,
+        current_state: ServiceState::Stopped,
+        controls_accepted: ServiceControlAccept::empty(),
+        exit_code: ServiceExitCode::Win32(0),
+        checkpoint: 0,
+        wait_hint: Duration::default(),
+        process_id: None,
+    })?;
+
+    info!("Service stopped");
+    Ok(())
+}
+
+fn spawn_for_interactive_sessions(supervisor: &Arc<Supervisor>) {
+    let sessions = supervisor::interactive_sessions();
+    info!("Interactive sessions: {:?}", sessions);
+    if sessions.is_empty() {
+        info!("No interactive session found");
+    }
+    for session_id in sessions {
+        supervisor.ensure_running(session_id);
+    }
+}
+
+fn resume_targets(supervisor: &Supervisor) -> Vec<u32> {
+    let mut sessions = supervisor.sessions();
+    for session_id in supervisor::interactive_sessions() {
+        if !sessions.contains(&session_id) {
+            sessions.push(session_id);
+        }
+    }
+    sessions
+}
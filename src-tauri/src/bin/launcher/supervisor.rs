@@ -0,0 +1,609 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use hotspot_lib::ipc::{self, IpcRequest, IpcResponse};
+use hotspot_lib::launcher_service::{self, LauncherConfig};
+use log::{error, info, warn};
+use windows::{
+    core::PWSTR, Win32::Foundation::*, Win32::System::Environment::*,
+    Win32::System::RemoteDesktop::*, Win32::System::Threading::*,
+};
+
+const IPC_TIMEOUT: Duration = Duration::from_secs(5);
+const WATCH_INTERVAL_MS: u32 = 1000;
+const LOGOFF_GRACE_MS: u32 = 10_000;
+
+struct ProcessHandle(HANDLE);
+
+unsafe impl Send for ProcessHandle {}
+unsafe impl Sync for ProcessHandle {}
+
+impl Drop for ProcessHandle {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseHandle(self.0);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Slot {
+    Launching,
+    Running(u32),
+}
+
+#[derive(Default)]
+struct RestartState {
+    consecutive_crashes: u32,
+    total_restarts: u32,
+}
+
+pub struct Supervisor {
+    config: LauncherConfig,
+    shutdown: AtomicBool,
+    restarts: Mutex<HashMap<u32, RestartState>>,
+    sessions: Mutex<HashMap<u32, Slot>>,
+    /// Apps this supervisor started, by session. Holding the handle keeps
+    /// the PID from being reused, so only these are ever terminated.
+    spawned: Mutex<HashMap<u32, (u32, Arc<ProcessHandle>)>>,
+    stopping: Mutex<HashSet<u32>>,
+}
+
+impl Supervisor {
+    pub fn new(config: LauncherConfig) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            shutdown: AtomicBool::new(false),
+            restarts: Mutex::new(HashMap::new()),
+            sessions: Mutex::new(HashMap::new()),
+            spawned: Mutex::new(HashMap::new()),
+            stopping: Mutex::new(HashSet::new()),
+        })
+    }
+
+    pub fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+    }
+
+    pub fn sessions(&self) -> Vec<u32> {
+        self.sessions
+            .lock()
+            .map(|sessions| {
+                sessions
+                    .iter()
+                    .filter(|(_, slot)| matches!(slot, Slot::Running(_)))
+                    .map(|(id, _)| *id)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn set_slot(&self, session_id: u32, slot: Option<Slot>) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            match slot {
+                Some(slot) => sessions.insert(session_id, slot),
+                None => sessions.remove(&session_id),
+            };
+            info!("Session table: {:?}", *sessions);
+        }
+    }
+
+    fn clear_slot_if(&self, session_id: u32, pid: u32) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            if sessions.get(&session_id) == Some(&Slot::Running(pid)) {
+                sessions.remove(&session_id);
+                info!("Session table: {:?}", *sessions);
+            }
+        }
+    }
+
+    pub fn ensure_running(self: &Arc<Self>, session_id: u32) {
+        if let Ok(mut stopping) = self.stopping.lock() {
+            stopping.remove(&session_id);
+        }
+        if let Ok(mut restarts) = self.restarts.lock() {
+            if let Some(state) = restarts.get_mut(&session_id) {
+                state.consecutive_crashes = 0;
+            }
+        }
+
+        let delay = Duration::from_secs(self.config.start_delay_secs);
+        if !delay.is_zero() {
+            info!(
+                "Waiting {:?} before launching in session {}",
+                delay, session_id
+            );
+            if !self.sleep_unless_shutdown(delay) {
+                return;
+            }
+        }
+        self.start_if_absent(session_id);
+    }
+
+    fn start_if_absent(self: &Arc<Self>, session_id: u32) {
+        {
+            let Ok(mut sessions) = self.sessions.lock() else {
+                return;
+            };
+            match sessions.get(&session_id) {
+                Some(Slot::Launching) => {
+                    info!("Launch already in progress for session {}", session_id);
+                    return;
+                }
+                Some(Slot::Running(pid)) if is_process_alive(*pid) => {
+                    info!(
+                        "App already tracked in session {} (PID {}), not spawning",
+                        session_id, pid
+                    );
+                    return;
+                }
+                _ => {}
+            }
+            sessions.insert(session_id, Slot::Launching);
+        }
+
+        if let Some(IpcResponse::Health { pid, .. }) = self.send(session_id, IpcRequest::Health) {
+            match self.adopt(session_id, pid) {
+                Ok(handle) => {
+                    info!(
+                        "App already running in session {} (PID {}), watching it",
+                        session_id, pid
+                    );
+                    self.set_slot(session_id, Some(Slot::Running(pid)));
+                    let supervisor = Arc::clone(self);
+                    std::thread::spawn(move || supervisor.watch(session_id, pid, handle));
+                    return;
+                }
+                Err(e) => warn!(
+                    "Could not watch PID {} in session {} ({}), spawning instead",
+                    pid, session_id, e
+                ),
+            }
+        }
+
+        match spawn_app_for_session(&self.config, session_id) {
+            Ok((pid, handle)) => {
+                let handle = Arc::new(handle);
+                if let Ok(mut spawned) = self.spawned.lock() {
+                    spawned.insert(session_id, (pid, Arc::clone(&handle)));
+                }
+                self.set_slot(session_id, Some(Slot::Running(pid)));
+                let supervisor = Arc::clone(self);
+                std::thread::spawn(move || supervisor.watch(session_id, pid, handle));
+            }
+            Err(e) => {
+                error!("Could not spawn app for session {}: {}", session_id, e);
+                self.set_slot(session_id, None);
+            }
+        }
+    }
+
+    /// Opens the app that answered over IPC. It's checked again once the
+    /// handle is held, since the PID can't be reused after that.
+    fn adopt(&self, session_id: u32, pid: u32) -> Result<Arc<ProcessHandle>, String> {
+        let app_exe = get_hotspot_exe_path(&self.config).ok_or("app executable not found")?;
+        let process = unsafe {
+            OpenProcess(
+                PROCESS_SYNCHRONIZE | PROCESS_QUERY_LIMITED_INFORMATION,
+                false,
+                pid,
+            )
+        }
+        .map(ProcessHandle)
+        .map_err(|e| e.to_string())?;
+        ipc::verify_process(pid, session_id, Path::new(&app_exe))?;
+        Ok(Arc::new(process))
+    }
+
+    fn watch(self: Arc<Self>, session_id: u32, pid: u32, handle: Arc<ProcessHandle>) {
+        let started = Instant::now();
+
+        loop {
+            if self.shutdown.load(Ordering::SeqCst) {
+                info!("Stopped watching PID {} in session {}", pid, session_id);
+                return;
+            }
+            let wait = unsafe { WaitForSingleObject(handle.0, WATCH_INTERVAL_MS) };
+            if wait == WAIT_OBJECT_0 {
+                break;
+            }
+            if wait != WAIT_TIMEOUT {
+                error!(
+                    "Waiting on PID {} failed ({:?}), giving up supervision",
+                    pid, wait
+                );
+                return;
+            }
+        }
+
+        let mut exit_code = 0u32;
+        unsafe {
+            let _ = GetExitCodeProcess(handle.0, &mut exit_code);
+        }
+        if let Ok(mut spawned) = self.spawned.lock() {
+            if spawned
+                .get(&session_id)
+                .is_some_and(|(_, tracked)| Arc::ptr_eq(tracked, &handle))
+            {
+                spawned.remove(&session_id);
+            }
+        }
+        drop(handle);
+        let runtime = started.elapsed();
+
+        self.clear_slot_if(session_id, pid);
+
+        let logging_off = self
+            .stopping
+            .lock()
+            .map(|mut stopping| stopping.remove(&session_id))
+            .unwrap_or(false);
+        if logging_off {
+            info!(
+                "App in session {} (PID {}) exited with code {:#x} during logoff",
+                session_id, pid, exit_code
+            );
+            return;
+        }
+
+        if exit_code == 0 {
+            info!(
+                "App in session {} (PID {}) exited normally after {:?}, not restarting",
+                session_id, pid, runtime
+            );
+            if let Ok(mut restarts) = self.restarts.lock() {
+                restarts.remove(&session_id);
+            }
+            return;
+        }
+
+        if !self.config.restart.enabled {
+            warn!(
+                "App in session {} (PID {}) exited with code {:#x} after {:?}; restarts disabled",
+                session_id, pid, exit_code, runtime
+            );
+            return;
+        }
+
+        let Some((delay, restart_count)) = self.record_crash(session_id, runtime) else {
+            error!(
+                "App in session {} (PID {}) exited with code {:#x}; crashed {} times in a row, giving up",
+                session_id, pid, exit_code, self.config.restart.max_consecutive_crashes
+            );
+            return;
+        };
+
+        warn!(
+            "App in session {} (PID {}) exited with code {:#x} after {:?}; restart #{} in {:?}",
+            session_id, pid, exit_code, runtime, restart_count, delay
+        );
+
+        if self.sleep_unless_shutdown(delay) {
+            self.start_if_absent(session_id);
+        }
+    }
+
+    pub fn stop_session(&self, session_id: u32) {
+        if let Ok(mut stopping) = self.stopping.lock() {
+            stopping.insert(session_id);
+        }
+
+        let spawned = self
+            .spawned
+            .lock()
+            .ok()
+            .and_then(|spawned| spawned.get(&session_id).cloned());
+        let asked = self.send(session_id, IpcRequest::Shutdown).is_some();
+
+        // An app someone else started is asked to leave, never terminated
+        let Some((pid, process)) = spawned else {
+            if asked {
+                info!("Asked app in session {} to shut down", session_id);
+            } else {
+                info!("No app to stop in session {}", session_id);
+            }
+            return;
+        };
+
+        unsafe {
+            if WaitForSingleObject(process.0, LOGOFF_GRACE_MS) == WAIT_OBJECT_0 {
+                info!(
+                    "App in session {} (PID {}) shut down cleanly",
+                    session_id, pid
+                );
+            } else {
+                warn!(
+                    "App in session {} (PID {}) did not exit within {} ms, terminating",
+                    session_id, pid, LOGOFF_GRACE_MS
+                );
+                if let Err(e) = TerminateProcess(process.0, 1) {
+                    error!("TerminateProcess for PID {} failed: {:?}", pid, e);
+                }
+            }
+        }
+    }
+
+    pub fn request_reconnect(&self, session_id: u32) {
+        self.send(session_id, IpcRequest::Reconnect);
+    }
+
+    /// Requests only go to a pipe served by our app in that session.
+    fn send(&self, session_id: u32, request: IpcRequest) -> Option<IpcResponse> {
+        let Some(app_exe) = get_hotspot_exe_path(&self.config) else {
+            warn!(
+                "IPC {:?} to session {} skipped, app executable not found",
+                request, session_id
+            );
+            return None;
+        };
+        match ipc::send_request(
+            session_id,
+            Path::new(&app_exe),
+            request.clone(),
+            IPC_TIMEOUT,
+        ) {
+            Ok(response) => {
+                info!(
+                    "IPC {:?} to session {} answered: {:?}",
+                    request, session_id, response
+                );
+                Some(response)
+            }
+            Err(e) => {
+                warn!("IPC {:?} to session {} failed: {}", request, session_id, e);
+                None
+            }
+        }
+    }
+
+    fn record_crash(&self, session_id: u32, runtime: Duration) -> Option<(Duration, u32)> {
+        let policy = &self.config.restart;
+        let mut restarts = self.restarts.lock().ok()?;
+        let state = restarts.entry(session_id).or_default();
+
+        if runtime >= Duration::from_secs(policy.stable_runtime_secs) {
+            state.consecutive_crashes = 0;
+        }
+        state.consecutive_crashes += 1;
+        if state.consecutive_crashes > policy.max_consecutive_crashes {
+            return None;
+        }
+        state.total_restarts += 1;
+
+        let exponent = (state.consecutive_crashes - 1).min(16);
+        let delay = Duration::from_secs(policy.base_backoff_secs)
+            .saturating_mul(1 << exponent)
+            .min(Duration::from_secs(policy.max_backoff_secs));
+        Some((delay, state.total_restarts))
+    }
+
+    fn sleep_unless_shutdown(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        while Instant::now() < deadline {
+            if self.shutdown.load(Ordering::SeqCst) {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(500));
+        }
+        !self.shutdown.load(Ordering::SeqCst)
+    }
+}
+
+pub fn interactive_sessions() -> Vec<u32> {
+    unsafe {
+        let mut info: *mut WTS_SESSION_INFOW = std::ptr::null_mut();
+        let mut count = 0u32;
+        if let Err(e) =
+            WTSEnumerateSessionsW(Some(WTS_CURRENT_SERVER_HANDLE), 0, 1, &mut info, &mut count)
+        {
+            error!("WTSEnumerateSessionsW failed: {:?}", e);
+            return Vec::new();
+        }
+
+        let sessions = std::slice::from_raw_parts(info, count as usize)
+            .iter()
+            .filter(|session| session.State == WTSActive && session.SessionId != 0)
+            .map(|session| session.SessionId)
+            .collect();
+        WTSFreeMemory(info as *mut std::ffi::c_void);
+        sessions
+    }
+}
+
+fn is_process_alive(pid: u32) -> bool {
+    unsafe {
+        let Ok(process) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) else {
+            return false;
+        };
+        let process = ProcessHandle(process);
+        let mut exit_code = 0u32;
+        GetExitCodeProcess(process.0, &mut exit_code).is_ok() && exit_code == STILL_ACTIVE.0 as u32
+    }
+}
+
+fn spawn_app_for_session(
+    config: &LauncherConfig,
+    session_id: u32,
+) -> Result<(u32, ProcessHandle), String> {
+    info!("Attempting to spawn app for session {}", session_id);
+    unsafe {
+        let mut user_token: HANDLE = HANDLE::default();
+
+        if let Err(e) = WTSQueryUserToken(session_id, &mut user_token) {
+            return Err(format!(
+                "Failed to query user token for session {}: {:?}",
+                session_id, e
+            ));
+        }
+        info!(
+            "Successfully obtained user token for session {}",
+            session_id
+        );
+
+        let result = match get_hotspot_exe_path(config) {
+            Some(path) => create_process_as_user(user_token, &path, &config.args),
+            None => Err("Could not find hotspot.exe path".to_string()),
+        };
+
+        let _ = CloseHandle(user_token);
+        result
+    }
+}
+
+unsafe fn create_process_as_user(
+    user_token: HANDLE,
+    path: &str,
+    args: &[String],
+) -> Result<(u32, ProcessHandle), String> {
+    info!("Target executable path: {}", path);
+    let cmd_line = launcher_service::command_line(Path::new(path), args);
+    info!("Command line: {}", cmd_line);
+    let mut cmd_wide: Vec<u16> = cmd_line.encode_utf16().chain(std::iter::once(0)).collect();
+
+    let desktop_str = "winsta0\\default\0";
+    let mut desktop_wide: Vec<u16> = desktop_str.encode_utf16().collect();
+
+    let si = STARTUPINFOW {
+        cb: std::mem::size_of::<STARTUPINFOW>() as u32,
+        lpDesktop: PWSTR(desktop_wide.as_mut_ptr()),
+        ..Default::default()
+    };
+
+    let mut pi = PROCESS_INFORMATION::default();
+
+    let mut env_block: *mut std::ffi::c_void = std::ptr::null_mut();
+    if CreateEnvironmentBlock(&mut env_block, Some(user_token), false).is_ok() {
+        info!("Environment block created");
+    } else {
+        warn!("Failed to create environment block");
+    }
+
+    let result = CreateProcessAsUserW(
+        Some(user_token),
+        None,
+        Some(PWSTR(cmd_wide.as_mut_ptr())),
+        None,
+        None,
+        false,
+        CREATE_UNICODE_ENVIRONMENT,
+        Some(env_block),
+        None,
+        &si,
+        &mut pi,
+    );
+
+    if !env_block.is_null() {
+        let _ = DestroyEnvironmentBlock(env_block);
+    }
+
+    match result {
+        Ok(()) => {
+            info!("CreateProcessAsUserW succeeded. PID: {:?}", pi.dwProcessId);
+            let _ = CloseHandle(pi.hThread);
+            Ok((pi.dwProcessId, ProcessHandle(pi.hProcess)))
+        }
+        Err(e) => Err(format!("CreateProcessAsUserW failed. Error: {:?}", e)),
+    }
+}
+
+/// Only the app installed next to the launcher is started;
+/// `LauncherConfig::load` already refuses any other `exe_path`.
+fn get_hotspot_exe_path(config: &LauncherConfig) -> Option<String> {
+    let installed = launcher_service::installed_app()?;
+    if let Some(path) = &config.exe_path {
+        if !launcher_service::same_path(path, &installed) {
+            error!(
+                "Ignoring exe_path {}, only {} is started",
+                path.display(),
+                installed.display()
+            );
+        }
+    }
+
+    if !installed.exists() {
+        warn!("{} does not exist", installed.display());
+        return None;
+    }
+    installed.to_str().map(|s| s.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hotspot_lib::launcher_service::RestartPolicy;
+
+    const SHORT: Duration = Duration::from_secs(1);
+
+    fn with_policy(restart: RestartPolicy) -> Arc<Supervisor> {
+        Supervisor::new(LauncherConfig {
+            restart,
+            ..Default::default()
+        })
+    }
+
+    fn delays(supervisor: &Supervisor, session_id: u32, crashes: usize) -> Vec<u64> {
+        (0..crashes)
+            .map_while(|_| supervisor.record_crash(session_id, SHORT))
+            .map(|(delay, _)| delay.as_secs())
+            .collect()
+    }
+
+    #[test]
+    fn backoff_doubles_until_the_crash_limit() {
+        let supervisor = with_policy(RestartPolicy::default());
+        assert_eq!(
+            delays(&supervisor, 1, 8),
+            vec![2, 4, 8, 16, 32, 64, 128, 256]
+        );
+        // The ninth crash in a row gives up
+        assert_eq!(supervisor.record_crash(1, SHORT), None);
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        let supervisor = with_policy(RestartPolicy {
+            base_backoff_secs: 2,
+            max_backoff_secs: 10,
+            ..Default::default()
+        });
+        assert_eq!(delays(&supervisor, 1, 6), vec![2, 4, 8, 10, 10, 10]);
+
+        // A huge crash limit doesn't overflow the shift
+        let supervisor = with_policy(RestartPolicy {
+            base_backoff_secs: 1,
+            max_backoff_secs: u64::MAX,
+            max_consecutive_crashes: 40,
+            ..Default::default()
+        });
+        assert_eq!(delays(&supervisor, 1, 40).last(), Some(&65536));
+    }
+
+    #[test]
+    fn stable_runtime_resets_the_backoff() {
+        let policy = RestartPolicy::default();
+        let stable = Duration::from_secs(policy.stable_runtime_secs);
+        let supervisor = with_policy(policy);
+
+        assert_eq!(delays(&supervisor, 1, 3), vec![2, 4, 8]);
+        assert_eq!(
+            supervisor.record_crash(1, stable - SHORT),
+            Some((Duration::from_secs(16), 4))
+        );
+        // The total keeps counting across resets
+        assert_eq!(
+            supervisor.record_crash(1, stable),
+            Some((Duration::from_secs(2), 5))
+        );
+        assert_eq!(delays(&supervisor, 1, 1), vec![4]);
+    }
+
+    #[test]
+    fn sessions_back_off_independently() {
+        let supervisor = with_policy(RestartPolicy::default());
+        assert_eq!(delays(&supervisor, 1, 3), vec![2, 4, 8]);
+        assert_eq!(delays(&supervisor, 2, 1), vec![2]);
+    }
+}
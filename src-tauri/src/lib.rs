@@ -1,10 +1,15 @@
 mod modules;
 
 use log::{error, info, LevelFilter};
-use modules::{auth, config, hotspot_toggle, network, startup, tray};
+use modules::{
+    auth, config, history, hotspot_toggle, http, interfaces, ipc, latency, local_api, netwatch,
+    network, portal, profiles, session, speedtest, startup, tray, wifi,
+};
 use simplelog::*;
 use std::fs::File;
-use std::sync::Mutex;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
 use sysinfo::{System, SystemExt};
 use tauri::{
     menu::{Menu, MenuItem},
@@ -12,22 +17,51 @@ use tauri::{
     Emitter, Manager,
 };
 
+pub use modules::{cli, ipc, launcher_service};
+
 pub struct AppState {
     pub credentials: Mutex<config::Credentials>,
     pub is_connected: Mutex<bool>,
     pub settings: Mutex<config::Settings>,
     pub sys: Mutex<System>,
     pub tray: Mutex<Option<TrayIcon>>,
+    pub local_api: Mutex<Vec<tauri::async_runtime::JoinHandle<()>>>,
+    pub launch_options: Mutex<startup::StartupOptions>,
+    pub startup_ready: Mutex<bool>,
+    pub latency: Mutex<latency::LatencyHistory>,
+    pub speed_test: Mutex<Option<Arc<AtomicBool>>>,
+    pub http: http::HttpClient,
+    pub manual_disconnect: Mutex<bool>,
+    pub startup: Arc<dyn startup::StartupHost>,
+    pub active_profile: profiles::ActiveProfile,
+    pub wifi: Arc<dyn wifi::WifiBackend>,
+    /// Held while the history store is rewritten.
+    pub history_write: Mutex<()>,
+    pub session: Mutex<Option<session::Session>>,
 }
 
 impl Default for AppState {
     fn default() -> Self {
+        // The HTTP client resolves and verifies against the active profile
+        let active_profile = profiles::ActiveProfile::default();
         Self {
             credentials: Mutex::new(config::Credentials::default()),
             is_connected: Mutex::new(false),
             settings: Mutex::new(config::Settings::default()),
             sys: Mutex::new(System::new_all()),
             tray: Mutex::new(None),
+            local_api: Mutex::new(Vec::new()),
+            launch_options: Mutex::new(startup::StartupOptions::default()),
+            startup_ready: Mutex::new(false),
+            latency: Mutex::new(latency::LatencyHistory::default()),
+            speed_test: Mutex::new(None),
+            http: http::HttpClient::new(&http::stored_settings(), active_profile.clone()),
+            manual_disconnect: Mutex::new(false),
+            startup: Arc::new(startup::SystemHost),
+            active_profile,
+            wifi: Arc::new(wifi::SystemBackend),
+            history_write: Mutex::new(()),
+            session: Mutex::new(None),
         }
     }
 }
@@ -39,11 +73,15 @@ fn get_github_token() -> String {
     secrets::GITHUB_TOKEN.to_string()
 }
 
+pub fn log_path() -> PathBuf {
+    std::env::temp_dir().join("hotspot_manager.log")
+}
+
 fn init_logging() {
     // Use default config to avoid 'time' crate dependency issues for now
     let log_config = ConfigBuilder::new().set_time_format_rfc3339().build();
 
-    let log_path = std::env::temp_dir().join("hotspot_manager.log");
+    let log_path = log_path();
 
     if let Ok(file) = File::create(&log_path) {
         let _ = WriteLogger::init(LevelFilter::Info, log_config, file);
@@ -75,33 +113,51 @@ pub fn run() {
         .setup(|app| {
             info!("Setup started");
 
-            // Migrate legacy autostart if needed (fire and forget)
-            std::thread::spawn(|| {
-                startup::check_and_migrate_legacy_autostart();
-            });
-
             let args: Vec<String> = std::env::args().collect();
-            let has_minimized_flag = args.iter().any(|arg| arg == "--minimized");
-            info!("Args: {:?}, Minimized Flag: {}", args, has_minimized_flag);
+            // Startup registrations carry the persisted options as arguments
+            let launch_options = startup::StartupOptions::parse(&args);
+            info!("Args: {:?}, Launch options: {:?}", args, launch_options);
+            let should_minimize = launch_options.minimized;
+            if let Ok(mut options) = app.state::<AppState>().launch_options.lock() {
+                *options = launch_options;
+            }
 
             // Load settings
             let handle = app.handle().clone();
-            let should_minimize = tauri::async_runtime::block_on(async move {
+            tauri::async_runtime::block_on(async move {
                 match config::load_settings(handle.clone()).await {
-                    Ok(settings) => {
-                        info!(
-                            "Settings loaded: minimize={}, startInTray={}",
-                            settings.minimize_to_tray, settings.start_in_tray
-                        );
-                        has_minimized_flag && settings.start_in_tray
-                    }
-                    Err(e) => {
-                        error!("Failed to load settings: {}", e);
-                        has_minimized_flag
-                    }
+                    Ok(settings) => info!(
+                        "Settings loaded: minimize={}, startInTray={}",
+                        settings.minimize_to_tray, settings.start_in_tray
+                    ),
+                    Err(e) => error!("Failed to load settings: {}", e),
                 }
             });
 
+            local_api::restart(app.handle());
+            ipc::start_server(app.handle().clone());
+            netwatch::start_watcher(app.handle().clone());
+            latency::start_monitor(app.handle().clone());
+            session::start_scheduler(app.handle().clone());
+
+            // Report autostart leftovers without touching them (fire and forget)
+            let (startup_host, startup_method, startup_options) = {
+                let state = app.state::<AppState>();
+                let (method, options) = state
+                    .settings
+                    .lock()
+                    .map(|s| (s.startup_method, startup::StartupOptions::from_settings(&s)))
+                    .unwrap_or_default();
+                (state.startup.clone(), method, options)
+            };
+            std::thread::spawn(move || {
+                startup::log_startup_diagnosis(
+                    startup_host.as_ref(),
+                    startup_method,
+                    &startup_options,
+                )
+            });
+
             if let Some(window) = app.get_webview_window("main") {
                 if !should_minimize {
                     info!("Starting visible");
@@ -171,13 +227,42 @@ pub fn run() {
 
             // Initial Connection Check & Tray Update
             let handle = app.handle().clone();
+            let wait_timeout = app
+                .state::<AppState>()
+                .settings
+                .lock()
+                .map(|s| s.network_wait_timeout_secs)
+                .unwrap_or(30);
             std::thread::spawn(move || {
-                // Initial check after 1.5s
-                std::thread::sleep(std::time::Duration::from_millis(1500));
-                info!("Doing initial connection check...");
-
                 tauri::async_runtime::block_on(async {
-                    let is_connected = network::check_connection().await;
+                    // Only set when launched by a startup registration
+                    let (delay, launch_profile, launch_connect) = handle
+                        .state::<AppState>()
+                        .launch_options
+                        .lock()
+                        .map(|o| (o.delay_secs, o.profile.clone(), o.connect))
+                        .unwrap_or_default();
+                    if delay > 0 {
+                        info!("Startup delay: {}s", delay);
+                        tokio::time::sleep(std::time::Duration::from_secs(delay)).await;
+                    }
+
+                    info!("Waiting up to {}s for the network...", wait_timeout);
+                    let up =
+                        netwatch::wait_for_network(std::time::Duration::from_secs(wait_timeout))
+                            .await;
+                    info!(
+                        "Network {}, doing initial connection check...",
+                        if up { "is up" } else { "wait timed out" }
+                    );
+
+                    // Probes and auto-login depend on which network we're on
+                    let ssid = tokio::task::spawn_blocking(netwatch::current_ssid)
+                        .await
+                        .unwrap_or(None);
+                    let switched = profiles::switch_for_launch(&handle, ssid, launch_profile).await;
+
+                    let is_connected = network::is_online(&handle.state::<AppState>().http).await;
                     let status = if is_connected {
                         "connected"
                     } else {
@@ -186,6 +271,21 @@ pub fn run() {
                     info!("Initial connection status: {}", status);
                     tray::update_tray_icon(handle.clone(), status.to_string());
                     let _ = handle.emit("network-status-update", is_connected);
+
+                    if let Ok(mut ready) = handle.state::<AppState>().startup_ready.lock() {
+                        *ready = true;
+                    }
+                    let _ = handle.emit("startup-ready", is_connected);
+
+                    let connect = launch_connect
+                        || handle
+                            .state::<AppState>()
+                            .settings
+                            .lock()
+                            .map(|s| s.connect_on_startup)
+                            .unwrap_or(false);
+                    profiles::startup_login(&handle, switched.as_ref(), connect, is_connected)
+                        .await;
                 });
             });
 
@@ -209,10 +309,24 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             network::check_connection,
-            network::get_ip_info,
+            network::get_public_ip,
+            interfaces::get_interfaces,
+            latency::get_latency_stats,
+            speedtest::run_speed_test,
+            speedtest::cancel_speed_test,
+            history::get_history,
+            history::export_history,
+            history::clear_history,
             network::get_network_stats,
+            wifi::scan_wifi,
+            wifi::connect_wifi,
             auth::perform_login,
             auth::perform_logout,
+            auth::set_manual_disconnect,
+            auth::get_manual_disconnect,
+            http::get_updater_proxy,
+            session::get_session_info,
+            portal::get_portal_account_status,
             config::save_credentials,
             config::load_credentials,
             config::save_settings,
@@ -220,10 +334,19 @@ pub fn run() {
             startup::enable_startup,
             startup::disable_startup,
             startup::is_startup_enabled,
+            startup::diagnose_startup,
+            startup::repair_startup,
+            startup::get_launch_options,
+            startup::is_startup_ready,
             get_github_token,
             tray::update_tray_icon,
             hotspot_toggle::toggle_hotspot,
-            hotspot_toggle::get_hotspot_status
+            hotspot_toggle::get_hotspot_status,
+            profiles::get_profiles,
+            profiles::save_profiles,
+            profiles::get_active_profile,
+            profiles::detect_current_network,
+            profiles::save_profile_credentials
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -2,6 +2,11 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if hotspot_lib::cli::is_cli_invocation(&args) {
+        std::process::exit(hotspot_lib::cli::run(&args));
+    }
+
     #[cfg(windows)]
     unsafe {
         use windows::Win32::System::Threading::{
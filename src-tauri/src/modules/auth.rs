@@ -1,75 +1,243 @@
-use super::network::check_connection;
+use log::{info, warn};
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::config::load_credentials;
+use super::http::{self, HttpClient};
+use super::network::is_online;
+use super::portal::{self, PortalForm, PortalProvider};
+use super::session;
+use super::tray::update_tray_icon;
+use crate::AppState;
+
+const LOGOUT_PROBES: u32 = 3;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogoutResult {
+    /// What the portal page answered; `None` when it couldn't be read.
+    pub portal_logged_in: Option<bool>,
+    /// A connectivity probe still got through after logging out.
+    pub still_online: bool,
+    /// The portal's answer and the probes disagree.
+    pub conflict: bool,
+    pub ended: bool,
+}
+
+impl LogoutResult {
+    /// Ended only when the internet is gone and the portal doesn't claim
+    /// the session is still open.
+    fn new(portal_logged_in: Option<bool>, still_online: bool) -> Self {
+        Self {
+            portal_logged_in,
+            still_online,
+            conflict: portal_logged_in.is_some_and(|logged_in| logged_in != still_online),
+            ended: !still_online && portal_logged_in != Some(true),
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match (self.ended, self.portal_logged_in) {
+            (true, _) => "Oturum kapatıldı",
+            (false, Some(true)) if !self.still_online => {
+                "Çıkış doğrulanamadı: internet kesildi ama portal oturumu hâlâ açık gösteriyor"
+            }
+            (false, Some(false)) => {
+                "Çıkış doğrulanamadı: portal oturumu kapattı ama internet hâlâ erişilebilir"
+            }
+            (false, Some(true)) => "Oturum kapatılamadı: portal hâlâ oturumu açık gösteriyor",
+            (false, None) => "Oturum kapatılamadı: internet hâlâ erişilebilir",
+        }
+    }
+}
 
 #[tauri::command]
-pub async fn perform_login(username: String, password: String) -> Result<bool, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(15))
-        .cookie_store(true)
-        .redirect(reqwest::redirect::Policy::limited(10))
-        .build()
-        .map_err(|e| e.to_string())?;
+pub async fn perform_login(
+    app: AppHandle,
+    username: String,
+    password: String,
+) -> Result<bool, String> {
+    let state = app.state::<AppState>();
+    let result = login(&state.http, username, password).await;
+    state.http.save_cookies();
+    session::start(&app, result?).await;
+    set_manual_disconnect(app.clone(), false);
+    Ok(true)
+}
 
-    let login_url = "https://hotspot.maxxarena.de/?auth=ticket&pageID=page-0";
+/// Logs in to the active profile's portal and returns the remaining ticket
+/// time when the response shows it.
+pub async fn login(
+    http: &HttpClient,
+    username: String,
+    password: String,
+) -> Result<Option<Duration>, String> {
+    let provider = portal::active_provider(http)?;
+    let client = http.client();
 
+    // Loading the page first gives the portal its session cookie
     let _ = client
-        .get("https://hotspot.maxxarena.de/")
+        .get(provider.status_url())
         .send()
         .await
-        .map_err(|e| e.to_string())?;
-
-    let params = [
-        ("auth", "ticket"),
-        ("lp-screen-size", "1920:1080:1920:1080"),
-        ("lp-input-username", username.as_str()),
-        ("lp-input-password", password.as_str()),
-        ("submit-login", "Oturum aç"),
-    ];
-
-    let response = client
-        .post(login_url)
-        .form(&params)
-        .header("Content-Type", "application/x-www-form-urlencoded")
-        .header("Origin", "https://hotspot.maxxarena.de")
-        .header("Referer", "https://hotspot.maxxarena.de/")
+        .map_err(|e| http::describe_error(&e))?;
+
+    let response = post_form(&client, provider, provider.login_form(&username, &password))
         .send()
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| http::describe_error(&e))?;
 
     let body = response.text().await.unwrap_or_default();
 
-    if body.contains("Oturum açma başarılı")
-        || body.contains("başarılı")
-        || body.contains("Oturumu kapat")
-        || body.contains("logout")
-    {
-        Ok(true)
+    if provider.login_succeeded(&body) {
+        Ok(portal::parse_remaining(&body))
+    } else if is_online(http).await {
+        Ok(None)
     } else {
-        let is_online = check_connection().await;
-        if is_online {
-            Ok(true)
-        } else {
-            Err(format!("Giriş başarısız oldu"))
-        }
+        Err("Giriş başarısız oldu".to_string())
     }
 }
 
+fn post_form(
+    client: &reqwest::Client,
+    provider: &dyn PortalProvider,
+    form: PortalForm,
+) -> reqwest::RequestBuilder {
+    let referer = provider.status_url();
+    let origin = reqwest::Url::parse(referer)
+        .map(|url| url.origin().ascii_serialization())
+        .unwrap_or_else(|_| referer.trim_end_matches('/').to_string());
+    client
+        .post(form.url)
+        .form(&form.fields)
+        .header("Origin", origin)
+        .header("Referer", referer)
+}
+
 #[tauri::command]
-pub async fn perform_logout() -> Result<(), String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .cookie_store(true)
-        .redirect(reqwest::redirect::Policy::none())
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    let params = [("logout", "1")];
-    let _ = client
-        .post("https://hotspot.maxxarena.de/")
-        .form(&params)
-        .header("Origin", "https://hotspot.maxxarena.de")
-        .header("Referer", "https://hotspot.maxxarena.de/")
+pub async fn perform_logout(app: AppHandle) -> Result<LogoutResult, String> {
+    // Set before the request so nothing logs back in while it is in flight
+    set_manual_disconnect(app.clone(), true);
+    let state = app.state::<AppState>();
+    let result = logout(&state.http).await;
+    state.http.save_cookies();
+    let result = result?;
+    if result.ended {
+        session::clear(&app);
+    }
+    Ok(result)
+}
+
+/// Sends the logout form, then checks both what the portal answered and
+/// whether the internet is really gone. Only the latter decides `ended`.
+pub async fn logout(http: &HttpClient) -> Result<LogoutResult, String> {
+    let provider = portal::active_provider(http)?;
+    let response = post_form(&http.client(), provider, provider.logout_form())
+        .timeout(Duration::from_secs(5))
         .send()
-        .await;
+        .await
+        .map_err(|e| format!("Çıkış isteği gönderilemedi: {}", http::describe_error(&e)))?;
+    let body = response.text().await.unwrap_or_default();
+    let portal_logged_in = (!body.is_empty()).then(|| provider.parse_status(&body).logged_in);
+
+    // The portal can take a moment before it intercepts traffic again
+    let mut still_online = true;
+    for attempt in 0..LOGOUT_PROBES {
+        if attempt > 0 {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        if !is_online(http).await {
+            still_online = false;
+            break;
+        }
+    }
 
-    Ok(())
+    let result = LogoutResult::new(portal_logged_in, still_online);
+    if result.ended {
+        info!("Logout verified");
+    } else {
+        warn!("Logout not verified: {:?}", result);
+    }
+    Ok(result)
+}
+
+pub fn manual_disconnect(app: &AppHandle) -> bool {
+    app.state::<AppState>()
+        .manual_disconnect
+        .lock()
+        .map(|m| *m)
+        .unwrap_or(false)
+}
+
+/// Set by a manual logout and cleared by the next login; while set nothing
+/// logs in on its own.
+#[tauri::command]
+pub fn set_manual_disconnect(app: AppHandle, value: bool) {
+    let changed = match app.state::<AppState>().manual_disconnect.lock() {
+        Ok(mut current) => std::mem::replace(&mut *current, value) != value,
+        Err(_) => false,
+    };
+    if changed {
+        let _ = app.emit("manual-disconnect-changed", value);
+    }
+}
+
+#[tauri::command]
+pub fn get_manual_disconnect(app: AppHandle) -> bool {
+    manual_disconnect(&app)
+}
+
+pub async fn login_with_saved_credentials(app: &AppHandle) -> Result<bool, String> {
+    let credentials = load_credentials(app.clone()).await?;
+    let result = perform_login(app.clone(), credentials.username, credentials.password).await;
+    publish_status(app, result.is_ok());
+    result
+}
+
+pub async fn logout_and_publish(app: &AppHandle) -> Result<LogoutResult, String> {
+    let result = perform_logout(app.clone()).await?;
+    publish_status(app, result.still_online);
+    Ok(result)
+}
+
+pub fn publish_status(app: &AppHandle, connected: bool) {
+    let status = if connected {
+        "connected"
+    } else {
+        "disconnected"
+    };
+    update_tray_icon(app.clone(), status.to_string());
+    let _ = app.emit("network-status-update", connected);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logout_verdict() {
+        let ended = LogoutResult::new(Some(false), false);
+        assert!(ended.ended && !ended.conflict);
+
+        // The page couldn't be read, the probes decide
+        assert!(LogoutResult::new(None, false).ended);
+        assert!(!LogoutResult::new(None, true).ended);
+
+        let still_in = LogoutResult::new(Some(true), true);
+        assert!(!still_in.ended && !still_in.conflict);
+    }
+
+    #[test]
+    fn logout_conflicts() {
+        let portal_open = LogoutResult::new(Some(true), false);
+        assert!(!portal_open.ended && portal_open.conflict);
+        assert!(portal_open.describe().contains("portal oturumu hâlâ açık"));
+
+        let still_online = LogoutResult::new(Some(false), true);
+        assert!(!still_online.ended && still_online.conflict);
+        assert!(still_online
+            .describe()
+            .contains("internet hâlâ erişilebilir"));
+    }
 }
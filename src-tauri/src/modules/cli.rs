@@ -0,0 +1,331 @@
+use serde_json::json;
+use std::io::BufRead;
+use sysinfo::{System, SystemExt};
+
+use super::{auth, config, hotspot_toggle, http, interfaces, network};
+
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_OFFLINE: i32 = 3;
+
+const COMMANDS: &[&str] = &["status", "login", "logout", "hotspot", "stats", "help"];
+
+const USAGE: &str = "Kullanım: hotspot <komut> [--json]
+
+Komutlar:
+  status                                İnternet bağlantısını kontrol et
+  login [--username <ad>] [--password-stdin]
+                                        Portala giriş yap. Şifre HOTSPOT_PASSWORD
+                                        ortam değişkeninden, --password-stdin
+                                        ile standart girdiden ya da kayıtlı
+                                        bilgilerden okunur
+  logout                                Portal oturumunu kapat
+  hotspot on|off|status                 Mobil etkin noktayı yönet
+  stats                                 Ağ trafiği istatistiklerini göster
+
+Çıkış kodları:
+  0  Başarılı
+  1  İşlem başarısız
+  2  Hatalı kullanım
+  3  Bağlantı yok / etkin nokta kapalı";
+
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    json: bool,
+    username: Option<String>,
+    /// Command line arguments are visible to every local user, so the
+    /// password only comes through stdin or the environment.
+    password_stdin: bool,
+    positional: Vec<String>,
+}
+
+struct Outcome {
+    code: i32,
+    value: serde_json::Value,
+    message: String,
+}
+
+impl Outcome {
+    fn new(code: i32, value: serde_json::Value, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            value,
+            message: message.into(),
+        }
+    }
+
+    fn failure(error: String) -> Self {
+        Self::new(
+            EXIT_FAILURE,
+            json!({ "ok": false, "error": error }),
+            format!("Hata: {}", error),
+        )
+    }
+}
+
+pub fn is_cli_invocation(args: &[String]) -> bool {
+    args.first()
+        .map(|cmd| COMMANDS.contains(&cmd.as_str()) || cmd == "--help")
+        .unwrap_or(false)
+}
+
+pub fn run(args: &[String]) -> i32 {
+    attach_console();
+
+    let command = args.first().map(String::as_str).unwrap_or("help");
+    let options = match parse_options(args.get(1..).unwrap_or_default()) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+
+    let outcome = tauri::async_runtime::block_on(async {
+        match command {
+            "status" => status().await,
+            "login" => login(&options).await,
+            "logout" => logout().await,
+            "hotspot" => hotspot(&options).await,
+            "stats" => stats(),
+            _ => Outcome::new(EXIT_OK, json!({ "ok": true, "usage": USAGE }), USAGE),
+        }
+    });
+
+    if options.json {
+        println!("{}", outcome.value);
+    } else if outcome.code == EXIT_FAILURE {
+        eprintln!("{}", outcome.message);
+    } else {
+        println!("{}", outcome.message);
+    }
+
+    outcome.code
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => options.json = true,
+            "--username" | "-u" => {
+                options.username = Some(iter.next().ok_or("--username değer bekliyor")?.clone())
+            }
+            "--password-stdin" => options.password_stdin = true,
+            "--password" | "-p" => {
+                return Err(
+                    "Şifre komut satırında verilemez; HOTSPOT_PASSWORD veya --password-stdin \
+                     kullanın"
+                        .to_string(),
+                )
+            }
+            other if other.starts_with('-') => {
+                return Err(format!("Bilinmeyen seçenek: {}", other))
+            }
+            other => options.positional.push(other.to_string()),
+        }
+    }
+
+    Ok(options)
+}
+
+/// The first line of stdin, without its line ending.
+fn read_password_stdin() -> Result<String, String> {
+    let mut line = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| format!("Şifre okunamadı: {}", e))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+async fn status() -> Outcome {
+    let connected = network::is_online(&http::HttpClient::standalone()).await;
+    let local_ip = interfaces::local_ip();
+
+    Outcome::new(
+        if connected { EXIT_OK } else { EXIT_OFFLINE },
+        json!({ "ok": true, "connected": connected, "localIp": local_ip }),
+        format!(
+            "Durum: {}\nYerel IP: {}",
+            if connected {
+                "Bağlı"
+            } else {
+                "Bağlı Değil"
+            },
+            local_ip
+        ),
+    )
+}
+
+async fn login(options: &Options) -> Outcome {
+    let stored = config::read_stored_credentials();
+
+    let username = options
+        .username
+        .clone()
+        .or_else(|| std::env::var("HOTSPOT_USERNAME").ok())
+        .or_else(|| stored.as_ref().map(|c| c.username.clone()))
+        .unwrap_or_default();
+    let password = if options.password_stdin {
+        match read_password_stdin() {
+            Ok(password) => password,
+            Err(e) => return Outcome::failure(e),
+        }
+    } else {
+        std::env::var("HOTSPOT_PASSWORD")
+            .ok()
+            .or_else(|| stored.as_ref().map(|c| c.password.clone()))
+            .unwrap_or_default()
+    };
+
+    if username.is_empty() || password.is_empty() {
+        return Outcome::new(
+            EXIT_USAGE,
+            json!({ "ok": false, "error": "Kullanıcı adı ve şifre gerekli" }),
+            "Kullanıcı adı ve şifre gerekli",
+        );
+    }
+
+    // Same cookie jar as the app, so a later `logout` from either side works
+    let http = http::HttpClient::standalone();
+    let result = auth::login(&http, username, password).await;
+    http.save_cookies();
+    match result {
+        Ok(_) => Outcome::new(
+            EXIT_OK,
+            json!({ "ok": true, "connected": true }),
+            "Giriş başarılı",
+        ),
+        Err(e) => Outcome::failure(e),
+    }
+}
+
+async fn logout() -> Outcome {
+    let http = http::HttpClient::standalone();
+    let result = auth::logout(&http).await;
+    http.save_cookies();
+    match result {
+        Ok(result) => Outcome::new(
+            if result.ended { EXIT_OK } else { EXIT_FAILURE },
+            json!({ "ok": result.ended, "logout": result }),
+            result.describe(),
+        ),
+        Err(e) => Outcome::failure(e),
+    }
+}
+
+async fn hotspot(options: &Options) -> Outcome {
+    let action = options
+        .positional
+        .first()
+        .map(String::as_str)
+        .unwrap_or("status");
+
+    let result = match action {
+        "on" => tokio::task::spawn_blocking(|| hotspot_toggle::set_hotspot_state(true)).await,
+        "off" => tokio::task::spawn_blocking(|| hotspot_toggle::set_hotspot_state(false)).await,
+        "status" => tokio::task::spawn_blocking(hotspot_toggle::read_hotspot_state).await,
+        other => {
+            return Outcome::new(
+                EXIT_USAGE,
+                json!({ "ok": false, "error": format!("Bilinmeyen işlem: {}", other) }),
+                format!("Bilinmeyen işlem: {} (on|off|status)", other),
+            )
+        }
+    };
+
+    match result
+        .map_err(|e| format!("Task hatası: {}", e))
+        .and_then(|r| r)
+    {
+        Ok(enabled) => Outcome::new(
+            if enabled || action == "off" {
+                EXIT_OK
+            } else {
+                EXIT_OFFLINE
+            },
+            json!({ "ok": true, "enabled": enabled }),
+            format!(
+                "Mobil etkin nokta: {}",
+                if enabled { "Açık" } else { "Kapalı" }
+            ),
+        ),
+        Err(e) => Outcome::failure(e),
+    }
+}
+
+fn stats() -> Outcome {
+    let mut sys = System::new_all();
+    let stats = network::collect_network_stats(&mut sys);
+
+    Outcome::new(
+        EXIT_OK,
+        json!({
+            "ok": true,
+            "totalReceived": stats.total_received,
+            "totalTransmitted": stats.total_transmitted,
+        }),
+        format!(
+            "Alınan: {} bayt\nGönderilen: {} bayt",
+            stats.total_received, stats.total_transmitted
+        ),
+    )
+}
+
+pub fn attach_console() {
+    #[cfg(windows)]
+    unsafe {
+        use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn cli_invocations() {
+        assert!(is_cli_invocation(&args(&["status"])));
+        assert!(is_cli_invocation(&args(&["login", "--json"])));
+        assert!(is_cli_invocation(&args(&["--help"])));
+        // Launch flags and unknown words start the app
+        assert!(!is_cli_invocation(&args(&[])));
+        assert!(!is_cli_invocation(&args(&["--minimized"])));
+        assert!(!is_cli_invocation(&args(&["statuss"])));
+    }
+
+    #[test]
+    fn options() {
+        let options = parse_options(&args(&["-u", "ali", "--json", "on", "--password-stdin"]));
+        assert_eq!(
+            options,
+            Ok(Options {
+                json: true,
+                username: Some("ali".to_string()),
+                password_stdin: true,
+                positional: vec!["on".to_string()],
+            })
+        );
+        assert_eq!(parse_options(&[]), Ok(Options::default()));
+    }
+
+    #[test]
+    fn invalid_options() {
+        assert!(parse_options(&args(&["--username"])).is_err());
+        assert!(parse_options(&args(&["--verbose"])).is_err());
+        // Never accepted on the command line
+        let error = parse_options(&args(&["--password", "gizli"])).unwrap_err();
+        assert!(error.contains("HOTSPOT_PASSWORD"));
+        assert!(!error.contains("gizli"));
+        assert!(parse_options(&args(&["-p", "gizli"])).is_err());
+    }
+}
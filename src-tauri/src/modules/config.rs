@@ -1,10 +1,15 @@
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 use tauri::Manager;
 use tauri_plugin_store::StoreExt;
 
+use super::local_api;
+use super::startup::StartupMethod;
 use crate::AppState;
 
+const APP_IDENTIFIER: &str = "com.hotspot.app";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Credentials {
     pub username: String,
@@ -28,6 +33,155 @@ pub struct Settings {
     pub start_in_tray: bool,
     pub connect_on_startup: bool,
     pub keep_hotspot_on: bool,
+    #[serde(default)]
+    pub local_api: LocalApiSettings,
+    #[serde(default)]
+    pub startup_method: StartupMethod,
+    #[serde(default)]
+    pub startup_delay_secs: u64,
+    #[serde(default)]
+    pub startup_profile: Option<String>,
+    #[serde(default = "default_network_wait_timeout")]
+    pub network_wait_timeout_secs: u64,
+    #[serde(default)]
+    pub latency: LatencySettings,
+    #[serde(default)]
+    pub speed_test: SpeedTestSettings,
+    #[serde(default)]
+    pub session: SessionSettings,
+    #[serde(default)]
+    pub http: HttpSettings,
+}
+
+fn default_network_wait_timeout() -> u64 {
+    30
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+    pub token: String,
+    pub bind_hotspot: bool,
+}
+
+impl Default for LocalApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 47615,
+            token: String::new(),
+            bind_hotspot: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencySettings {
+    pub enabled: bool,
+    pub targets: Vec<String>,
+    pub interval_secs: u64,
+}
+
+impl Default for LatencySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            targets: vec![
+                "gateway".to_string(),
+                "portal".to_string(),
+                "1.1.1.1:443".to_string(),
+            ],
+            interval_secs: 5,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedTestSettings {
+    pub download_url: String,
+    pub upload_url: String,
+    pub streams: usize,
+    pub warmup_secs: u64,
+    pub duration_secs: u64,
+}
+
+impl Default for SpeedTestSettings {
+    fn default() -> Self {
+        Self {
+            download_url: "https://speed.cloudflare.com/__down?bytes=25000000".to_string(),
+            upload_url: "https://speed.cloudflare.com/__up".to_string(),
+            streams: 4,
+            warmup_secs: 2,
+            duration_secs: 8,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSettings {
+    pub proactive_relogin: bool,
+    pub relogin_before_secs: u64,
+    /// Ticket length used when the portal doesn't show the remaining time;
+    /// 0 means unknown.
+    pub ticket_minutes: u64,
+}
+
+impl Default for SessionSettings {
+    fn default() -> Self {
+        Self {
+            proactive_relogin: true,
+            relogin_before_secs: 120,
+            ticket_minutes: 0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpSettings {
+    pub timeout_secs: u64,
+    pub connect_timeout_secs: u64,
+    pub user_agent: String,
+    #[serde(default)]
+    pub proxy: ProxySettings,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 15,
+            connect_timeout_secs: 5,
+            user_agent: format!("HotspotManager/{}", env!("CARGO_PKG_VERSION")),
+            proxy: ProxySettings::default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ProxyMode {
+    /// Whatever Windows (or the environment) is configured with.
+    #[default]
+    System,
+    None,
+    Manual,
+    Pac,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxySettings {
+    pub mode: ProxyMode,
+    /// `http://host:port`, used in manual mode.
+    pub url: String,
+    /// Comma separated hosts that skip the manual proxy.
+    pub bypass: String,
+    pub pac_url: String,
 }
 
 impl Default for Settings {
@@ -38,6 +192,15 @@ impl Default for Settings {
             start_in_tray: true,
             connect_on_startup: false,
             keep_hotspot_on: false,
+            local_api: LocalApiSettings::default(),
+            startup_method: StartupMethod::default(),
+            startup_delay_secs: 0,
+            startup_profile: None,
+            network_wait_timeout_secs: default_network_wait_timeout(),
+            latency: LatencySettings::default(),
+            speed_test: SpeedTestSettings::default(),
+            session: SessionSettings::default(),
+            http: HttpSettings::default(),
         }
     }
 }
@@ -79,8 +242,55 @@ pub async fn load_credentials(app: tauri::AppHandle) -> Result<Credentials, Stri
     }
 }
 
+pub fn app_data_dir() -> Option<PathBuf> {
+    #[cfg(windows)]
+    let base = std::env::var_os("APPDATA").map(PathBuf::from);
+    #[cfg(not(windows))]
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/share")));
+
+    base.map(|b| b.join(APP_IDENTIFIER))
+}
+
+pub fn read_store_file(name: &str) -> Option<serde_json::Map<String, serde_json::Value>> {
+    let path = app_data_dir()?.join(name);
+    let content = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content).ok()? {
+        serde_json::Value::Object(map) => Some(map),
+        _ => None,
+    }
+}
+
+pub fn read_stored_credentials() -> Option<Credentials> {
+    let store = read_store_file("credentials.json")?;
+    let username = store.get("username")?.as_str()?.to_string();
+    let password = store.get("password")?.as_str()?.to_string();
+
+    if username.is_empty() && password.is_empty() {
+        None
+    } else {
+        Some(Credentials { username, password })
+    }
+}
+
 #[tauri::command]
-pub async fn save_settings(app: tauri::AppHandle, settings: Settings) -> Result<(), String> {
+pub async fn save_settings(app: tauri::AppHandle, mut settings: Settings) -> Result<(), String> {
+    let previous_api = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .map(|s| s.local_api.clone())
+        .unwrap_or_default();
+
+    if settings.local_api.token.is_empty() {
+        settings.local_api.token = if previous_api.token.is_empty() {
+            local_api::generate_token()?
+        } else {
+            previous_api.token.clone()
+        };
+    }
+
     let store = app.store("settings.json").map_err(|e| e.to_string())?;
     store.set(
         "minimizeToTray",
@@ -93,18 +303,40 @@ pub async fn save_settings(app: tauri::AppHandle, settings: Settings) -> Result<
         serde_json::json!(settings.connect_on_startup),
     );
     store.set("keepHotspotOn", serde_json::json!(settings.keep_hotspot_on));
+    store.set("localApi", serde_json::json!(settings.local_api));
+    store.set("startupMethod", serde_json::json!(settings.startup_method));
+    store.set(
+        "startupDelaySecs",
+        serde_json::json!(settings.startup_delay_secs),
+    );
+    store.set(
+        "startupProfile",
+        serde_json::json!(settings.startup_profile),
+    );
+    store.set(
+        "networkWaitTimeoutSecs",
+        serde_json::json!(settings.network_wait_timeout_secs),
+    );
+    store.set("latency", serde_json::json!(settings.latency));
+    store.set("speedTest", serde_json::json!(settings.speed_test));
+    store.set("session", serde_json::json!(settings.session));
+    store.set("http", serde_json::json!(settings.http));
     store.save().map_err(|e| e.to_string())?;
 
     let state = app.state::<AppState>();
     if let Ok(mut cache) = state.settings.lock() {
         *cache = settings.clone();
     }
+    let http_result = state.http.configure(&settings.http);
+    if settings.local_api != previous_api {
+        local_api::restart(&app);
+    }
     println!(
         "Settings saved and cached: minimize={}, startInTray={}",
         settings.minimize_to_tray, settings.start_in_tray
     );
 
-    Ok(())
+    http_result
 }
 
 #[tauri::command]
@@ -136,18 +368,73 @@ pub async fn load_settings(app: tauri::AppHandle) -> Result<Settings, String> {
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
+    let local_api = store
+        .get("localApi")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    let startup_method = store
+        .get("startupMethod")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    let startup_delay_secs = store
+        .get("startupDelaySecs")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+
+    let startup_profile = store
+        .get("startupProfile")
+        .and_then(|v| v.as_str().map(|s| s.to_string()));
+
+    let network_wait_timeout_secs = store
+        .get("networkWaitTimeoutSecs")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_else(default_network_wait_timeout);
+
+    let latency = store
+        .get("latency")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    let speed_test = store
+        .get("speedTest")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    let session = store
+        .get("session")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    let http = store
+        .get("http")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
     let settings = Settings {
         minimize_to_tray,
         auto_reconnect,
         start_in_tray,
         connect_on_startup,
         keep_hotspot_on,
+        local_api,
+        startup_method,
+        startup_delay_secs,
+        startup_profile,
+        network_wait_timeout_secs,
+        latency,
+        speed_test,
+        session,
+        http,
     };
 
     let state = app.state::<AppState>();
     if let Ok(mut cache) = state.settings.lock() {
         *cache = settings.clone();
     }
+    // Broken TLS exceptions are logged there and don't fail the load
+    let _ = state.http.configure(&settings.http);
     println!(
         "Settings loaded and cached: minimize={}, startInTray={}",
         minimize_to_tray, start_in_tray
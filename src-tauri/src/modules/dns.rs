@@ -0,0 +1,358 @@
+use log::warn;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+use super::profiles::ActiveProfile;
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(3);
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+
+/// Resolves through the active profile's DNS servers, and through the system
+/// resolver when the profile has none or none of them answers. The profile is
+/// read per lookup, so switching networks needs no new client.
+pub struct ProfileResolver {
+    active: ActiveProfile,
+}
+
+impl ProfileResolver {
+    pub fn new(active: ActiveProfile) -> Self {
+        Self { active }
+    }
+}
+
+impl Resolve for ProfileResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let name = name.as_str().to_string();
+        let entries = self
+            .active
+            .lock()
+            .ok()
+            .and_then(|p| p.as_ref().map(|p| p.dns_servers.clone()))
+            .unwrap_or_default();
+        Box::pin(async move {
+            let ips = resolve(&entries, &name).await?;
+            Ok(Box::new(ips.into_iter().map(|ip| SocketAddr::new(ip, 0))) as Addrs)
+        })
+    }
+}
+
+/// Looks `host` up through the given profile DNS servers, then through the
+/// system resolver. For connections that don't go through reqwest.
+pub async fn resolve(entries: &[String], host: &str) -> std::io::Result<Vec<IpAddr>> {
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Ok(vec![ip]);
+    }
+    for server in servers(entries) {
+        match lookup(server, host).await {
+            Ok(ips) if !ips.is_empty() => return Ok(ips),
+            Ok(_) => warn!("DNS server {} has no address for {}", server, host),
+            Err(e) => warn!("DNS server {} failed for {}: {}", server, host, e),
+        }
+    }
+    Ok(tokio::net::lookup_host((host, 0))
+        .await?
+        .map(|addr| addr.ip())
+        .collect())
+}
+
+/// "10.0.0.1" or "10.0.0.1:5353"; anything else is skipped.
+pub fn servers(entries: &[String]) -> Vec<SocketAddr> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let entry = entry.trim();
+            let parsed = entry.parse::<SocketAddr>().ok().or_else(|| {
+                entry
+                    .parse::<IpAddr>()
+                    .ok()
+                    .map(|ip| SocketAddr::new(ip, 53))
+            });
+            if parsed.is_none() {
+                warn!("Ignoring invalid DNS server {:?}", entry);
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// A records first, AAAA only when there are none.
+async fn lookup(server: SocketAddr, name: &str) -> Result<Vec<IpAddr>, String> {
+    let ips = query(server, name, TYPE_A).await?;
+    if !ips.is_empty() {
+        return Ok(ips);
+    }
+    query(server, name, TYPE_AAAA).await
+}
+
+async fn query(server: SocketAddr, name: &str, qtype: u16) -> Result<Vec<IpAddr>, String> {
+    let bind: SocketAddr = if server.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(bind).await.map_err(|e| e.to_string())?;
+    socket.connect(server).await.map_err(|e| e.to_string())?;
+
+    // Unpredictable ids, so an off-path sender has to guess them
+    let mut id = [0u8; 2];
+    SystemRandom::new()
+        .fill(&mut id)
+        .map_err(|_| "no random query id".to_string())?;
+    let request = encode_query(u16::from_be_bytes(id), name, qtype)?;
+    socket.send(&request).await.map_err(|e| e.to_string())?;
+
+    let mut buf = [0u8; 1500];
+    tokio::time::timeout(QUERY_TIMEOUT, async {
+        loop {
+            let len = socket.recv(&mut buf).await.map_err(|e| e.to_string())?;
+            // Anything not answering this exact question is dropped
+            if answers(&buf[..len], &request) {
+                return parse_response(&buf[..len], qtype);
+            }
+            warn!("Dropped a mismatched DNS reply from {}", server);
+        }
+    })
+    .await
+    .map_err(|_| "timed out".to_string())?
+}
+
+fn encode_query(id: u16, name: &str, qtype: u16) -> Result<Vec<u8>, String> {
+    let mut packet = Vec::with_capacity(name.len() + 18);
+    packet.extend_from_slice(&id.to_be_bytes());
+    // Recursion desired, one question
+    packet.extend_from_slice(&[0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!("invalid domain name {}", name));
+        }
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&qtype.to_be_bytes());
+    packet.extend_from_slice(&1u16.to_be_bytes());
+    Ok(packet)
+}
+
+/// Same id, the response bit set and our single question echoed back. Names
+/// compare case-insensitively, some servers randomize the case.
+fn answers(packet: &[u8], request: &[u8]) -> bool {
+    packet.len() >= request.len()
+        && packet[..2] == request[..2]
+        && packet[2] & 0x80 != 0
+        && packet[4..6] == request[4..6]
+        && packet[12..request.len()].eq_ignore_ascii_case(&request[12..])
+}
+
+fn parse_response(packet: &[u8], qtype: u16) -> Result<Vec<IpAddr>, String> {
+    let read_u16 = |at: usize| -> Result<u16, String> {
+        packet
+            .get(at..at + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .ok_or_else(|| "truncated response".to_string())
+    };
+    let flags = read_u16(2)?;
+    match flags & 0x000f {
+        0 => {}
+        // NXDOMAIN is an answer, just an empty one
+        3 => return Ok(Vec::new()),
+        rcode => return Err(format!("response code {}", rcode)),
+    }
+    let questions = read_u16(4)?;
+    let answers = read_u16(6)?;
+
+    let mut at = 12;
+    for _ in 0..questions {
+        at = skip_name(packet, at)? + 4;
+    }
+    let mut ips = Vec::new();
+    for _ in 0..answers {
+        at = skip_name(packet, at)?;
+        let rtype = read_u16(at)?;
+        let len = read_u16(at + 8)? as usize;
+        at += 10;
+        let data = packet
+            .get(at..at + len)
+            .ok_or_else(|| "truncated response".to_string())?;
+        // CNAMEs come before the addresses they point to and are skipped
+        match (rtype, len) {
+            (TYPE_A, 4) if qtype == TYPE_A => {
+                ips.push(IpAddr::from(<[u8; 4]>::try_from(data).unwrap()))
+            }
+            (TYPE_AAAA, 16) if qtype == TYPE_AAAA => {
+                ips.push(IpAddr::from(<[u8; 16]>::try_from(data).unwrap()))
+            }
+            _ => {}
+        }
+        at += len;
+    }
+    Ok(ips)
+}
+
+/// Returns the offset right after a (possibly compressed) name.
+fn skip_name(packet: &[u8], mut at: usize) -> Result<usize, String> {
+    loop {
+        let len = *packet
+            .get(at)
+            .ok_or_else(|| "truncated response".to_string())?;
+        match len {
+            0 => return Ok(at + 1),
+            l if l & 0xc0 == 0xc0 => return Ok(at + 2),
+            l => at += 1 + l as usize,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_A: [u8; 29] = [
+        0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0, // header
+        7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 3, b'c', b'o', b'm', 0, // name
+        0, 1, 0, 1, // A, IN
+    ];
+
+    // 2001:db8::1
+    const DOC_V6: [u8; 16] = [0x20, 1, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+
+    /// A response to `request` with the given flags and answer records.
+    fn response(request: &[u8], rcode: u8, answers: &[(u16, &[u8])]) -> Vec<u8> {
+        let mut packet = request.to_vec();
+        packet[2] |= 0x80;
+        packet[3] = 0x80 | rcode;
+        packet[6..8].copy_from_slice(&(answers.len() as u16).to_be_bytes());
+        for (rtype, data) in answers {
+            // Name pointer to the question at offset 12
+            packet.extend_from_slice(&[0xc0, 12]);
+            packet.extend_from_slice(&rtype.to_be_bytes());
+            packet.extend_from_slice(&[0, 1, 0, 0, 0x0e, 0x10]);
+            packet.extend_from_slice(&(data.len() as u16).to_be_bytes());
+            packet.extend_from_slice(data);
+        }
+        packet
+    }
+
+    #[test]
+    fn encodes_query() {
+        assert_eq!(
+            encode_query(0x1234, "example.com.", TYPE_A).unwrap(),
+            EXAMPLE_A
+        );
+        assert!(encode_query(1, "example..com", TYPE_A).is_err());
+        assert!(encode_query(1, &format!("{}.com", "a".repeat(64)), TYPE_A).is_err());
+    }
+
+    #[test]
+    fn parses_addresses_after_cname() {
+        let cname = [4, b'e', b'd', b'g', b'e', 0xc0, 12];
+        let packet = response(
+            &EXAMPLE_A,
+            0,
+            &[
+                (5, &cname),
+                (TYPE_A, &[93, 184, 216, 34]),
+                (TYPE_AAAA, &DOC_V6),
+            ],
+        );
+        assert_eq!(
+            parse_response(&packet, TYPE_A).unwrap(),
+            vec![IpAddr::from([93, 184, 216, 34])]
+        );
+        assert_eq!(
+            parse_response(&packet, TYPE_AAAA).unwrap(),
+            vec!["2001:db8::1".parse::<IpAddr>().unwrap()]
+        );
+    }
+
+    #[test]
+    fn response_codes_and_truncation() {
+        assert_eq!(
+            parse_response(&response(&EXAMPLE_A, 3, &[]), TYPE_A).unwrap(),
+            Vec::<IpAddr>::new()
+        );
+        assert!(parse_response(&response(&EXAMPLE_A, 2, &[]), TYPE_A).is_err());
+
+        let packet = response(&EXAMPLE_A, 0, &[(TYPE_A, &[10, 0, 0, 1])]);
+        assert!(parse_response(&packet[..packet.len() - 2], TYPE_A).is_err());
+        assert!(parse_response(&packet[..20], TYPE_A).is_err());
+    }
+
+    #[test]
+    fn only_matching_responses_are_answers() {
+        let good = response(&EXAMPLE_A, 0, &[(TYPE_A, &[10, 0, 0, 1])]);
+        assert!(answers(&good, &EXAMPLE_A));
+
+        // 0x20 encoding: the server may flip the case of the name
+        let mut mixed = good.clone();
+        mixed[13..16].copy_from_slice(b"ExA");
+        assert!(answers(&mixed, &EXAMPLE_A));
+
+        let mut other_id = good.clone();
+        other_id[1] ^= 1;
+        assert!(!answers(&other_id, &EXAMPLE_A));
+
+        let mut other_name = good.clone();
+        other_name[13] = b'x';
+        assert!(!answers(&other_name, &EXAMPLE_A));
+
+        let mut other_type = good.clone();
+        other_type[26] = 28;
+        assert!(!answers(&other_type, &EXAMPLE_A));
+
+        // Our own query reflected back is not a response
+        assert!(!answers(&EXAMPLE_A, &EXAMPLE_A));
+        assert!(!answers(&good[..20], &EXAMPLE_A));
+    }
+
+    #[test]
+    fn server_entries() {
+        let entries = ["10.0.0.1", " 10.0.0.2:5353 ", "dns.local", "::1"].map(String::from);
+        assert_eq!(
+            servers(&entries),
+            vec![
+                "10.0.0.1:53".parse::<SocketAddr>().unwrap(),
+                "10.0.0.2:5353".parse().unwrap(),
+                "[::1]:53".parse().unwrap(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn spoofed_packets_are_ignored() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = server.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            let (len, client) = server.recv_from(&mut buf).await.unwrap();
+            let request = &buf[..len];
+
+            let mut forged = response(request, 0, &[(TYPE_A, &[6, 6, 6, 6])]);
+            forged[1] ^= 1;
+            server.send_to(&forged, client).await.unwrap();
+            let mut wrong_name = response(request, 0, &[(TYPE_A, &[6, 6, 6, 6])]);
+            wrong_name[13] ^= 1;
+            server.send_to(&wrong_name, client).await.unwrap();
+
+            let real = response(request, 0, &[(TYPE_A, &[10, 1, 2, 3])]);
+            server.send_to(&real, client).await.unwrap();
+        });
+
+        assert_eq!(
+            query(address, "portal.example", TYPE_A).await.unwrap(),
+            vec![IpAddr::from([10, 1, 2, 3])]
+        );
+    }
+
+    #[tokio::test]
+    async fn literal_addresses_skip_dns() {
+        assert_eq!(
+            resolve(&[], "192.168.1.1").await.unwrap(),
+            vec![IpAddr::from([192, 168, 1, 1])]
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use chrono::{DateTime, Local};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+use super::latency::LatencyStats;
+use super::speedtest::SpeedTestResult;
+use super::{netwatch, profiles};
+use crate::AppState;
+
+const HISTORY_STORE: &str = "history.json";
+const MAX_ENTRIES: usize = 10_000;
+pub const LATENCY_SAMPLE_EVERY: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoryKind {
+    SpeedTest,
+    Latency,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    /// RFC 3339, local time.
+    pub timestamp: String,
+    pub kind: HistoryKind,
+    pub profile: Option<String>,
+    pub ssid: Option<String>,
+    pub server: Option<String>,
+    pub download_mbps: Option<f64>,
+    pub upload_mbps: Option<f64>,
+    pub latency_ms: Option<f64>,
+    pub jitter_ms: Option<f64>,
+    pub loss_percent: Option<f64>,
+}
+
+impl HistoryEntry {
+    fn new(app: &AppHandle, kind: HistoryKind, latency: Option<&LatencyStats>) -> Self {
+        Self {
+            timestamp: Local::now().to_rfc3339(),
+            kind,
+            profile: profiles::active_profile(app).map(|p| p.name),
+            ssid: netwatch::current_ssid(),
+            server: None,
+            download_mbps: None,
+            upload_mbps: None,
+            latency_ms: latency.and_then(|l| l.avg_ms),
+            jitter_ms: latency.and_then(|l| l.jitter_ms),
+            loss_percent: latency.map(|l| l.loss_percent),
+        }
+    }
+}
+
+fn load(app: &AppHandle) -> Vec<HistoryEntry> {
+    app.store(HISTORY_STORE)
+        .ok()
+        .and_then(|store| store.get("entries"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn append(app: &AppHandle, entry: HistoryEntry) -> Result<(), String> {
+    // A speed test and a latency sample finishing together would otherwise
+    // both load the old list and one of them would be lost
+    let state = app.state::<AppState>();
+    let _guard = state.history_write.lock().map_err(|e| e.to_string())?;
+    let store = app.store(HISTORY_STORE).map_err(|e| e.to_string())?;
+    let mut entries = load(app);
+    entries.push(entry);
+    if entries.len() > MAX_ENTRIES {
+        entries.drain(..entries.len() - MAX_ENTRIES);
+    }
+    store.set("entries", serde_json::json!(entries));
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Blocking: reads the SSID and writes the store.
+pub fn record_speed_test(
+    app: &AppHandle,
+    result: &SpeedTestResult,
+    latency: Option<&LatencyStats>,
+) -> Result<(), String> {
+    let entry = HistoryEntry {
+        server: Some(result.server.clone()),
+        download_mbps: Some(result.download_mbps),
+        upload_mbps: Some(result.upload_mbps),
+        ..HistoryEntry::new(app, HistoryKind::SpeedTest, latency)
+    };
+    append(app, entry)
+}
+
+/// Blocking: reads the SSID and writes the store.
+pub fn record_latency(app: &AppHandle, latency: &LatencyStats) -> Result<(), String> {
+    let entry = HistoryEntry {
+        server: Some(latency.target.clone()),
+        ..HistoryEntry::new(app, HistoryKind::Latency, Some(latency))
+    };
+    append(app, entry)
+}
+
+fn query(
+    app: &AppHandle,
+    kind: Option<HistoryKind>,
+    since: Option<&str>,
+    until: Option<&str>,
+) -> Result<Vec<HistoryEntry>, String> {
+    filter(load(app), kind, since, until)
+}
+
+/// `since` and `until` are inclusive RFC 3339 bounds; entries with an
+/// unreadable timestamp never match.
+fn filter(
+    entries: Vec<HistoryEntry>,
+    kind: Option<HistoryKind>,
+    since: Option<&str>,
+    until: Option<&str>,
+) -> Result<Vec<HistoryEntry>, String> {
+    let parse = |value: &str| {
+        DateTime::parse_from_rfc3339(value)
+            .map_err(|e| format!("Geçersiz tarih '{}': {}", value, e))
+    };
+    let since = since.map(parse).transpose()?;
+    let until = until.map(parse).transpose()?;
+
+    Ok(entries
+        .into_iter()
+        .filter(|e| kind.is_none_or(|k| e.kind == k))
+        .filter(|e| match DateTime::parse_from_rfc3339(&e.timestamp) {
+            Ok(at) => since.is_none_or(|s| at >= s) && until.is_none_or(|u| at <= u),
+            Err(_) => false,
+        })
+        .collect())
+}
+
+/// SSIDs are picked by whoever runs the access point, so text a spreadsheet
+/// would read as a formula gets a leading `'`.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn to_csv(entries: &[HistoryEntry]) -> String {
+    let number = |v: Option<f64>| v.map(|v| format!("{:.2}", v)).unwrap_or_default();
+    let mut out = String::from(
+        "timestamp,kind,profile,ssid,server,download_mbps,upload_mbps,latency_ms,jitter_ms,loss_percent\n",
+    );
+    for e in entries {
+        let kind = match e.kind {
+            HistoryKind::SpeedTest => "speedTest",
+            HistoryKind::Latency => "latency",
+        };
+        let row = [
+            csv_field(&e.timestamp),
+            kind.to_string(),
+            csv_field(e.profile.as_deref().unwrap_or_default()),
+            csv_field(e.ssid.as_deref().unwrap_or_default()),
+            csv_field(e.server.as_deref().unwrap_or_default()),
+            number(e.download_mbps),
+            number(e.upload_mbps),
+            number(e.latency_ms),
+            number(e.jitter_ms),
+            number(e.loss_percent),
+        ];
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+#[tauri::command]
+pub async fn get_history(
+    app: AppHandle,
+    kind: Option<HistoryKind>,
+    since: Option<String>,
+    until: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<HistoryEntry>, String> {
+    let mut entries = query(&app, kind, since.as_deref(), until.as_deref())?;
+    if let Some(limit) = limit {
+        entries.drain(..entries.len().saturating_sub(limit));
+    }
+    Ok(entries)
+}
+
+/// Writes the matching entries to the Downloads folder and returns the path.
+#[tauri::command]
+pub async fn export_history(
+    app: AppHandle,
+    format: ExportFormat,
+    kind: Option<HistoryKind>,
+    since: Option<String>,
+    until: Option<String>,
+) -> Result<String, String> {
+    let entries = query(&app, kind, since.as_deref(), until.as_deref())?;
+    let (content, extension) = match format {
+        ExportFormat::Csv => (to_csv(&entries), "csv"),
+        ExportFormat::Json => (
+            serde_json::to_string_pretty(&entries).map_err(|e| e.to_string())?,
+            "json",
+        ),
+    };
+
+    let dir = app
+        .path()
+        .download_dir()
+        .or_else(|_| app.path().app_data_dir())
+        .map_err(|e| e.to_string())?;
+    let path = dir.join(format!(
+        "hotspot-gecmis-{}.{}",
+        Local::now().format("%Y%m%d-%H%M%S"),
+        extension
+    ));
+    std::fs::write(&path, content).map_err(|e| format!("Dosya yazılamadı: {}", e))?;
+    info!(
+        "Exported {} history entries to {}",
+        entries.len(),
+        path.display()
+    );
+    Ok(path.display().to_string())
+}
+
+#[tauri::command]
+pub async fn clear_history(app: AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let _guard = state.history_write.lock().map_err(|e| e.to_string())?;
+    let store = app.store(HISTORY_STORE).map_err(|e| e.to_string())?;
+    store.set("entries", serde_json::json!([]));
+    store.save().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: &str, kind: HistoryKind) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: timestamp.to_string(),
+            kind,
+            profile: None,
+            ssid: None,
+            server: None,
+            download_mbps: None,
+            upload_mbps: None,
+            latency_ms: None,
+            jitter_ms: None,
+            loss_percent: None,
+        }
+    }
+
+    fn timestamps(entries: &[HistoryEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.timestamp.as_str()).collect()
+    }
+
+    fn sample() -> Vec<HistoryEntry> {
+        vec![
+            entry("2026-03-01T09:00:00+03:00", HistoryKind::Latency),
+            entry("2026-03-01T12:00:00+03:00", HistoryKind::SpeedTest),
+            entry("2026-03-02T09:00:00+03:00", HistoryKind::Latency),
+            entry("bozuk", HistoryKind::Latency),
+        ]
+    }
+
+    #[test]
+    fn filter_bounds_are_inclusive() {
+        let entries = filter(
+            sample(),
+            None,
+            Some("2026-03-01T12:00:00+03:00"),
+            Some("2026-03-02T09:00:00+03:00"),
+        )
+        .unwrap();
+        assert_eq!(
+            timestamps(&entries),
+            ["2026-03-01T12:00:00+03:00", "2026-03-02T09:00:00+03:00"]
+        );
+    }
+
+    #[test]
+    fn filter_compares_instants_across_offsets() {
+        // 09:00 UTC is 12:00 in Istanbul
+        let entries = filter(sample(), None, None, Some("2026-03-01T09:00:00Z")).unwrap();
+        assert_eq!(
+            timestamps(&entries),
+            ["2026-03-01T09:00:00+03:00", "2026-03-01T12:00:00+03:00"]
+        );
+    }
+
+    #[test]
+    fn filter_by_kind_skips_unreadable_timestamps() {
+        let entries = filter(sample(), Some(HistoryKind::Latency), None, None).unwrap();
+        assert_eq!(
+            timestamps(&entries),
+            ["2026-03-01T09:00:00+03:00", "2026-03-02T09:00:00+03:00"]
+        );
+    }
+
+    #[test]
+    fn filter_rejects_invalid_bounds() {
+        assert!(filter(sample(), None, Some("dün"), None).is_err());
+    }
+
+    #[test]
+    fn csv_field_quoting() {
+        assert_eq!(csv_field("Yurt-WiFi"), "Yurt-WiFi");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("Kat 3, Oda 12"), "\"Kat 3, Oda 12\"");
+        assert_eq!(csv_field("\"Ev\" ağı"), "\"\"\"Ev\"\" ağı\"");
+        assert_eq!(csv_field("satır\nsonu"), "\"satır\nsonu\"");
+        assert_eq!(csv_field("satır\rsonu"), "\"satır\rsonu\"");
+    }
+
+    #[test]
+    fn csv_formulas_are_escaped() {
+        assert_eq!(csv_field("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(csv_field("+1"), "'+1");
+        assert_eq!(csv_field("-2"), "'-2");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(csv_field("Yurt=WiFi"), "Yurt=WiFi");
+    }
+
+    #[test]
+    fn csv_rows() {
+        let mut speed = entry("2026-03-01T12:00:00+03:00", HistoryKind::SpeedTest);
+        speed.ssid = Some("Kat 3, Oda 12".to_string());
+        speed.download_mbps = Some(48.256);
+        let csv = to_csv(&[speed]);
+        let row = csv.lines().nth(1).unwrap();
+        assert_eq!(
+            row,
+            "2026-03-01T12:00:00+03:00,speedTest,,\"Kat 3, Oda 12\",,48.26,,,,"
+        );
+    }
+}
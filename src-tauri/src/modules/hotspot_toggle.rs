@@ -2,6 +2,11 @@ use log::info;
 use std::os::windows::process::CommandExt;
 use std::process::Command;
 
+const TETHERING_MANAGER: &str = r#"
+            $cp = [Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]::GetInternetConnectionProfile()
+            $tm = [Windows.Networking.NetworkOperators.NetworkOperatorTetheringManager,Windows.Networking.NetworkOperators,ContentType=WindowsRuntime]::CreateFromConnectionProfile($cp)
+"#;
+
 fn run_powershell(script: &str) -> Result<String, String> {
     const CREATE_NO_WINDOW: u32 = 0x08000000;
     let output = Command::new("powershell")
@@ -18,63 +23,57 @@ fn run_powershell(script: &str) -> Result<String, String> {
     }
 }
 
+pub fn read_hotspot_state() -> Result<bool, String> {
+    let script = format!(
+        "{}            $tm.TetheringOperationalState.ToString()",
+        TETHERING_MANAGER
+    );
+    let result = run_powershell(&script)?;
+    Ok(result == "On")
+}
+
+pub fn set_hotspot_state(enabled: bool) -> Result<bool, String> {
+    if enabled {
+        info!("Mobil etkin nokta açılıyor...");
+        let script = format!(
+            "{}            $result = $tm.StartTetheringAsync().AsTask().Result\n            $result.Status.ToString()",
+            TETHERING_MANAGER
+        );
+        let result = run_powershell(&script)?;
+        if result == "Success" {
+            info!("Mobil etkin nokta açıldı");
+            Ok(true)
+        } else {
+            Err(format!("Açma başarısız: {}", result))
+        }
+    } else {
+        info!("Mobil etkin nokta kapatılıyor...");
+        let script = format!(
+            "{}            $result = $tm.StopTetheringAsync().AsTask().Result\n            $result.Status.ToString()",
+            TETHERING_MANAGER
+        );
+        let result = run_powershell(&script)?;
+        if result == "Success" {
+            info!("Mobil etkin nokta kapatıldı");
+            Ok(false)
+        } else {
+            Err(format!("Kapatma başarısız: {}", result))
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn get_hotspot_status() -> Result<bool, String> {
-    tokio::task::spawn_blocking(|| {
-        let script = r#"
-            $cp = [Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]::GetInternetConnectionProfile()
-            $tm = [Windows.Networking.NetworkOperators.NetworkOperatorTetheringManager,Windows.Networking.NetworkOperators,ContentType=WindowsRuntime]::CreateFromConnectionProfile($cp)
-            $tm.TetheringOperationalState.ToString()
-        "#;
-        let result = run_powershell(script)?;
-        Ok(result == "On")
-    })
-    .await
-    .map_err(|e| format!("Task hatası: {}", e))?
+    tokio::task::spawn_blocking(read_hotspot_state)
+        .await
+        .map_err(|e| format!("Task hatası: {}", e))?
 }
 
 #[tauri::command]
 pub async fn toggle_hotspot() -> Result<bool, String> {
     tokio::task::spawn_blocking(|| {
-        let status_script = r#"
-            $cp = [Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]::GetInternetConnectionProfile()
-            $tm = [Windows.Networking.NetworkOperators.NetworkOperatorTetheringManager,Windows.Networking.NetworkOperators,ContentType=WindowsRuntime]::CreateFromConnectionProfile($cp)
-            $tm.TetheringOperationalState.ToString()
-        "#;
-        let state = run_powershell(status_script)?;
-        let is_on = state == "On";
-
-        if is_on {
-            info!("Mobil etkin nokta kapatılıyor...");
-            let script = r#"
-                $cp = [Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]::GetInternetConnectionProfile()
-                $tm = [Windows.Networking.NetworkOperators.NetworkOperatorTetheringManager,Windows.Networking.NetworkOperators,ContentType=WindowsRuntime]::CreateFromConnectionProfile($cp)
-                $result = $tm.StopTetheringAsync().AsTask().Result
-                $result.Status.ToString()
-            "#;
-            let result = run_powershell(script)?;
-            if result == "Success" {
-                info!("Mobil etkin nokta kapatıldı");
-                Ok(false)
-            } else {
-                Err(format!("Kapatma başarısız: {}", result))
-            }
-        } else {
-            info!("Mobil etkin nokta açılıyor...");
-            let script = r#"
-                $cp = [Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]::GetInternetConnectionProfile()
-                $tm = [Windows.Networking.NetworkOperators.NetworkOperatorTetheringManager,Windows.Networking.NetworkOperators,ContentType=WindowsRuntime]::CreateFromConnectionProfile($cp)
-                $result = $tm.StartTetheringAsync().AsTask().Result
-                $result.Status.ToString()
-            "#;
-            let result = run_powershell(script)?;
-            if result == "Success" {
-                info!("Mobil etkin nokta açıldı");
-                Ok(true)
-            } else {
-                Err(format!("Açma başarısız: {}", result))
-            }
-        }
+        let is_on = read_hotspot_state()?;
+        set_hotspot_state(!is_on)
     })
     .await
     .map_err(|e| format!("Task hatası: {}", e))?
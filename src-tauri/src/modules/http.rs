@@ -0,0 +1,532 @@
+use log::{info, warn};
+use reqwest_cookie_store::CookieStoreMutex;
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use super::config::{self, HttpSettings, ProxyMode, ProxySettings};
+use super::profiles::{self, ActiveProfile, NetworkProfile};
+use super::{dns, tls};
+use crate::AppState;
+
+const COOKIE_FILE: &str = "cookies.json";
+/// How long a PAC answer is used before it's looked up again in the background.
+const PAC_TTL: Duration = Duration::from_secs(10 * 60);
+/// How soon a failed PAC lookup is tried again.
+const PAC_RETRY: Duration = Duration::from_secs(30);
+/// The longest a request waits for the first lookup of its host.
+const PAC_WAIT: Duration = Duration::from_secs(10);
+
+/// The settings file's HTTP section, for when the app isn't running (CLI).
+pub fn stored_settings() -> HttpSettings {
+    config::read_store_file("settings.json")
+        .and_then(|store| store.get("http").cloned())
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// Connect timeout, user agent, proxy and DNS every client shares. The total
+/// timeout is left to the caller, a speed test stream runs far longer than a probe.
+fn base_builder(
+    settings: &HttpSettings,
+    active: &ActiveProfile,
+    pac: &Arc<Pac>,
+) -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(settings.connect_timeout_secs.max(1)))
+        .dns_resolver(Arc::new(dns::ProfileResolver::new(active.clone())));
+    let builder = with_proxy(builder, &settings.proxy, pac);
+    if settings.user_agent.is_empty() {
+        builder
+    } else {
+        builder.user_agent(settings.user_agent.clone())
+    }
+}
+
+fn with_proxy(
+    builder: reqwest::ClientBuilder,
+    proxy: &ProxySettings,
+    pac: &Arc<Pac>,
+) -> reqwest::ClientBuilder {
+    match proxy.mode {
+        ProxyMode::System => builder,
+        ProxyMode::None => builder.no_proxy(),
+        ProxyMode::Manual => match reqwest::Proxy::all(proxy.url.trim()) {
+            Ok(p) => builder.proxy(p.no_proxy(reqwest::NoProxy::from_string(&proxy.bypass))),
+            Err(e) => {
+                warn!(
+                    "Invalid proxy {:?}, using the system proxy: {}",
+                    proxy.url, e
+                );
+                builder
+            }
+        },
+        ProxyMode::Pac => {
+            let pac = pac.clone();
+            builder.proxy(reqwest::Proxy::custom(move |url| pac.proxy_for(url)))
+        }
+    }
+}
+
+/// Asks Windows to run the PAC script, once per host and `PAC_TTL`. A
+/// lookup blocks while the script is fetched and evaluated, so it runs on
+/// its own thread; later lookups refresh in the background and requests
+/// keep using the previous answer meanwhile.
+struct Pac {
+    url: String,
+    lookup: fn(&str, &str) -> Result<Option<String>, String>,
+    cache: Mutex<HashMap<String, PacEntry>>,
+    resolved: Condvar,
+}
+
+#[derive(Default)]
+struct PacEntry {
+    /// `None` means DIRECT.
+    proxy: Option<String>,
+    /// `None` until the first lookup is done.
+    expires: Option<Instant>,
+    refreshing: bool,
+}
+
+impl Pac {
+    fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            lookup: pac_lookup,
+            cache: Mutex::new(HashMap::new()),
+            resolved: Condvar::new(),
+        }
+    }
+
+    fn proxy_for(self: &Arc<Self>, url: &reqwest::Url) -> Option<String> {
+        let key = format!(
+            "{}://{}:{}",
+            url.scheme(),
+            url.host_str().unwrap_or_default(),
+            url.port_or_known_default().unwrap_or_default()
+        );
+        let mut cache = self.cache.lock().ok()?;
+        let entry = cache.entry(key.clone()).or_default();
+        let stale = entry.expires.is_none_or(|at| at <= Instant::now());
+        if stale && !entry.refreshing {
+            entry.refreshing = true;
+            let (pac, key, target) = (self.clone(), key.clone(), url.to_string());
+            std::thread::spawn(move || pac.refresh(key, &target));
+        }
+        if entry.expires.is_some() {
+            return entry.proxy.clone();
+        }
+        drop(cache);
+
+        // Reqwest asks from an async worker; let the runtime move its other
+        // tasks elsewhere while this request waits for the first answer
+        let wait = || self.wait_for(&key);
+        match tokio::runtime::Handle::try_current().map(|h| h.runtime_flavor()) {
+            Ok(tokio::runtime::RuntimeFlavor::MultiThread) => tokio::task::block_in_place(wait),
+            _ => wait(),
+        }
+    }
+
+    fn wait_for(&self, key: &str) -> Option<String> {
+        let cache = self.cache.lock().ok()?;
+        let (cache, waited) = self
+            .resolved
+            .wait_timeout_while(cache, PAC_WAIT, |cache| {
+                cache.get(key).is_some_and(|e| e.expires.is_none())
+            })
+            .ok()?;
+        if waited.timed_out() {
+            warn!("PAC lookup for {} still running, going direct", key);
+        }
+        cache.get(key).and_then(|e| e.proxy.clone())
+    }
+
+    fn refresh(&self, key: String, url: &str) {
+        let result = (self.lookup)(&self.url, url);
+        if let Ok(mut cache) = self.cache.lock() {
+            let entry = cache.entry(key.clone()).or_default();
+            match result {
+                Ok(proxy) => {
+                    entry.proxy = proxy;
+                    entry.expires = Some(Instant::now() + PAC_TTL);
+                }
+                // A known answer is kept until the retry; the first one
+                // goes direct
+                Err(e) => {
+                    warn!("PAC lookup for {} failed, retrying later: {}", key, e);
+                    entry.expires = Some(Instant::now() + PAC_RETRY);
+                }
+            }
+            entry.refreshing = false;
+        }
+        self.resolved.notify_all();
+    }
+}
+
+/// `None` means DIRECT.
+#[cfg(windows)]
+fn pac_lookup(pac_url: &str, url: &str) -> Result<Option<String>, String> {
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{GlobalFree, HGLOBAL};
+    use windows::Win32::Networking::WinHttp::*;
+
+    let wide = |s: &str| s.encode_utf16().chain(Some(0)).collect::<Vec<u16>>();
+    let (agent, pac_url, url) = (wide("HotspotManager"), wide(pac_url), wide(url));
+
+    unsafe {
+        let session = WinHttpOpen(
+            PCWSTR(agent.as_ptr()),
+            WINHTTP_ACCESS_TYPE_NO_PROXY,
+            PCWSTR::null(),
+            PCWSTR::null(),
+            0,
+        );
+        if session.is_null() {
+            return Err(windows::core::Error::from_win32().to_string());
+        }
+        let mut options = WINHTTP_AUTOPROXY_OPTIONS {
+            dwFlags: WINHTTP_AUTOPROXY_CONFIG_URL,
+            lpszAutoConfigUrl: PCWSTR(pac_url.as_ptr()),
+            fAutoLogonIfChallenged: true.into(),
+            ..Default::default()
+        };
+        let mut info = WINHTTP_PROXY_INFO::default();
+        let result = WinHttpGetProxyForUrl(session, PCWSTR(url.as_ptr()), &mut options, &mut info);
+        let _ = WinHttpCloseHandle(session);
+        result.map_err(|e| e.to_string())?;
+
+        let proxy =
+            if info.dwAccessType == WINHTTP_ACCESS_TYPE_NAMED_PROXY && !info.lpszProxy.is_null() {
+                info.lpszProxy.to_string().ok()
+            } else {
+                None
+            };
+        for s in [info.lpszProxy, info.lpszProxyBypass] {
+            if !s.is_null() {
+                let _ = GlobalFree(Some(HGLOBAL(s.0 as _)));
+            }
+        }
+        Ok(proxy.and_then(|list| first_proxy(&list)))
+    }
+}
+
+/// WinHTTP lists proxies as "host:port; other:port", sometimes with a
+/// "scheme=" prefix; the first one is used.
+#[cfg(windows)]
+fn first_proxy(list: &str) -> Option<String> {
+    let entry = list
+        .split(|c: char| c == ';' || c.is_whitespace())
+        .find(|e| !e.is_empty())?;
+    let entry = entry.rsplit('=').next().unwrap_or(entry);
+    Some(if entry.contains("://") {
+        entry.to_string()
+    } else {
+        format!("http://{}", entry)
+    })
+}
+
+#[cfg(not(windows))]
+fn pac_lookup(_pac_url: &str, _url: &str) -> Result<Option<String>, String> {
+    Err("PAC is only supported on Windows".to_string())
+}
+
+/// Spells out certificate failures, which reqwest reports as a bare
+/// connection error.
+pub fn describe_error(e: &reqwest::Error) -> String {
+    let mut source: Option<&dyn std::error::Error> = Some(e);
+    while let Some(inner) = source {
+        let text = inner.to_string();
+        if text.to_lowercase().contains("certificate") || text.contains("sertifika") {
+            return format!(
+                "Portal sertifikası doğrulanamadı ({}). Sertifika kendinden imzalı veya süresi \
+                 dolmuşsa profilin TLS ayarlarından parmak izi sabitleyebilir ya da CA dosyası \
+                 ekleyebilirsiniz.",
+                text
+            );
+        }
+        source = inner.source();
+    }
+    e.to_string()
+}
+
+/// The updater runs its own client, so the frontend passes this along to `check`.
+#[tauri::command]
+pub async fn get_updater_proxy(app: AppHandle) -> Option<String> {
+    let endpoint = app
+        .config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|u| u["endpoints"][0].as_str())
+        .unwrap_or("https://github.com/")
+        .to_string();
+    tokio::task::spawn_blocking(move || app.state::<AppState>().http.proxy_for(&endpoint))
+        .await
+        .ok()
+        .flatten()
+}
+
+fn cookie_path() -> Option<PathBuf> {
+    config::app_data_dir().map(|dir| dir.join(COOKIE_FILE))
+}
+
+fn load_cookies() -> reqwest_cookie_store::CookieStore {
+    let loaded = cookie_path()
+        .and_then(|path| std::fs::File::open(path).ok())
+        .map(|file| cookie_store::serde::json::load(BufReader::new(file)));
+    match loaded {
+        Some(Ok(store)) => store,
+        Some(Err(e)) => {
+            warn!("Could not read saved cookies, starting empty: {}", e);
+            Default::default()
+        }
+        None => Default::default(),
+    }
+}
+
+/// Client for portal traffic. The cookie jar outlives the client, so
+/// reconfiguring keeps the portal session and logout can send it back.
+pub struct HttpClient {
+    cookies: Arc<CookieStoreMutex>,
+    client: Mutex<reqwest::Client>,
+    settings: Mutex<HttpSettings>,
+    /// Followed by the resolver and the TLS setup of every client built here.
+    active: ActiveProfile,
+    /// Portal TLS exceptions the client was built with, so a profile switch
+    /// is noticed on the next request.
+    tls: Mutex<Option<tls::Scope>>,
+    /// Shared by every client built here so PAC answers are looked up once.
+    pac: Mutex<Arc<Pac>>,
+}
+
+impl HttpClient {
+    pub fn new(settings: &HttpSettings, active: ActiveProfile) -> Self {
+        let cookies = Arc::new(CookieStoreMutex::new(load_cookies()));
+        let scope = tls::active_scope(active.lock().ok().and_then(|p| p.clone()).as_ref());
+        let pac = Arc::new(Pac::new(settings.proxy.pac_url.trim()));
+        let (client, _) = Self::build(settings, &cookies, &active, &pac, scope.as_ref());
+        Self {
+            cookies,
+            client: Mutex::new(client),
+            settings: Mutex::new(settings.clone()),
+            active,
+            tls: Mutex::new(scope),
+            pac: Mutex::new(pac),
+        }
+    }
+
+    /// A client for when the app isn't running (CLI): the stored settings
+    /// and the stored profile the app would pick for the current network.
+    pub fn standalone() -> Self {
+        let profile = profiles::stored_match();
+        Self::new(&stored_settings(), Arc::new(Mutex::new(profile)))
+    }
+
+    pub fn settings(&self) -> HttpSettings {
+        self.settings.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    pub fn active_profile(&self) -> Option<NetworkProfile> {
+        self.active.lock().ok().and_then(|p| p.clone())
+    }
+
+    fn pac(&self) -> Arc<Pac> {
+        self.pac
+            .lock()
+            .map(|pac| pac.clone())
+            .unwrap_or_else(|_| Arc::new(Pac::new(self.settings().proxy.pac_url.trim())))
+    }
+
+    /// A separate client with the shared settings but no cookies, for
+    /// probes and speed tests.
+    pub fn builder(&self) -> reqwest::ClientBuilder {
+        base_builder(&self.settings(), &self.active, &self.pac())
+    }
+
+    /// The proxy the updater should use for `url`. `None` leaves it on the
+    /// system proxy; the updater has no way to be told to go direct. Blocks
+    /// on a PAC lookup.
+    pub fn proxy_for(&self, url: &str) -> Option<String> {
+        let settings = self.settings().proxy;
+        match settings.mode {
+            ProxyMode::System | ProxyMode::None => None,
+            ProxyMode::Manual => Some(settings.url.trim().to_string()).filter(|u| !u.is_empty()),
+            ProxyMode::Pac => reqwest::Url::parse(url)
+                .ok()
+                .and_then(|url| self.pac().proxy_for(&url)),
+        }
+    }
+
+    /// Falling back to the default verification only ever rejects more, so
+    /// broken TLS exceptions still leave a working client; the error is
+    /// returned next to it for the UI.
+    fn build(
+        settings: &HttpSettings,
+        cookies: &Arc<CookieStoreMutex>,
+        active: &ActiveProfile,
+        pac: &Arc<Pac>,
+        scope: Option<&tls::Scope>,
+    ) -> (reqwest::Client, Result<(), String>) {
+        let mut builder = base_builder(settings, active, pac);
+        let mut tls_result = Ok(());
+        if let Some(scope) = scope {
+            match tls::client_config(scope) {
+                Ok(config) => {
+                    info!("Using TLS exceptions for portal {}", scope.portal_host);
+                    builder = builder.use_preconfigured_tls(config);
+                }
+                Err(e) => {
+                    warn!("Portal TLS settings ignored: {}", e);
+                    tls_result = Err(format!("Portal TLS ayarları uygulanamadı: {}", e));
+                }
+            }
+        }
+        let client = builder
+            .timeout(Duration::from_secs(settings.timeout_secs.max(1)))
+            .cookie_provider(cookies.clone())
+            .redirect(reqwest::redirect::Policy::limited(10))
+            .build()
+            .unwrap_or_else(|e| {
+                warn!("HTTP client setup failed, using defaults: {}", e);
+                reqwest::Client::builder()
+                    .cookie_provider(cookies.clone())
+                    .build()
+                    .unwrap_or_default()
+            });
+        (client, tls_result)
+    }
+
+    pub fn client(&self) -> reqwest::Client {
+        let scope = tls::active_scope(self.active_profile().as_ref());
+        let stale = self
+            .tls
+            .lock()
+            .map(|built| *built != scope)
+            .unwrap_or(false);
+        if stale {
+            // Already logged; the request goes out with default verification
+            let _ = self.configure(&self.settings());
+        }
+        self.client.lock().map(|c| c.clone()).unwrap_or_default()
+    }
+
+    /// Rebuilds the client; an error means the active profile's TLS
+    /// exceptions couldn't be applied.
+    pub fn configure(&self, settings: &HttpSettings) -> Result<(), String> {
+        if let Ok(mut current) = self.settings.lock() {
+            *current = settings.clone();
+        }
+        if let Ok(mut pac) = self.pac.lock() {
+            // A new script may answer differently
+            if pac.url != settings.proxy.pac_url.trim() {
+                *pac = Arc::new(Pac::new(settings.proxy.pac_url.trim()));
+            }
+        }
+        let scope = tls::active_scope(self.active_profile().as_ref());
+        let (client, tls_result) = Self::build(
+            settings,
+            &self.cookies,
+            &self.active,
+            &self.pac(),
+            scope.as_ref(),
+        );
+        if let Ok(mut current) = self.client.lock() {
+            *current = client;
+        }
+        if let Ok(mut built) = self.tls.lock() {
+            *built = scope;
+        }
+        tls_result
+    }
+
+    /// Session cookies are kept too; the portal rarely sets an expiry.
+    pub fn save_cookies(&self) {
+        let Some(path) = cookie_path() else {
+            return;
+        };
+        let result = (|| -> Result<(), String> {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            let store = self.cookies.lock().map_err(|e| e.to_string())?;
+            let mut file = std::fs::File::create(&path).map_err(|e| e.to_string())?;
+            cookie_store::serde::json::save_incl_expired_and_nonpersistent(&store, &mut file)
+                .map_err(|e| e.to_string())
+        })();
+        match result {
+            Ok(()) => info!("Cookies saved to {:?}", path),
+            Err(e) => warn!("Could not save cookies: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static LOOKUPS: AtomicUsize = AtomicUsize::new(0);
+
+    // Fails the first time, then answers with a proxy
+    fn flaky_lookup(_pac_url: &str, _url: &str) -> Result<Option<String>, String> {
+        match LOOKUPS.fetch_add(1, Ordering::SeqCst) {
+            0 => Err("script download failed".to_string()),
+            _ => Ok(Some("http://proxy.local:8080".to_string())),
+        }
+    }
+
+    fn expire_all(pac: &Pac) {
+        for entry in pac.cache.lock().unwrap().values_mut() {
+            entry.expires = Some(Instant::now());
+        }
+    }
+
+    fn wait_until_idle(pac: &Pac) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while pac.cache.lock().unwrap().values().any(|e| e.refreshing) {
+            assert!(Instant::now() < deadline, "PAC refresh never finished");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn pac_failures_are_retried_and_answers_refreshed() {
+        let pac = Arc::new(Pac {
+            lookup: flaky_lookup,
+            ..Pac::new("http://wpad/wpad.dat")
+        });
+        let url = reqwest::Url::parse("https://hotspot.example.com/login").unwrap();
+
+        // The first lookup fails: direct, but only until the retry
+        assert_eq!(pac.proxy_for(&url), None);
+        assert_eq!(pac.proxy_for(&url), None);
+        assert_eq!(LOOKUPS.load(Ordering::SeqCst), 1);
+
+        expire_all(&pac);
+        // The stale answer is served while the refresh runs
+        assert_eq!(pac.proxy_for(&url), None);
+        wait_until_idle(&pac);
+        assert_eq!(
+            pac.proxy_for(&url).as_deref(),
+            Some("http://proxy.local:8080")
+        );
+        assert_eq!(LOOKUPS.load(Ordering::SeqCst), 2);
+
+        // A request from inside the runtime doesn't stall it
+        let other = reqwest::Url::parse("https://example.org/").unwrap();
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .build()
+            .unwrap();
+        let proxy = runtime.block_on(async {
+            let pac = pac.clone();
+            tokio::spawn(async move { pac.proxy_for(&other) })
+                .await
+                .unwrap()
+        });
+        assert_eq!(proxy.as_deref(), Some("http://proxy.local:8080"));
+    }
+}
@@ -0,0 +1,370 @@
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr};
+
+use super::local_api::HOTSPOT_SUBNET;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkInterface {
+    pub name: String,
+    pub description: String,
+    pub mac: Option<String>,
+    pub ipv4: Vec<String>,
+    pub ipv6: Vec<String>,
+    pub gateways: Vec<String>,
+    pub dns_servers: Vec<String>,
+    pub mtu: Option<u32>,
+    pub link_speed_mbps: Option<u64>,
+    pub up: bool,
+    pub is_hotspot: bool,
+    pub is_virtual: bool,
+}
+
+impl NetworkInterface {
+    fn add_address(&mut self, ip: IpAddr) {
+        match ip {
+            IpAddr::V4(v4) => {
+                if v4.octets()[..3] == HOTSPOT_SUBNET {
+                    self.is_hotspot = true;
+                }
+                self.ipv4.push(v4.to_string());
+            }
+            IpAddr::V6(v6) => self.ipv6.push(v6.to_string()),
+        }
+    }
+}
+
+const VIRTUAL_MARKERS: &[&str] = &[
+    "virtual",
+    "hyper-v",
+    "vmware",
+    "virtualbox",
+    "tap-",
+    "wireguard",
+    "tailscale",
+    "zerotier",
+    "loopback",
+    "docker",
+    "veth",
+];
+
+fn looks_virtual(text: &str) -> bool {
+    let text = text.to_lowercase();
+    VIRTUAL_MARKERS.iter().any(|m| text.contains(m))
+}
+
+fn format_mac(bytes: &[u8]) -> Option<String> {
+    if bytes.is_empty() || bytes.iter().all(|b| *b == 0) {
+        return None;
+    }
+    Some(
+        bytes
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(":"),
+    )
+}
+
+/// The address other code reports as "the" local IP: an IPv4 on an adapter
+/// that is up and isn't the hotspot. Adapters with a gateway win, then
+/// physical ones over virtual ones, so a VPN-only machine still has one.
+pub fn primary_ipv4(interfaces: &[NetworkInterface]) -> Option<String> {
+    interfaces
+        .iter()
+        .filter(|i| i.up && !i.is_hotspot && !i.ipv4.is_empty())
+        .max_by_key(|i| (!i.gateways.is_empty(), !i.is_virtual))
+        .and_then(|i| i.ipv4.first().cloned())
+}
+
+pub fn local_ip() -> String {
+    list()
+        .ok()
+        .and_then(|interfaces| primary_ipv4(&interfaces))
+        .unwrap_or_else(|| "Bilinmiyor".to_string())
+}
+
+#[tauri::command]
+pub async fn get_interfaces() -> Result<Vec<NetworkInterface>, String> {
+    tokio::task::spawn_blocking(list)
+        .await
+        .map_err(|e| format!("Task hatası: {}", e))?
+}
+
+#[cfg(windows)]
+pub fn list() -> Result<Vec<NetworkInterface>, String> {
+    use windows::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, ERROR_NO_DATA, ERROR_SUCCESS};
+    use windows::Win32::NetworkManagement::IpHelper::{
+        GetAdaptersAddresses, GAA_FLAG_INCLUDE_GATEWAYS, GAA_FLAG_SKIP_ANYCAST,
+        GAA_FLAG_SKIP_MULTICAST, IF_TYPE_SOFTWARE_LOOPBACK, IP_ADAPTER_ADDRESSES_LH,
+    };
+    use windows::Win32::NetworkManagement::Ndis::IfOperStatusUp;
+    use windows::Win32::Networking::WinSock::AF_UNSPEC;
+
+    const IF_TYPE_TUNNEL: u32 = 131;
+
+    let flags = GAA_FLAG_INCLUDE_GATEWAYS | GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST;
+    let mut size: u32 = 16 * 1024;
+    // u64 keeps the buffer aligned for the adapter structs
+    let mut buffer: Vec<u64> = Vec::new();
+
+    for _ in 0..3 {
+        buffer.resize((size as usize).div_ceil(8), 0);
+        let first = buffer.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES_LH;
+        let code = unsafe {
+            GetAdaptersAddresses(AF_UNSPEC.0 as u32, flags, None, Some(first), &mut size)
+        };
+
+        if code == ERROR_NO_DATA.0 {
+            return Ok(Vec::new());
+        }
+        if code == ERROR_BUFFER_OVERFLOW.0 {
+            continue;
+        }
+        if code != ERROR_SUCCESS.0 {
+            return Err(format!("GetAdaptersAddresses başarısız: {}", code));
+        }
+
+        let mut interfaces = Vec::new();
+        let mut adapter = first;
+        while let Some(a) = unsafe { adapter.as_ref() } {
+            adapter = a.Next;
+            if a.IfType == IF_TYPE_SOFTWARE_LOOPBACK {
+                continue;
+            }
+
+            let description = unsafe { a.Description.to_string() }.unwrap_or_default();
+            let mut iface = NetworkInterface {
+                name: unsafe { a.FriendlyName.to_string() }.unwrap_or_default(),
+                mac: format_mac(&a.PhysicalAddress[..(a.PhysicalAddressLength as usize).min(8)]),
+                mtu: (a.Mtu != u32::MAX).then_some(a.Mtu),
+                link_speed_mbps: (a.TransmitLinkSpeed != u64::MAX && a.TransmitLinkSpeed > 0)
+                    .then_some(a.TransmitLinkSpeed / 1_000_000),
+                up: a.OperStatus == IfOperStatusUp,
+                is_hotspot: description.contains("Wi-Fi Direct Virtual Adapter"),
+                is_virtual: a.IfType == IF_TYPE_TUNNEL || looks_virtual(&description),
+                description,
+                ..Default::default()
+            };
+
+            unsafe {
+                let mut unicast = a.FirstUnicastAddress;
+                while let Some(u) = unicast.as_ref() {
+                    if let Some(ip) = socket_ip(&u.Address) {
+                        iface.add_address(ip);
+                    }
+                    unicast = u.Next;
+                }
+                let mut gateway = a.FirstGatewayAddress;
+                while let Some(g) = gateway.as_ref() {
+                    if let Some(ip) = socket_ip(&g.Address) {
+                        iface.gateways.push(ip.to_string());
+                    }
+                    gateway = g.Next;
+                }
+                let mut dns = a.FirstDnsServerAddress;
+                while let Some(d) = dns.as_ref() {
+                    if let Some(ip) = socket_ip(&d.Address) {
+                        iface.dns_servers.push(ip.to_string());
+                    }
+                    dns = d.Next;
+                }
+            }
+            interfaces.push(iface);
+        }
+        return Ok(interfaces);
+    }
+    Err("GetAdaptersAddresses için arabellek yetersiz".to_string())
+}
+
+#[cfg(windows)]
+unsafe fn socket_ip(
+    address: &windows::Win32::Networking::WinSock::SOCKET_ADDRESS,
+) -> Option<IpAddr> {
+    use std::net::Ipv6Addr;
+    use windows::Win32::Networking::WinSock::{AF_INET, AF_INET6, SOCKADDR_IN, SOCKADDR_IN6};
+
+    let sockaddr = address.lpSockaddr;
+    if sockaddr.is_null() {
+        return None;
+    }
+    let family = (*sockaddr).sa_family;
+    if family == AF_INET {
+        let v4 = &*(sockaddr as *const SOCKADDR_IN);
+        Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(
+            v4.sin_addr.S_un.S_addr,
+        ))))
+    } else if family == AF_INET6 {
+        let v6 = &*(sockaddr as *const SOCKADDR_IN6);
+        Some(IpAddr::V6(Ipv6Addr::from(v6.sin6_addr.u.Byte)))
+    } else {
+        None
+    }
+}
+
+#[cfg(not(windows))]
+pub fn list() -> Result<Vec<NetworkInterface>, String> {
+    use std::fs;
+
+    let addresses = local_ip_address::list_afinet_netifas().map_err(|e| e.to_string())?;
+    let gateways = linux_gateways();
+    // resolv.conf is system-wide; report it on every adapter
+    let dns_servers: Vec<String> = fs::read_to_string("/etc/resolv.conf")
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("nameserver"))
+        .map(|s| s.trim().to_string())
+        .collect();
+
+    let entries = fs::read_dir("/sys/class/net").map_err(|e| e.to_string())?;
+    let mut interfaces = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name == "lo" {
+            continue;
+        }
+        let dir = entry.path();
+        let read = |file: &str| {
+            fs::read_to_string(dir.join(file))
+                .map(|s| s.trim().to_string())
+                .ok()
+        };
+
+        let mut iface = NetworkInterface {
+            description: name.clone(),
+            mac: read("address").and_then(|mac| {
+                let bytes: Vec<u8> = mac
+                    .split(':')
+                    .filter_map(|b| u8::from_str_radix(b, 16).ok())
+                    .collect();
+                format_mac(&bytes)
+            }),
+            mtu: read("mtu").and_then(|v| v.parse().ok()),
+            link_speed_mbps: read("speed")
+                .and_then(|v| v.parse::<i64>().ok())
+                .filter(|v| *v > 0)
+                .map(|v| v as u64),
+            up: read("operstate").as_deref() == Some("up"),
+            is_virtual: !dir.join("device").exists() || looks_virtual(&name),
+            gateways: gateways
+                .iter()
+                .filter(|(iface, _)| *iface == name)
+                .map(|(_, gw)| gw.to_string())
+                .collect(),
+            dns_servers: dns_servers.clone(),
+            name,
+            ..Default::default()
+        };
+        let ips: Vec<IpAddr> = addresses
+            .iter()
+            .filter(|(n, _)| *n == iface.name)
+            .map(|(_, ip)| *ip)
+            .collect();
+        for ip in ips {
+            iface.add_address(ip);
+        }
+        interfaces.push(iface);
+    }
+    interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(interfaces)
+}
+
+#[cfg(not(windows))]
+fn linux_gateways() -> Vec<(String, Ipv4Addr)> {
+    parse_routes(&std::fs::read_to_string("/proc/net/route").unwrap_or_default())
+}
+
+/// Default routes from /proc/net/route, which prints addresses as native-endian hex.
+#[cfg(any(not(windows), test))]
+fn parse_routes(text: &str) -> Vec<(String, Ipv4Addr)> {
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if cols.len() < 3 || cols[1] != "00000000" {
+                return None;
+            }
+            let raw = u32::from_str_radix(cols[2], 16).ok()?;
+            Some((cols[0].to_string(), Ipv4Addr::from(raw.to_ne_bytes())))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iface(name: &str, ipv4: &str, gateway: bool, is_virtual: bool) -> NetworkInterface {
+        let mut iface = NetworkInterface {
+            name: name.to_string(),
+            up: true,
+            is_virtual,
+            ..Default::default()
+        };
+        if gateway {
+            iface.gateways.push("10.0.0.1".to_string());
+        }
+        iface.add_address(ipv4.parse().unwrap());
+        iface
+    }
+
+    #[test]
+    fn primary_prefers_gateway_then_physical() {
+        let vpn = iface("wg0", "10.8.0.2", true, true);
+        let wifi = iface("Wi-Fi", "192.168.1.20", true, false);
+        let lan = iface("Ethernet", "169.254.3.4", false, false);
+
+        let all = [vpn.clone(), wifi, lan.clone()];
+        assert_eq!(primary_ipv4(&all).as_deref(), Some("192.168.1.20"));
+        assert_eq!(primary_ipv4(&[lan.clone(), vpn]).as_deref(), Some("10.8.0.2"));
+        assert_eq!(primary_ipv4(&[lan]).as_deref(), Some("169.254.3.4"));
+    }
+
+    #[test]
+    fn primary_skips_down_and_hotspot_adapters() {
+        let mut down = iface("Wi-Fi", "192.168.1.20", true, false);
+        down.up = false;
+        let hotspot = iface("Local Area Connection* 2", "192.168.137.1", true, false);
+        assert!(hotspot.is_hotspot);
+
+        assert_eq!(primary_ipv4(&[down, hotspot]), None);
+        assert_eq!(primary_ipv4(&[]), None);
+    }
+
+    #[test]
+    fn virtual_markers() {
+        assert!(looks_virtual("Hyper-V Virtual Ethernet Adapter"));
+        assert!(looks_virtual("TAP-Windows Adapter V9"));
+        assert!(looks_virtual("docker0"));
+        assert!(!looks_virtual("Intel(R) Wi-Fi 6 AX201 160MHz"));
+        assert!(!looks_virtual("wlp2s0"));
+    }
+
+    #[test]
+    fn mac_formatting() {
+        assert_eq!(
+            format_mac(&[0x00, 0x1a, 0x2b, 0x3c, 0x4d, 0x5e]).as_deref(),
+            Some("00:1A:2B:3C:4D:5E")
+        );
+        assert_eq!(format_mac(&[0; 6]), None);
+        assert_eq!(format_mac(&[]), None);
+    }
+
+    #[test]
+    fn default_routes() {
+        // The kernel prints the address bytes in host order
+        let gateway = format!("{:08X}", u32::from_ne_bytes([192, 168, 1, 1]));
+        let table = format!(
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\n\
+             wlp2s0\t00000000\t{}\t0003\t0\t0\t600\t00000000\n\
+             wlp2s0\t0001A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\n\
+             broken\t00000000\n\
+             eth0\t00000000\tnothex\t0003\t0\t0\t100\t00000000\n",
+            gateway
+        );
+        assert_eq!(
+            parse_routes(&table),
+            vec![("wlp2s0".to_string(), Ipv4Addr::new(192, 168, 1, 1))]
+        );
+    }
+}
@@ -0,0 +1,558 @@
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+#[cfg(windows)]
+use {
+    super::launcher_service::same_path,
+    super::{auth, network, profiles},
+    crate::AppState,
+    log::{error, info, warn},
+    std::path::{Path, PathBuf},
+    std::time::Duration,
+    tauri::Manager,
+    windows::Win32::Foundation::{CloseHandle, LocalFree, HANDLE, HLOCAL},
+};
+
+/// Bumped whenever a request or response variant is added or changed.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+#[cfg(windows)]
+const RECONNECT_ATTEMPTS: u32 = 3;
+#[cfg(windows)]
+const RECONNECT_RETRY_DELAY: Duration = Duration::from_secs(5);
+#[cfg(windows)]
+const CANCEL_RETRY: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope<T> {
+    pub version: u32,
+    pub id: u64,
+    pub payload: T,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum IpcRequest {
+    Health,
+    Relogin,
+    Reconnect,
+    Restart,
+    Shutdown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum IpcResponse {
+    #[serde(rename_all = "camelCase")]
+    Health {
+        pid: u32,
+        app_version: String,
+        connected: bool,
+    },
+    Ack,
+    Error {
+        message: String,
+    },
+}
+
+#[cfg(windows)]
+pub fn current_session_id() -> u32 {
+    use windows::Win32::System::RemoteDesktop::ProcessIdToSessionId;
+    let mut session_id = 0u32;
+    unsafe {
+        let _ = ProcessIdToSessionId(std::process::id(), &mut session_id);
+    }
+    session_id
+}
+
+#[cfg(windows)]
+pub fn endpoint(session_id: u32) -> String {
+    format!(r"\\.\pipe\hotspot-manager-{}", session_id)
+}
+
+#[cfg(windows)]
+pub fn start_server(app: AppHandle) {
+    let name = endpoint(current_session_id());
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = serve(app, &name).await {
+            error!("IPC server on {} stopped: {}", name, e);
+        }
+    });
+}
+
+/// The launcher service is the only client, and it only exists on Windows.
+#[cfg(not(windows))]
+pub fn start_server(_app: AppHandle) {}
+
+#[cfg(windows)]
+async fn serve(app: AppHandle, name: &str) -> std::io::Result<()> {
+    let mut server = create_pipe(name, true)?;
+    info!("IPC server listening on {}", name);
+
+    loop {
+        server.connect().await?;
+        let client = server;
+        server = create_pipe(name, false)?;
+
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = handle_client(&app, client).await {
+                warn!("IPC client failed: {}", e);
+            }
+        });
+    }
+}
+
+/// Creates a pipe instance only SYSTEM (the launcher service) and the user
+/// running the app can open; the default DACL lets Everyone read it.
+#[cfg(windows)]
+fn create_pipe(
+    name: &str,
+    first: bool,
+) -> std::io::Result<tokio::net::windows::named_pipe::NamedPipeServer> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+    use windows::core::PCWSTR;
+    use windows::Win32::Security::Authorization::{
+        ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+    };
+    use windows::Win32::Security::{PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES};
+
+    let sddl = format!("D:P(A;;GA;;;SY)(A;;GA;;;{})", current_user_sid()?);
+    let sddl: Vec<u16> = sddl.encode_utf16().chain(Some(0)).collect();
+    let mut descriptor = PSECURITY_DESCRIPTOR::default();
+    unsafe {
+        ConvertStringSecurityDescriptorToSecurityDescriptorW(
+            PCWSTR(sddl.as_ptr()),
+            SDDL_REVISION_1,
+            &mut descriptor,
+            None,
+        )
+        .map_err(std::io::Error::other)?;
+
+        let mut attributes = SECURITY_ATTRIBUTES {
+            nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+            lpSecurityDescriptor: descriptor.0,
+            bInheritHandle: false.into(),
+        };
+        let server = ServerOptions::new()
+            .first_pipe_instance(first)
+            .create_with_security_attributes_raw(name, &mut attributes as *mut _ as *mut _);
+        let _ = LocalFree(Some(HLOCAL(descriptor.0)));
+        server
+    }
+}
+
+#[cfg(windows)]
+fn current_user_sid() -> std::io::Result<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Security::Authorization::ConvertSidToStringSidW;
+    use windows::Win32::Security::{GetTokenInformation, TokenUser, TOKEN_QUERY, TOKEN_USER};
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    unsafe {
+        let mut token = HANDLE::default();
+        OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token)
+            .map_err(std::io::Error::other)?;
+        let mut len = 0u32;
+        let _ = GetTokenInformation(token, TokenUser, None, 0, &mut len);
+        // u64s keep the SID pointer inside TOKEN_USER aligned
+        let mut buffer = vec![0u64; (len as usize).div_ceil(8)];
+        let result = GetTokenInformation(
+            token,
+            TokenUser,
+            Some(buffer.as_mut_ptr().cast()),
+            len,
+            &mut len,
+        );
+        let _ = CloseHandle(token);
+        result.map_err(std::io::Error::other)?;
+
+        let user = &*(buffer.as_ptr() as *const TOKEN_USER);
+        let mut sid = PWSTR::null();
+        ConvertSidToStringSidW(user.User.Sid, &mut sid).map_err(std::io::Error::other)?;
+        let text = sid.to_string();
+        let _ = LocalFree(Some(HLOCAL(sid.0.cast())));
+        text.map_err(std::io::Error::other)
+    }
+}
+
+#[cfg(windows)]
+async fn handle_client<S>(app: &AppHandle, stream: S) -> std::io::Result<()>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = tokio::io::BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        let (id, response) = match parse_request(&line) {
+            Ok((id, request)) => {
+                info!("IPC request {}: {:?}", id, request);
+                (id, dispatch(app, request).await)
+            }
+            Err(rejected) => rejected,
+        };
+        writer.write_all(encode(id, &response).as_bytes()).await?;
+        writer.flush().await?;
+    }
+
+    Ok(())
+}
+
+/// One envelope per line.
+#[cfg(any(windows, test))]
+fn encode<T: Serialize>(id: u64, payload: T) -> String {
+    let envelope = Envelope {
+        version: PROTOCOL_VERSION,
+        id,
+        payload,
+    };
+    let mut line = serde_json::to_string(&envelope).unwrap_or_default();
+    line.push('\n');
+    line
+}
+
+/// The version is checked before the payload, so a request this version
+/// doesn't know yet is reported as a mismatch. A rejected request comes back
+/// with the error reply to send.
+#[cfg(any(windows, test))]
+fn parse_request(line: &str) -> Result<(u64, IpcRequest), (u64, IpcResponse)> {
+    let malformed = |e: serde_json::Error| IpcResponse::Error {
+        message: format!("Malformed request: {}", e),
+    };
+    let envelope =
+        serde_json::from_str::<Envelope<serde_json::Value>>(line).map_err(|e| (0, malformed(e)))?;
+    if envelope.version != PROTOCOL_VERSION {
+        return Err((
+            envelope.id,
+            IpcResponse::Error {
+                message: format!("Unsupported protocol version {}", envelope.version),
+            },
+        ));
+    }
+    serde_json::from_value(envelope.payload)
+        .map(|request| (envelope.id, request))
+        .map_err(|e| (envelope.id, malformed(e)))
+}
+
+#[cfg(any(windows, test))]
+fn parse_response(line: &str, id: u64) -> Result<IpcResponse, String> {
+    let envelope: Envelope<serde_json::Value> =
+        serde_json::from_str(line.trim()).map_err(|e| format!("Malformed response: {}", e))?;
+    if envelope.version != PROTOCOL_VERSION {
+        return Err(format!("Unsupported protocol version {}", envelope.version));
+    }
+    if envelope.id != id {
+        return Err(format!(
+            "Response to request {}, expected {}",
+            envelope.id, id
+        ));
+    }
+    serde_json::from_value(envelope.payload).map_err(|e| format!("Malformed response: {}", e))
+}
+
+#[cfg(windows)]
+async fn dispatch(app: &AppHandle, request: IpcRequest) -> IpcResponse {
+    match request {
+        IpcRequest::Health => IpcResponse::Health {
+            pid: std::process::id(),
+            app_version: app.package_info().version.to_string(),
+            connected: network::is_online(&app.state::<AppState>().http).await,
+        },
+        IpcRequest::Relogin => match unattended_login(app).await {
+            Some(Ok(_)) => IpcResponse::Ack,
+            Some(Err(message)) => IpcResponse::Error { message },
+            None => IpcResponse::Error {
+                message: "Automatic login is not allowed".to_string(),
+            },
+        },
+        IpcRequest::Reconnect => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move { reconnect(&app).await });
+            IpcResponse::Ack
+        }
+        IpcRequest::Restart => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(250)).await;
+                info!("Restart requested over IPC");
+                app.restart();
+            });
+            IpcResponse::Ack
+        }
+        IpcRequest::Shutdown => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(250)).await;
+                info!("Shutdown requested over IPC");
+                app.exit(0);
+            });
+            IpcResponse::Ack
+        }
+    }
+}
+
+/// Logs in for the active profile, or with the saved credentials when there
+/// is none. `None` when a manual logout or the profile rules that out.
+#[cfg(windows)]
+async fn unattended_login(app: &AppHandle) -> Option<Result<bool, String>> {
+    // Checked per call, a manual logout may land between retries
+    let profile = profiles::active_profile(app);
+    if !profiles::unattended_login_allowed(app, profile.as_ref()) {
+        return None;
+    }
+    Some(match &profile {
+        Some(profile) => profiles::login_for_profile(app, profile).await,
+        None => auth::login_with_saved_credentials(app).await,
+    })
+}
+
+#[cfg(windows)]
+async fn reconnect(app: &AppHandle) {
+    for attempt in 1..=RECONNECT_ATTEMPTS {
+        if network::is_online(&app.state::<AppState>().http).await {
+            info!("Reconnect: connection already up");
+            auth::publish_status(app, true);
+            return;
+        }
+        let Some(result) = unattended_login(app).await else {
+            info!("Reconnect: automatic login not allowed here, not logging in");
+            return;
+        };
+        match result {
+            Ok(_) => {
+                info!("Reconnect: relogin succeeded on attempt {}", attempt);
+                return;
+            }
+            Err(e) => warn!("Reconnect attempt {} failed: {}", attempt, e),
+        }
+        if attempt < RECONNECT_ATTEMPTS {
+            tokio::time::sleep(RECONNECT_RETRY_DELAY).await;
+        }
+    }
+    error!("Reconnect gave up after {} attempts", RECONNECT_ATTEMPTS);
+}
+
+/// Runs the exchange on a worker thread so a hung app can't block the caller
+/// past `timeout`. On timeout the worker's pending pipe read or write is
+/// cancelled rather than left blocked. Only a server running `app_exe` in
+/// `session_id` gets the request.
+#[cfg(windows)]
+pub fn send_request(
+    session_id: u32,
+    app_exe: &Path,
+    request: IpcRequest,
+    timeout: Duration,
+) -> Result<IpcResponse, String> {
+    use std::os::windows::io::AsRawHandle;
+    use std::sync::mpsc::{self, RecvTimeoutError};
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::System::IO::CancelSynchronousIo;
+
+    let name = endpoint(session_id);
+    let app_exe = app_exe.to_path_buf();
+    let (tx, rx) = mpsc::channel();
+    let worker = std::thread::spawn(move || {
+        let _ = tx.send(exchange(&name, session_id, &app_exe, request));
+    });
+
+    let result = match rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(_) => {
+            // The worker may sit between two calls when a cancel lands, so
+            // keep cancelling until it reports back
+            let thread = HANDLE(worker.as_raw_handle());
+            loop {
+                unsafe {
+                    let _ = CancelSynchronousIo(thread);
+                }
+                match rx.recv_timeout(CANCEL_RETRY) {
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    _ => break,
+                }
+            }
+            Err("IPC request timed out".to_string())
+        }
+    };
+    let _ = worker.join();
+    result
+}
+
+#[cfg(windows)]
+fn exchange(
+    name: &str,
+    session_id: u32,
+    app_exe: &Path,
+    request: IpcRequest,
+) -> Result<IpcResponse, String> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::System::Pipes::GetNamedPipeServerProcessId;
+
+    let stream = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(name)
+        .map_err(|e| format!("Could not open {}: {}", name, e))?;
+
+    // Pipe names are global, any process could have created this one first
+    let mut server_pid = 0u32;
+    unsafe { GetNamedPipeServerProcessId(HANDLE(stream.as_raw_handle()), &mut server_pid) }
+        .map_err(|e| format!("Could not identify the server of {}: {}", name, e))?;
+    verify_process(server_pid, session_id, app_exe)?;
+
+    let id = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    (&stream)
+        .write_all(encode(id, request).as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut reply)
+        .map_err(|e| e.to_string())?;
+    match parse_response(&reply, id)? {
+        IpcResponse::Health { pid, .. } if pid != server_pid => Err(format!(
+            "Health reply names PID {} but the pipe belongs to PID {}",
+            pid, server_pid
+        )),
+        response => Ok(response),
+    }
+}
+
+/// Checks that `pid` runs `app_exe` in `session_id`.
+#[cfg(windows)]
+pub fn verify_process(pid: u32, session_id: u32, app_exe: &Path) -> Result<(), String> {
+    use windows::Win32::System::RemoteDesktop::ProcessIdToSessionId;
+
+    let mut process_session = 0u32;
+    unsafe { ProcessIdToSessionId(pid, &mut process_session) }
+        .map_err(|e| format!("Could not get the session of PID {}: {}", pid, e))?;
+    if process_session != session_id {
+        return Err(format!(
+            "PID {} runs in session {}, expected {}",
+            pid, process_session, session_id
+        ));
+    }
+
+    let image = process_image(pid)?;
+    let canonical = |path: &Path| std::fs::canonicalize(path).unwrap_or_else(|_| path.into());
+    if !same_path(&canonical(&image), &canonical(app_exe)) {
+        return Err(format!(
+            "PID {} runs {}, expected {}",
+            pid,
+            image.display(),
+            app_exe.display()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn process_image(pid: u32) -> Result<PathBuf, String> {
+    use windows::core::PWSTR;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid)
+            .map_err(|e| format!("Could not open PID {}: {}", pid, e))?;
+        let mut buffer = [0u16; 1024];
+        let mut len = buffer.len() as u32;
+        let result = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut len,
+        );
+        let _ = CloseHandle(process);
+        result.map_err(|e| format!("Could not get the image of PID {}: {}", pid, e))?;
+        Ok(PathBuf::from(String::from_utf16_lossy(
+            &buffer[..len as usize],
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REQUESTS: [IpcRequest; 5] = [
+        IpcRequest::Health,
+        IpcRequest::Relogin,
+        IpcRequest::Reconnect,
+        IpcRequest::Restart,
+        IpcRequest::Shutdown,
+    ];
+
+    #[test]
+    fn requests_round_trip() {
+        for request in REQUESTS {
+            let line = encode(42, &request);
+            assert!(line.ends_with('\n') && !line.trim_end().contains('\n'));
+            assert_eq!(parse_request(line.trim_end()), Ok((42, request)));
+        }
+    }
+
+    #[test]
+    fn responses_round_trip() {
+        let health = IpcResponse::Health {
+            pid: 1234,
+            app_version: "0.5.54".to_string(),
+            connected: true,
+        };
+        assert_eq!(parse_response(&encode(7, &health), 7), Ok(health));
+        assert_eq!(
+            parse_response(&encode(7, IpcResponse::Ack), 7),
+            Ok(IpcResponse::Ack)
+        );
+    }
+
+    #[test]
+    fn rejects_other_versions_and_unknown_requests() {
+        let old = r#"{"version":1,"id":5,"payload":{"type":"health"}}"#;
+        assert_eq!(
+            parse_request(old),
+            Err((
+                5,
+                IpcResponse::Error {
+                    message: "Unsupported protocol version 1".to_string()
+                }
+            ))
+        );
+
+        let unknown = format!(
+            r#"{{"version":{},"id":6,"payload":{{"type":"selfDestruct"}}}}"#,
+            PROTOCOL_VERSION
+        );
+        assert!(matches!(
+            parse_request(&unknown),
+            Err((6, IpcResponse::Error { message })) if message.starts_with("Malformed request")
+        ));
+
+        assert!(matches!(
+            parse_request("not json"),
+            Err((0, IpcResponse::Error { message })) if message.starts_with("Malformed request")
+        ));
+    }
+
+    #[test]
+    fn rejects_mismatched_responses() {
+        assert_eq!(
+            parse_response(&encode(8, IpcResponse::Ack), 9),
+            Err("Response to request 8, expected 9".to_string())
+        );
+        assert_eq!(
+            parse_response(r#"{"version":1,"id":9,"payload":{"type":"ack"}}"#, 9),
+            Err("Unsupported protocol version 1".to_string())
+        );
+        assert!(parse_response("", 9)
+            .unwrap_err()
+            .starts_with("Malformed response"));
+    }
+}
@@ -0,0 +1,421 @@
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::net::TcpStream;
+
+use super::{dns, history, interfaces, portal, profiles};
+use crate::AppState;
+
+const WINDOW: usize = 120;
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Rolling RTT samples per target; `None` is a lost probe.
+#[derive(Default)]
+pub struct LatencyHistory {
+    targets: Vec<(String, VecDeque<Option<f64>>)>,
+}
+
+impl LatencyHistory {
+    fn record(&mut self, target: &str, rtt: Option<f64>) {
+        let index = match self.targets.iter().position(|(t, _)| t == target) {
+            Some(index) => index,
+            None => {
+                self.targets
+                    .push((target.to_string(), VecDeque::with_capacity(WINDOW)));
+                self.targets.len() - 1
+            }
+        };
+        let samples = &mut self.targets[index].1;
+        if samples.len() == WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(rtt);
+    }
+
+    fn retain(&mut self, targets: &[String]) {
+        self.targets.retain(|(t, _)| targets.contains(t));
+    }
+
+    pub fn stats(&self) -> Vec<LatencyStats> {
+        self.targets
+            .iter()
+            .map(|(target, samples)| LatencyStats::from_samples(target, samples))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyStats {
+    pub target: String,
+    pub last_ms: Option<f64>,
+    pub min_ms: Option<f64>,
+    pub avg_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    /// Mean difference between consecutive successful RTTs.
+    pub jitter_ms: Option<f64>,
+    pub loss_percent: f64,
+    pub samples: usize,
+}
+
+impl LatencyStats {
+    fn from_samples(target: &str, samples: &VecDeque<Option<f64>>) -> Self {
+        let rtts: Vec<f64> = samples.iter().flatten().copied().collect();
+        let lost = samples.len() - rtts.len();
+
+        let mut sorted = rtts.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let p95 = (!sorted.is_empty()).then(|| {
+            let rank = ((sorted.len() as f64) * 0.95).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1]
+        });
+        let jitter = (rtts.len() > 1).then(|| {
+            rtts.windows(2).map(|w| (w[1] - w[0]).abs()).sum::<f64>() / (rtts.len() - 1) as f64
+        });
+
+        Self {
+            target: target.to_string(),
+            last_ms: samples.back().copied().flatten(),
+            min_ms: sorted.first().copied(),
+            avg_ms: (!rtts.is_empty()).then(|| rtts.iter().sum::<f64>() / rtts.len() as f64),
+            p95_ms: p95,
+            jitter_ms: jitter,
+            loss_percent: if samples.is_empty() {
+                0.0
+            } else {
+                lost as f64 * 100.0 / samples.len() as f64
+            },
+            samples: samples.len(),
+        }
+    }
+}
+
+/// The stats the UI headlines as "ping": the first target that isn't the
+/// gateway or the portal.
+pub fn headline(stats: &[LatencyStats]) -> Option<&LatencyStats> {
+    stats
+        .iter()
+        .find(|s| s.target != "gateway" && s.target != "portal")
+        .or_else(|| stats.first())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Probe {
+    Tcp(String),
+    Icmp(String),
+}
+
+/// `host:port` probes with a TCP connect, `icmp:host` with an echo request.
+/// `gateway` and `portal` resolve to the current default gateway (ICMP) and
+/// the active profile's login portal (TCP).
+fn parse_target(target: &str, gateway: Option<&IpAddr>, portal: Option<&str>) -> Option<Probe> {
+    match target.trim() {
+        "gateway" => gateway.map(|ip| Probe::Icmp(ip.to_string())),
+        "portal" => portal.map(|addr| Probe::Tcp(addr.to_string())),
+        t => match t.strip_prefix("icmp:") {
+            Some(host) => Some(Probe::Icmp(host.to_string())),
+            None if t.contains(':') => Some(Probe::Tcp(t.to_string())),
+            None => Some(Probe::Tcp(format!("{}:443", t))),
+        },
+    }
+}
+
+/// `host:port` of a portal's status page; IPv6 hosts keep their brackets.
+fn portal_target(status_url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(status_url).ok()?;
+    Some(format!(
+        "{}:{}",
+        url.host_str()?,
+        url.port_or_known_default()?
+    ))
+}
+
+fn default_gateway() -> Option<IpAddr> {
+    interfaces::list()
+        .ok()?
+        .into_iter()
+        .filter(|i| i.up && !i.is_hotspot && !i.is_virtual)
+        .flat_map(|i| i.gateways)
+        .filter_map(|g| g.parse::<IpAddr>().ok())
+        .find(IpAddr::is_ipv4)
+}
+
+/// Host names go through the active profile's DNS servers, like the HTTP
+/// clients.
+async fn resolve(host: &str, dns_servers: &[String]) -> Option<IpAddr> {
+    tokio::time::timeout(PROBE_TIMEOUT, dns::resolve(dns_servers, host))
+        .await
+        .ok()?
+        .ok()?
+        .into_iter()
+        .next()
+}
+
+async fn run_probe(probe: Probe, dns_servers: Vec<String>) -> Option<f64> {
+    match probe {
+        Probe::Tcp(addr) => {
+            let resolved = match addr.parse::<SocketAddr>() {
+                Ok(resolved) => resolved,
+                Err(_) => {
+                    let (host, port) = addr.rsplit_once(':')?;
+                    SocketAddr::new(resolve(host, &dns_servers).await?, port.parse().ok()?)
+                }
+            };
+            let start = Instant::now();
+            tokio::time::timeout(PROBE_TIMEOUT, TcpStream::connect(resolved))
+                .await
+                .ok()?
+                .ok()?;
+            Some(start.elapsed().as_secs_f64() * 1000.0)
+        }
+        Probe::Icmp(host) => {
+            let ip = resolve(&host, &dns_servers).await?;
+            tokio::task::spawn_blocking(move || icmp_echo(ip))
+                .await
+                .ok()
+                .flatten()
+        }
+    }
+}
+
+#[cfg(windows)]
+fn icmp_echo(ip: IpAddr) -> Option<f64> {
+    use windows::Win32::NetworkManagement::IpHelper::{
+        IcmpCloseHandle, IcmpCreateFile, IcmpSendEcho, ICMP_ECHO_REPLY,
+    };
+
+    let IpAddr::V4(v4) = ip else {
+        return None;
+    };
+    let payload = [0u8; 32];
+    // Room for one reply plus the echoed payload and an ICMP error
+    let mut reply = vec![0u8; std::mem::size_of::<ICMP_ECHO_REPLY>() + payload.len() + 8];
+
+    unsafe {
+        let handle = IcmpCreateFile().ok()?;
+        let count = IcmpSendEcho(
+            handle,
+            u32::from_ne_bytes(v4.octets()),
+            payload.as_ptr() as *const _,
+            payload.len() as u16,
+            None,
+            reply.as_mut_ptr() as *mut _,
+            reply.len() as u32,
+            PROBE_TIMEOUT.as_millis() as u32,
+        );
+        let _ = IcmpCloseHandle(handle);
+        if count == 0 {
+            return None;
+        }
+        let echo = &*(reply.as_ptr() as *const ICMP_ECHO_REPLY);
+        // IP_SUCCESS
+        (echo.Status == 0).then_some(echo.RoundTripTime as f64)
+    }
+}
+
+/// Unprivileged raw sockets aren't available everywhere, so use ping(8).
+#[cfg(not(windows))]
+fn icmp_echo(ip: IpAddr) -> Option<f64> {
+    let output = std::process::Command::new("ping")
+        .args([
+            "-c",
+            "1",
+            "-W",
+            &PROBE_TIMEOUT.as_secs().to_string(),
+            &ip.to_string(),
+        ])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let (_, rest) = text.split_once("time=")?;
+    rest.split_whitespace().next()?.parse().ok()
+}
+
+pub fn start_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        info!("Latency monitor started");
+        let mut last_recorded: Option<Instant> = None;
+        loop {
+            let settings = app
+                .state::<AppState>()
+                .settings
+                .lock()
+                .map(|s| s.latency.clone())
+                .unwrap_or_default();
+            let interval = Duration::from_secs(settings.interval_secs.max(1));
+            if !settings.enabled || settings.targets.is_empty() {
+                tokio::time::sleep(interval).await;
+                continue;
+            }
+
+            let gateway = tokio::task::spawn_blocking(default_gateway)
+                .await
+                .unwrap_or(None);
+            let dns_servers = profiles::active_profile(&app)
+                .map(|p| p.dns_servers)
+                .unwrap_or_default();
+            // None at networks without a portal
+            let portal = portal::active_provider(&app.state::<AppState>().http)
+                .ok()
+                .and_then(|provider| portal_target(provider.status_url()));
+            let mut round = tokio::task::JoinSet::new();
+            for target in &settings.targets {
+                match parse_target(target, gateway.as_ref(), portal.as_deref()) {
+                    Some(probe) => {
+                        let (target, dns_servers) = (target.clone(), dns_servers.clone());
+                        round.spawn(async move { (target, run_probe(probe, dns_servers).await) });
+                    }
+                    None => warn!("Latency target {} can't be probed right now", target),
+                }
+            }
+            let mut results = Vec::new();
+            while let Some(Ok(result)) = round.join_next().await {
+                results.push(result);
+            }
+
+            let stats = match app.state::<AppState>().latency.lock() {
+                Ok(mut history) => {
+                    history.retain(&settings.targets);
+                    for (target, rtt) in results {
+                        history.record(&target, rtt);
+                    }
+                    history.stats()
+                }
+                Err(_) => Vec::new(),
+            };
+            let _ = app.emit("latency-update", &stats);
+
+            let due = last_recorded.is_none_or(|at| at.elapsed() >= history::LATENCY_SAMPLE_EVERY);
+            if let Some(headline) = headline(&stats).filter(|_| due).cloned() {
+                last_recorded = Some(Instant::now());
+                let app = app.clone();
+                tokio::task::spawn_blocking(move || {
+                    if let Err(e) = history::record_latency(&app, &headline) {
+                        warn!("Could not record latency history: {}", e);
+                    }
+                });
+            }
+
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_latency_stats(state: tauri::State<AppState>) -> Vec<LatencyStats> {
+    state
+        .latency
+        .lock()
+        .map(|history| history.stats())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(samples: &[Option<f64>]) -> LatencyStats {
+        LatencyStats::from_samples("1.1.1.1:443", &samples.iter().copied().collect())
+    }
+
+    #[test]
+    fn p95_uses_the_nearest_rank() {
+        let samples: Vec<_> = (1..=20).map(|ms| Some(ms as f64)).collect();
+        let s = stats(&samples);
+        assert_eq!(s.min_ms, Some(1.0));
+        assert_eq!(s.avg_ms, Some(10.5));
+        assert_eq!(s.p95_ms, Some(19.0));
+        assert_eq!(s.last_ms, Some(20.0));
+
+        assert_eq!(stats(&[Some(7.0)]).p95_ms, Some(7.0));
+        assert_eq!(
+            stats(&[Some(30.0), Some(10.0), Some(20.0)]).p95_ms,
+            Some(30.0)
+        );
+    }
+
+    #[test]
+    fn jitter_skips_lost_probes() {
+        let s = stats(&[Some(10.0), None, Some(14.0), Some(12.0), None]);
+        // |14 - 10| and |12 - 14|
+        assert_eq!(s.jitter_ms, Some(3.0));
+        assert_eq!(s.last_ms, None);
+        assert_eq!(stats(&[Some(10.0), None]).jitter_ms, None);
+    }
+
+    #[test]
+    fn loss_percent() {
+        let s = stats(&[Some(10.0), None, Some(12.0), None]);
+        assert_eq!(s.loss_percent, 50.0);
+        assert_eq!(s.samples, 4);
+
+        let lost = stats(&[None, None]);
+        assert_eq!(lost.loss_percent, 100.0);
+        assert_eq!(lost.min_ms, None);
+        assert_eq!(lost.avg_ms, None);
+        assert_eq!(lost.p95_ms, None);
+
+        assert_eq!(stats(&[]).loss_percent, 0.0);
+    }
+
+    #[test]
+    fn history_keeps_the_last_window() {
+        let mut history = LatencyHistory::default();
+        for ms in 0..WINDOW + 5 {
+            history.record("a", Some(ms as f64));
+        }
+        history.record("b", None);
+
+        let stats = history.stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].samples, WINDOW);
+        assert_eq!(stats[0].min_ms, Some(5.0));
+        assert_eq!(stats[0].last_ms, Some((WINDOW + 4) as f64));
+        assert_eq!(stats[1].loss_percent, 100.0);
+
+        history.retain(&["b".to_string()]);
+        assert_eq!(history.stats().len(), 1);
+    }
+
+    #[test]
+    fn targets() {
+        let gateway: IpAddr = "192.168.1.1".parse().unwrap();
+        let portal = Some("hotspot.example.com:443");
+        let parse = |target| parse_target(target, Some(&gateway), portal);
+
+        assert_eq!(parse("gateway"), Some(Probe::Icmp("192.168.1.1".into())));
+        assert_eq!(
+            parse(" portal "),
+            Some(Probe::Tcp("hotspot.example.com:443".into()))
+        );
+        assert_eq!(parse("icmp:1.1.1.1"), Some(Probe::Icmp("1.1.1.1".into())));
+        assert_eq!(parse("1.1.1.1:53"), Some(Probe::Tcp("1.1.1.1:53".into())));
+        assert_eq!(
+            parse("example.com"),
+            Some(Probe::Tcp("example.com:443".into()))
+        );
+
+        assert_eq!(parse_target("gateway", None, portal), None);
+        assert_eq!(parse_target("portal", Some(&gateway), None), None);
+    }
+
+    #[test]
+    fn portal_targets() {
+        assert_eq!(
+            portal_target("https://hotspot.maxxarena.de/").as_deref(),
+            Some("hotspot.maxxarena.de:443")
+        );
+        assert_eq!(
+            portal_target("http://10.0.0.1:8080/login").as_deref(),
+            Some("10.0.0.1:8080")
+        );
+        assert_eq!(
+            portal_target("http://[fe80::1]/").as_deref(),
+            Some("[fe80::1]:80")
+        );
+        assert_eq!(portal_target("not a url"), None);
+    }
+}
@@ -0,0 +1,604 @@
+use serde::{Deserialize, Serialize};
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use windows_service::{
+    service::{
+        Service, ServiceAccess, ServiceErrorControl, ServiceInfo, ServiceStartType, ServiceState,
+        ServiceType,
+    },
+    service_manager::{ServiceManager, ServiceManagerAccess},
+};
+
+pub const DEFAULT_SERVICE_NAME: &str = "HotspotLauncher";
+/// The app the launcher starts, installed next to it.
+pub const APP_EXE: &str = "hotspot.exe";
+const ERROR_SERVICE_DOES_NOT_EXIST: i32 = 1060;
+const STOP_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LauncherConfig {
+    pub service_name: String,
+    pub display_name: String,
+    pub exe_path: Option<PathBuf>,
+    pub args: Vec<String>,
+    pub start_delay_secs: u64,
+    pub restart: RestartPolicy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestartPolicy {
+    pub enabled: bool,
+    pub base_backoff_secs: u64,
+    pub max_backoff_secs: u64,
+    pub stable_runtime_secs: u64,
+    pub max_consecutive_crashes: u32,
+}
+
+impl Default for LauncherConfig {
+    fn default() -> Self {
+        Self {
+            service_name: DEFAULT_SERVICE_NAME.to_string(),
+            display_name: "Hotspot Manager Launcher".to_string(),
+            exe_path: None,
+            args: vec!["--minimized".to_string()],
+            start_delay_secs: 0,
+            restart: RestartPolicy::default(),
+        }
+    }
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            base_backoff_secs: 2,
+            max_backoff_secs: 300,
+            stable_runtime_secs: 600,
+            max_consecutive_crashes: 8,
+        }
+    }
+}
+
+impl LauncherConfig {
+    pub fn default_path() -> PathBuf {
+        let base = std::env::var_os("ProgramData")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"));
+        base.join("Hotspot Manager").join("launcher.toml")
+    }
+
+    /// Refuses a config users could have edited or one pointing at anything
+    /// but the installed app: the SYSTEM service starts it in every session.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Could not read {}: {}", path.display(), e)),
+        };
+        #[cfg(windows)]
+        {
+            if let Some(dir) = path.parent() {
+                acl::check(dir)?;
+            }
+            acl::check(path)?;
+        }
+
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| format!("Invalid config {}: {}", path.display(), e))?;
+        if let Some(exe) = &config.exe_path {
+            let installed = installed_app().ok_or("Could not resolve the installed app")?;
+            if !same_path(exe, &installed) {
+                return Err(format!(
+                    "exe_path {} is not the installed app {}",
+                    exe.display(),
+                    installed.display()
+                ));
+            }
+        }
+        Ok(config)
+    }
+
+    /// Writes the config into a folder only SYSTEM and Administrators can
+    /// change, so it needs an elevated caller.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            #[cfg(windows)]
+            acl::create_dir(dir)?;
+            #[cfg(not(windows))]
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let content = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, content)
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+        #[cfg(windows)]
+        acl::lock_down(path)?;
+        Ok(())
+    }
+}
+
+/// The app next to the running executable; the launcher and the app are
+/// installed into the same folder.
+pub fn installed_app() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    Some(exe.parent()?.join(APP_EXE))
+}
+
+/// Windows paths compare case-insensitively, with or without the `\\?\`
+/// prefix `canonicalize` adds.
+pub fn same_path(a: &Path, b: &Path) -> bool {
+    let normalize = |path: &Path| {
+        let path = path.to_string_lossy();
+        path.strip_prefix(r"\\?\")
+            .unwrap_or(&path)
+            .replace('/', "\\")
+            .to_lowercase()
+    };
+    normalize(a) == normalize(b)
+}
+
+/// Quotes `arg` the way `CommandLineToArgvW` and the MSVC runtime split it
+/// back: backslashes are literal unless they precede a quote, so only those
+/// runs (and the one before the closing quote) are doubled.
+pub fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '\n', '\u{b}', '"']) {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        if c == '\\' {
+            backslashes += 1;
+            continue;
+        }
+        let escaped = if c == '"' {
+            backslashes * 2 + 1
+        } else {
+            backslashes
+        };
+        quoted.push_str(&"\\".repeat(escaped));
+        quoted.push(c);
+        backslashes = 0;
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// `"exe" args…` as `CreateProcess` and the service manager expect it.
+pub fn command_line(exe: &Path, args: &[String]) -> String {
+    std::iter::once(format!("\"{}\"", exe.display()))
+        .chain(args.iter().map(|arg| quote_arg(arg)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceSummary {
+    pub state: String,
+    pub running: bool,
+    pub process_id: Option<u32>,
+    pub executable_path: PathBuf,
+}
+
+pub fn is_not_installed(error: &windows_service::Error) -> bool {
+    matches!(error, windows_service::Error::Winapi(e) if e.raw_os_error() == Some(ERROR_SERVICE_DOES_NOT_EXIST))
+}
+
+pub fn install(
+    config: &LauncherConfig,
+    launcher_exe: &Path,
+    config_path: &Path,
+) -> windows_service::Result<()> {
+    let manager = ServiceManager::local_computer(
+        None::<&str>,
+        ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE,
+    )?;
+
+    let info = ServiceInfo {
+        name: OsString::from(&config.service_name),
+        display_name: OsString::from(&config.display_name),
+        service_type: ServiceType::OWN_PROCESS,
+        start_type: ServiceStartType::AutoStart,
+        error_control: ServiceErrorControl::Normal,
+        executable_path: launcher_exe.to_path_buf(),
+        launch_arguments: vec![
+            OsString::from("run"),
+            OsString::from("--config"),
+            config_path.as_os_str().to_os_string(),
+        ],
+        dependencies: vec![],
+        account_name: None,
+        account_password: None,
+    };
+
+    let access = ServiceAccess::CHANGE_CONFIG
+        | ServiceAccess::START
+        | ServiceAccess::STOP
+        | ServiceAccess::QUERY_STATUS;
+    let service = match manager.open_service(&config.service_name, access) {
+        Ok(service) => {
+            service.change_config(&info)?;
+            // A running service keeps its old command line until restarted
+            stop_and_wait(&service)?;
+            service
+        }
+        Err(e) if is_not_installed(&e) => manager.create_service(&info, access)?,
+        Err(e) => return Err(e),
+    };
+
+    service.set_description("Starts Hotspot Manager in every interactive user session")?;
+
+    if service.query_status()?.current_state != ServiceState::Running {
+        service.start::<&OsStr>(&[])?;
+    }
+    Ok(())
+}
+
+pub fn uninstall(service_name: &str) -> windows_service::Result<bool> {
+    let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    let service = match manager.open_service(
+        service_name,
+        ServiceAccess::QUERY_STATUS | ServiceAccess::STOP | ServiceAccess::DELETE,
+    ) {
+        Ok(service) => service,
+        Err(e) if is_not_installed(&e) => return Ok(false),
+        Err(e) => return Err(e),
+    };
+
+    stop_and_wait(&service)?;
+    service.delete()?;
+    Ok(true)
+}
+
+fn stop_and_wait(service: &Service) -> windows_service::Result<()> {
+    if service.query_status()?.current_state == ServiceState::Stopped {
+        return Ok(());
+    }
+    let _ = service.stop();
+    let deadline = Instant::now() + STOP_TIMEOUT;
+    while Instant::now() < deadline {
+        if service.query_status()?.current_state == ServiceState::Stopped {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+    Err(windows_service::Error::Winapi(std::io::Error::new(
+        std::io::ErrorKind::TimedOut,
+        format!("Service did not stop within {} s", STOP_TIMEOUT.as_secs()),
+    )))
+}
+
+pub fn query(service_name: &str) -> windows_service::Result<Option<ServiceSummary>> {
+    let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    let service = match manager.open_service(
+        service_name,
+        ServiceAccess::QUERY_STATUS | ServiceAccess::QUERY_CONFIG,
+    ) {
+        Ok(service) => service,
+        Err(e) if is_not_installed(&e) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    let status = service.query_status()?;
+    let config = service.query_config()?;
+
+    Ok(Some(ServiceSummary {
+        state: format!("{:?}", status.current_state),
+        running: status.current_state == ServiceState::Running,
+        process_id: status.process_id,
+        executable_path: config.executable_path,
+    }))
+}
+
+/// Rights that let the holder change a file or folder, what's in it or who
+/// may access it.
+#[cfg(any(windows, test))]
+const WRITE_RIGHTS: u32 = 0x0000_0002 // FILE_WRITE_DATA, FILE_ADD_FILE
+    | 0x0000_0004 // FILE_APPEND_DATA, FILE_ADD_SUBDIRECTORY
+    | 0x0000_0010 // FILE_WRITE_EA
+    | 0x0000_0040 // FILE_DELETE_CHILD
+    | 0x0000_0100 // FILE_WRITE_ATTRIBUTES
+    | 0x0001_0000 // DELETE
+    | 0x0004_0000 // WRITE_DAC
+    | 0x0008_0000 // WRITE_OWNER
+    | 0x1000_0000 // GENERIC_ALL
+    | 0x4000_0000; // GENERIC_WRITE
+
+/// One entry of a DACL; `trusted` is SYSTEM or Administrators.
+#[cfg(any(windows, test))]
+#[derive(Debug, Clone, Copy)]
+struct AccessEntry {
+    allow: bool,
+    inherit_only: bool,
+    mask: u32,
+    trusted: bool,
+}
+
+/// Only SYSTEM and Administrators may own the object or be allowed to
+/// change it. A missing DACL grants everyone everything.
+#[cfg(any(windows, test))]
+fn locked_down(owner_trusted: bool, dacl: Option<&[AccessEntry]>) -> bool {
+    owner_trusted
+        && dacl.is_some_and(|entries| {
+            entries.iter().all(|entry| {
+                !entry.allow
+                    || entry.inherit_only
+                    || entry.trusted
+                    || entry.mask & WRITE_RIGHTS == 0
+            })
+        })
+}
+
+#[cfg(windows)]
+mod acl {
+    use super::{locked_down, AccessEntry};
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{LocalFree, ERROR_ALREADY_EXISTS, ERROR_SUCCESS, HLOCAL};
+    use windows::Win32::Security::Authorization::{
+        ConvertStringSecurityDescriptorToSecurityDescriptorW, GetNamedSecurityInfoW,
+        SetNamedSecurityInfoW, SDDL_REVISION_1, SE_FILE_OBJECT,
+    };
+    use windows::Win32::Security::{
+        GetAce, GetSecurityDescriptorDacl, GetSecurityDescriptorOwner, IsWellKnownSid,
+        WinBuiltinAdministratorsSid, WinLocalSystemSid, ACCESS_ALLOWED_ACE, ACE_HEADER, ACL,
+        DACL_SECURITY_INFORMATION, INHERIT_ONLY_ACE, OWNER_SECURITY_INFORMATION,
+        PROTECTED_DACL_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR, PSID, SECURITY_ATTRIBUTES,
+    };
+    use windows::Win32::Storage::FileSystem::CreateDirectoryW;
+
+    /// Owned by Administrators; SYSTEM and Administrators get full control,
+    /// users may only read (the app shows the service settings).
+    const SDDL: &str = "O:BAD:PAI(A;OICI;FA;;;SY)(A;OICI;FA;;;BA)(A;OICI;FRFX;;;BU)";
+    const ACCESS_ALLOWED_ACE_TYPE: u8 = 0;
+    const ACCESS_DENIED_ACE_TYPES: [u8; 4] = [1, 6, 10, 12];
+
+    fn wide(path: &Path) -> Vec<u16> {
+        path.as_os_str().encode_wide().chain(Some(0)).collect()
+    }
+
+    fn with_descriptor<T>(
+        f: impl FnOnce(PSECURITY_DESCRIPTOR) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let sddl: Vec<u16> = SDDL.encode_utf16().chain(Some(0)).collect();
+        let mut descriptor = PSECURITY_DESCRIPTOR::default();
+        unsafe {
+            ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                PCWSTR(sddl.as_ptr()),
+                SDDL_REVISION_1,
+                &mut descriptor,
+                None,
+            )
+            .map_err(|e| format!("Invalid security descriptor: {}", e))?;
+            let result = f(descriptor);
+            let _ = LocalFree(Some(HLOCAL(descriptor.0)));
+            result
+        }
+    }
+
+    /// Creates `dir` with the locked-down ACL, or applies it to a folder
+    /// that is already there.
+    pub fn create_dir(dir: &Path) -> Result<(), String> {
+        if let Some(parent) = dir.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let path = wide(dir);
+        let created = with_descriptor(|descriptor| {
+            let attributes = SECURITY_ATTRIBUTES {
+                nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+                lpSecurityDescriptor: descriptor.0,
+                bInheritHandle: false.into(),
+            };
+            match unsafe { CreateDirectoryW(PCWSTR(path.as_ptr()), Some(&attributes)) } {
+                Ok(()) => Ok(true),
+                Err(e) if e.code() == ERROR_ALREADY_EXISTS.to_hresult() => Ok(false),
+                Err(e) => Err(format!("Could not create {}: {}", dir.display(), e)),
+            }
+        })?;
+        if created {
+            Ok(())
+        } else {
+            lock_down(dir)
+        }
+    }
+
+    /// Replaces the owner and DACL of `path`, dropping inherited entries.
+    pub fn lock_down(path: &Path) -> Result<(), String> {
+        let wide_path = wide(path);
+        with_descriptor(|descriptor| unsafe {
+            let mut owner = PSID::default();
+            let mut dacl: *mut ACL = std::ptr::null_mut();
+            let (mut present, mut defaulted) = (false.into(), false.into());
+            GetSecurityDescriptorOwner(descriptor, &mut owner, &mut defaulted)
+                .and_then(|()| {
+                    GetSecurityDescriptorDacl(descriptor, &mut present, &mut dacl, &mut defaulted)
+                })
+                .map_err(|e| format!("Invalid security descriptor: {}", e))?;
+            let result = SetNamedSecurityInfoW(
+                PCWSTR(wide_path.as_ptr()),
+                SE_FILE_OBJECT,
+                OWNER_SECURITY_INFORMATION
+                    | DACL_SECURITY_INFORMATION
+                    | PROTECTED_DACL_SECURITY_INFORMATION,
+                Some(owner),
+                None,
+                Some(dacl),
+                None,
+            );
+            if result != ERROR_SUCCESS {
+                return Err(format!(
+                    "Could not restrict access to {}: {}",
+                    path.display(),
+                    windows::core::Error::from(result.to_hresult())
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    /// Fails unless only SYSTEM and Administrators can change `path`.
+    pub fn check(path: &Path) -> Result<(), String> {
+        let wide_path = wide(path);
+        let trusted = |sid: PSID| unsafe {
+            IsWellKnownSid(sid, WinLocalSystemSid).as_bool()
+                || IsWellKnownSid(sid, WinBuiltinAdministratorsSid).as_bool()
+        };
+
+        let mut owner = PSID::default();
+        let mut dacl: *mut ACL = std::ptr::null_mut();
+        let mut descriptor = PSECURITY_DESCRIPTOR::default();
+        let result = unsafe {
+            GetNamedSecurityInfoW(
+                PCWSTR(wide_path.as_ptr()),
+                SE_FILE_OBJECT,
+                OWNER_SECURITY_INFORMATION | DACL_SECURITY_INFORMATION,
+                Some(&mut owner),
+                None,
+                Some(&mut dacl),
+                None,
+                &mut descriptor,
+            )
+        };
+        if result != ERROR_SUCCESS {
+            return Err(format!(
+                "Could not read the permissions of {}: {}",
+                path.display(),
+                windows::core::Error::from(result.to_hresult())
+            ));
+        }
+
+        let entries = (!dacl.is_null()).then(|| unsafe {
+            (0..u32::from((*dacl).AceCount))
+                .filter_map(|i| {
+                    let mut ace = std::ptr::null_mut();
+                    GetAce(dacl, i, &mut ace).ok()?;
+                    let header = &*(ace as *const ACE_HEADER);
+                    let allowed = &*(ace as *const ACCESS_ALLOWED_ACE);
+                    Some(AccessEntry {
+                        allow: !ACCESS_DENIED_ACE_TYPES.contains(&header.AceType),
+                        inherit_only: u32::from(header.AceFlags) & INHERIT_ONLY_ACE.0 != 0,
+                        mask: allowed.Mask,
+                        // Other allow types keep the SID elsewhere; never trust them
+                        trusted: header.AceType == ACCESS_ALLOWED_ACE_TYPE
+                            && trusted(PSID(&allowed.SidStart as *const u32 as *mut _)),
+                    })
+                })
+                .collect::<Vec<_>>()
+        });
+        let secure = locked_down(trusted(owner), entries.as_deref());
+        unsafe {
+            let _ = LocalFree(Some(HLOCAL(descriptor.0)));
+        }
+
+        if secure {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} can be changed by users other than SYSTEM and Administrators; reinstall the launcher service",
+                path.display()
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_arg_follows_command_line_to_argv_rules() {
+        assert_eq!(quote_arg("--minimized"), "--minimized");
+        assert_eq!(quote_arg(r"C:\dir\"), r"C:\dir\");
+        assert_eq!(quote_arg(r"\\server\share"), r"\\server\share");
+        assert_eq!(quote_arg(""), r#""""#);
+        assert_eq!(quote_arg("Yurt A"), r#""Yurt A""#);
+        assert_eq!(quote_arg("a\tb"), "\"a\tb\"");
+    }
+
+    #[test]
+    fn quote_arg_escapes_embedded_quotes() {
+        assert_eq!(quote_arg(r#"a"b"#), r#""a\"b""#);
+        assert_eq!(quote_arg(r#"a\"b"#), r#""a\\\"b""#);
+        // Backslashes that don't precede a quote stay as they are
+        assert_eq!(quote_arg(r"a\b c"), r#""a\b c""#);
+    }
+
+    #[test]
+    fn quote_arg_doubles_trailing_backslashes() {
+        assert_eq!(quote_arg(r"C:\My Dir\"), r#""C:\My Dir\\""#);
+        assert_eq!(quote_arg(r"C:\My Dir\\"), r#""C:\My Dir\\\\""#);
+    }
+
+    #[test]
+    fn command_line_quotes_only_what_needs_it() {
+        let args = ["--profile".to_string(), "Yurt A".to_string()];
+        assert_eq!(
+            command_line(Path::new(r"C:\Program Files\hotspot.exe"), &args),
+            r#""C:\Program Files\hotspot.exe" --profile "Yurt A""#
+        );
+        assert_eq!(
+            command_line(Path::new(r"C:\hotspot.exe"), &[]),
+            r#""C:\hotspot.exe""#
+        );
+    }
+
+    fn entry(allow: bool, mask: u32, trusted: bool) -> AccessEntry {
+        AccessEntry {
+            allow,
+            inherit_only: false,
+            mask,
+            trusted,
+        }
+    }
+
+    #[test]
+    fn only_system_and_administrators_may_change_the_config() {
+        const FULL: u32 = 0x001f_01ff;
+        const READ: u32 = 0x0012_00a9;
+        let installed = [
+            entry(true, FULL, true),
+            entry(true, FULL, true),
+            entry(true, READ, false),
+        ];
+        assert!(locked_down(true, Some(&installed)));
+        // Owned by a user, who can always rewrite the DACL
+        assert!(!locked_down(false, Some(&installed)));
+        // No DACL at all
+        assert!(!locked_down(true, None));
+        // ProgramData lets users add files to new folders
+        let inherited = [entry(true, FULL, true), entry(true, READ | 0x0006, false)];
+        assert!(!locked_down(true, Some(&inherited)));
+        // Denying is fine, as are entries that only apply to children
+        let denied = [entry(true, FULL, true), entry(false, FULL, false)];
+        assert!(locked_down(true, Some(&denied)));
+        let creator_owner = AccessEntry {
+            inherit_only: true,
+            ..entry(true, FULL, false)
+        };
+        assert!(locked_down(true, Some(&[creator_owner])));
+    }
+
+    #[test]
+    fn paths_compare_like_windows() {
+        let exe = Path::new(r"C:\Program Files\Hotspot Manager\hotspot.exe");
+        assert!(same_path(
+            exe,
+            Path::new(r"\\?\c:\program files\hotspot manager\HOTSPOT.EXE")
+        ));
+        assert!(same_path(
+            exe,
+            Path::new("C:/Program Files/Hotspot Manager/hotspot.exe")
+        ));
+        assert!(!same_path(exe, Path::new(r"C:\Users\x\hotspot.exe")));
+        assert!(!same_path(
+            exe,
+            Path::new(r"C:\Program Files\Hotspot Manager\hotspot.exe.bak")
+        ));
+    }
+}
@@ -0,0 +1,580 @@
+use log::{error, info, warn};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::json;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::{auth, hotspot_toggle, interfaces, network};
+use crate::AppState;
+
+const MAX_REQUEST_BYTES: usize = 64 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+pub const HOTSPOT_SUBNET: [u8; 3] = [192, 168, 137];
+const DEFAULT_LOG_LINES: usize = 100;
+const MAX_LOG_LINES: usize = 1000;
+
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn query_param(&self, name: &str) -> Option<&str> {
+        self.query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v)
+    }
+}
+
+/// 32 random bytes from the OS, hex encoded.
+pub fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "Güvenli rastgele sayı üretilemedi".to_string())?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+pub fn restart(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let settings = state
+        .settings
+        .lock()
+        .map(|s| s.local_api.clone())
+        .unwrap_or_default();
+
+    let Ok(mut servers) = state.local_api.lock() else {
+        return;
+    };
+
+    if !servers.is_empty() {
+        for server in servers.drain(..) {
+            server.abort();
+        }
+        info!("Local API stopped");
+    }
+
+    if !settings.enabled {
+        return;
+    }
+    if settings.token.is_empty() {
+        warn!("Local API enabled without a token, not starting");
+        return;
+    }
+
+    let mut addrs = vec![SocketAddr::new(
+        IpAddr::V4(Ipv4Addr::LOCALHOST),
+        settings.port,
+    )];
+    if settings.bind_hotspot {
+        match hotspot_address() {
+            Some(ip) => addrs.push(SocketAddr::new(IpAddr::V4(ip), settings.port)),
+            None => warn!("Local API hotspot bind requested but no hotspot adapter found"),
+        }
+    }
+
+    for addr in addrs {
+        let app = app.clone();
+        let token = settings.token.clone();
+        servers.push(tauri::async_runtime::spawn(serve(app, addr, token)));
+    }
+}
+
+fn hotspot_address() -> Option<Ipv4Addr> {
+    local_ip_address::list_afinet_netifas()
+        .ok()?
+        .into_iter()
+        .find_map(|(_, ip)| match ip {
+            IpAddr::V4(v4) if v4.octets()[..3] == HOTSPOT_SUBNET => Some(v4),
+            _ => None,
+        })
+}
+
+async fn serve(app: AppHandle, addr: SocketAddr, token: String) {
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("Local API failed to bind {}: {}", addr, e);
+            return;
+        }
+    };
+    info!("Local API listening on {}", addr);
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                let app = app.clone();
+                let token = token.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = handle_connection(&app, stream, &token).await {
+                        warn!("Local API request from {} failed: {}", peer, e);
+                    }
+                });
+            }
+            Err(e) => warn!("Local API accept failed: {}", e),
+        }
+    }
+}
+
+async fn handle_connection(
+    app: &AppHandle,
+    mut stream: TcpStream,
+    token: &str,
+) -> Result<(), String> {
+    let request = match tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Ok(request)) => request,
+        Ok(Err(e)) => {
+            let body = json!({ "ok": false, "error": "Geçersiz istek" });
+            let _ = write_response(&mut stream, 400, &body).await;
+            return Err(e);
+        }
+        Err(_) => return Err("request timed out".to_string()),
+    };
+
+    let (status, body) = if !is_authorized(&request, token) {
+        (401, json!({ "ok": false, "error": "Geçersiz token" }))
+    } else {
+        route(app, &request).await
+    };
+
+    write_response(&mut stream, status, &body).await
+}
+
+fn is_authorized(request: &Request, token: &str) -> bool {
+    let provided = request
+        .header("authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| request.header("x-api-token"))
+        .unwrap_or_default();
+
+    !token.is_empty()
+        && provided.len() == token.len()
+        && provided
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Endpoint {
+    Status,
+    Login,
+    Logout,
+    HotspotStatus,
+    SetHotspot,
+    Stats,
+    Logs,
+}
+
+fn endpoint(method: &str, path: &str) -> Result<Endpoint, (u16, serde_json::Value)> {
+    match (method, path) {
+        ("GET", "/status") => Ok(Endpoint::Status),
+        ("POST", "/login") => Ok(Endpoint::Login),
+        ("POST", "/logout") => Ok(Endpoint::Logout),
+        ("GET", "/hotspot") => Ok(Endpoint::HotspotStatus),
+        ("POST", "/hotspot") => Ok(Endpoint::SetHotspot),
+        ("GET", "/stats") => Ok(Endpoint::Stats),
+        ("GET", "/logs") => Ok(Endpoint::Logs),
+        (_, "/status" | "/login" | "/logout" | "/hotspot" | "/stats" | "/logs") => {
+            Err((405, json!({ "ok": false, "error": "Desteklenmeyen metod" })))
+        }
+        _ => Err((404, json!({ "ok": false, "error": "Bulunamadı" }))),
+    }
+}
+
+async fn route(app: &AppHandle, request: &Request) -> (u16, serde_json::Value) {
+    let endpoint = match endpoint(&request.method, &request.path) {
+        Ok(endpoint) => endpoint,
+        Err(response) => return response,
+    };
+    match endpoint {
+        Endpoint::Status => {
+            let connected = network::is_online(&app.state::<AppState>().http).await;
+            (
+                200,
+                json!({ "ok": true, "connected": connected, "localIp": interfaces::local_ip() }),
+            )
+        }
+        Endpoint::Login => match auth::login_with_saved_credentials(app).await {
+            Ok(_) => (200, json!({ "ok": true, "connected": true })),
+            Err(e) => (500, json!({ "ok": false, "error": e })),
+        },
+        Endpoint::Logout => match auth::logout_and_publish(app).await {
+            Ok(result) => (200, json!({ "ok": result.ended, "logout": result })),
+            Err(e) => (500, json!({ "ok": false, "error": e })),
+        },
+        Endpoint::HotspotStatus => hotspot_response(hotspot_toggle::get_hotspot_status().await),
+        Endpoint::SetHotspot => {
+            let enabled = serde_json::from_slice::<serde_json::Value>(&request.body)
+                .ok()
+                .and_then(|v| v.get("enabled").and_then(|e| e.as_bool()));
+            let result = match enabled {
+                Some(enabled) => {
+                    tokio::task::spawn_blocking(move || hotspot_toggle::set_hotspot_state(enabled))
+                        .await
+                        .map_err(|e| format!("Task hatası: {}", e))
+                        .and_then(|r| r)
+                }
+                None => hotspot_toggle::toggle_hotspot().await,
+            };
+            hotspot_response(result)
+        }
+        Endpoint::Stats => {
+            let state = app.state::<AppState>();
+            let stats = match state.sys.lock() {
+                Ok(mut sys) => network::collect_network_stats(&mut sys),
+                Err(_) => return (500, json!({ "ok": false, "error": "İstatistik alınamadı" })),
+            };
+            (
+                200,
+                json!({
+                    "ok": true,
+                    "totalReceived": stats.total_received,
+                    "totalTransmitted": stats.total_transmitted,
+                }),
+            )
+        }
+        Endpoint::Logs => {
+            let lines = request
+                .query_param("lines")
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(DEFAULT_LOG_LINES)
+                .min(MAX_LOG_LINES);
+            match std::fs::read_to_string(crate::log_path()) {
+                Ok(content) => (200, json!({ "ok": true, "lines": tail(&content, lines) })),
+                Err(e) => (500, json!({ "ok": false, "error": e.to_string() })),
+            }
+        }
+    }
+}
+
+fn tail(content: &str, lines: usize) -> Vec<&str> {
+    let all: Vec<&str> = content.lines().collect();
+    all[all.len().saturating_sub(lines)..].to_vec()
+}
+
+fn hotspot_response(result: Result<bool, String>) -> (u16, serde_json::Value) {
+    match result {
+        Ok(enabled) => (200, json!({ "ok": true, "enabled": enabled })),
+        Err(e) => (500, json!({ "ok": false, "error": e })),
+    }
+}
+
+async fn read_request<R: AsyncRead + Unpin>(stream: &mut R) -> Result<Request, String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Err("connection closed before headers".to_string());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            if pos > MAX_REQUEST_BYTES {
+                return Err("request headers too large".to_string());
+            }
+            break pos;
+        }
+        if buf.len() > MAX_REQUEST_BYTES {
+            return Err("request headers too large".to_string());
+        }
+    };
+
+    let head = std::str::from_utf8(&buf[..header_end])
+        .map_err(|_| "request headers are not UTF-8".to_string())?;
+    let mut lines = head.split("\r\n");
+    let request_line: Vec<&str> = lines.next().unwrap_or_default().split(' ').collect();
+    let [method, target, version] = request_line[..] else {
+        return Err("malformed request line".to_string());
+    };
+    if method.is_empty() || !target.starts_with('/') || !version.starts_with("HTTP/1.") {
+        return Err("malformed request line".to_string());
+    }
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let headers = lines
+        .map(|line| {
+            line.split_once(':')
+                .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+                .ok_or_else(|| "malformed header".to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let content_length = match headers.iter().find(|(k, _)| k == "content-length") {
+        Some((_, v)) => v
+            .parse::<usize>()
+            .map_err(|_| "invalid Content-Length".to_string())?,
+        None => 0,
+    };
+    if content_length > MAX_REQUEST_BYTES {
+        return Err("request body too large".to_string());
+    }
+
+    let mut body = buf[header_end + 4..].to_vec();
+    while body.len() < content_length {
+        let n = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Err("connection closed before body".to_string());
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+
+    Ok(Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query.to_string(),
+        headers,
+        body,
+    })
+}
+
+async fn write_response<W: AsyncWrite + Unpin>(
+    stream: &mut W,
+    status: u16,
+    body: &serde_json::Value,
+) -> Result<(), String> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+
+    stream
+        .write_all(response.as_bytes())
+        .await
+        .map_err(|e| e.to_string())?;
+    stream.shutdown().await.map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn parse(raw: &[u8]) -> Result<Request, String> {
+        read_request(&mut &raw[..]).await
+    }
+
+    fn request(headers: &[(&str, &str)]) -> Request {
+        Request {
+            method: "GET".to_string(),
+            path: "/status".to_string(),
+            query: String::new(),
+            headers: headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            body: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn parses_request_line_headers_and_body() {
+        let request = parse(
+            b"POST /hotspot?x=1&lines=20 HTTP/1.1\r\nHost: localhost\r\nContent-Length: 16\r\nX-Api-Token: abc\r\n\r\n{\"enabled\":true}trailing",
+        )
+        .await
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/hotspot");
+        assert_eq!(request.query_param("lines"), Some("20"));
+        assert_eq!(request.query_param("missing"), None);
+        assert_eq!(request.header("x-api-token"), Some("abc"));
+        assert_eq!(request.body, b"{\"enabled\":true}");
+
+        let request = parse(b"GET /status HTTP/1.0\r\n\r\nignored").await.unwrap();
+        assert!(request.body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn body_split_across_reads() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+        let writer = tokio::spawn(async move {
+            client
+                .write_all(b"POST /login HTTP/1.1\r\nContent-Length: 5\r\n\r\nhe")
+                .await
+                .unwrap();
+            client.write_all(b"llo").await.unwrap();
+        });
+        let request = read_request(&mut server).await.unwrap();
+        writer.await.unwrap();
+        assert_eq!(request.body, b"hello");
+    }
+
+    #[tokio::test]
+    async fn rejects_malformed_requests() {
+        for raw in [
+            &b"\r\n\r\n"[..],
+            b"GET\r\n\r\n",
+            b"GET /status\r\n\r\n",
+            b"GET status HTTP/1.1\r\n\r\n",
+            b"GET /status SMTP\r\n\r\n",
+            b"GET  /status HTTP/1.1\r\n\r\n",
+            b"GET /status HTTP/1.1 extra\r\n\r\n",
+            b"GET /status HTTP/1.1\r\nno colon\r\n\r\n",
+            b"GET /\xff HTTP/1.1\r\n\r\n",
+        ] {
+            let err = parse(raw).await.err();
+            assert!(
+                matches!(
+                    err.as_deref(),
+                    Some(
+                        "malformed request line"
+                            | "malformed header"
+                            | "request headers are not UTF-8"
+                    )
+                ),
+                "{:?}: {:?}",
+                String::from_utf8_lossy(raw),
+                err
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_content_length() {
+        let err = |raw: &'static [u8]| async move { parse(raw).await.err().unwrap() };
+        assert_eq!(
+            err(b"POST /login HTTP/1.1\r\nContent-Length: abc\r\n\r\n").await,
+            "invalid Content-Length"
+        );
+        assert_eq!(
+            err(b"POST /login HTTP/1.1\r\nContent-Length: -1\r\n\r\n").await,
+            "invalid Content-Length"
+        );
+        assert_eq!(
+            err(b"POST /login HTTP/1.1\r\nContent-Length: 65537\r\n\r\n").await,
+            "request body too large"
+        );
+        assert_eq!(
+            err(b"POST /login HTTP/1.1\r\nContent-Length: 10\r\n\r\nshort").await,
+            "connection closed before body"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_or_truncated_headers() {
+        let mut raw = b"GET /status HTTP/1.1\r\nX-Pad: ".to_vec();
+        raw.resize(MAX_REQUEST_BYTES + 10, b'a');
+        assert_eq!(
+            parse(&raw).await.err().unwrap(),
+            "request headers too large"
+        );
+
+        raw.extend_from_slice(b"\r\n\r\n");
+        assert_eq!(
+            parse(&raw).await.err().unwrap(),
+            "request headers too large"
+        );
+
+        assert_eq!(
+            parse(b"GET /status HTTP/1.1\r\nHost: x\r\n")
+                .await
+                .err()
+                .unwrap(),
+            "connection closed before headers"
+        );
+        assert_eq!(
+            parse(b"").await.err().unwrap(),
+            "connection closed before headers"
+        );
+    }
+
+    #[test]
+    fn authorization() {
+        let token = "s3cret";
+        assert!(is_authorized(
+            &request(&[("authorization", "Bearer s3cret")]),
+            token
+        ));
+        assert!(is_authorized(&request(&[("x-api-token", "s3cret")]), token));
+
+        assert!(!is_authorized(&request(&[]), token));
+        assert!(!is_authorized(
+            &request(&[("authorization", "Bearer s3creT")]),
+            token
+        ));
+        assert!(!is_authorized(
+            &request(&[("authorization", "Bearer s3cret2")]),
+            token
+        ));
+        assert!(!is_authorized(
+            &request(&[("authorization", "Basic s3cret")]),
+            token
+        ));
+        assert!(!is_authorized(
+            &request(&[("authorization", "s3cret")]),
+            token
+        ));
+        assert!(!is_authorized(&request(&[("x-api-token", "")]), token));
+        // An empty configured token never matches, even an empty header
+        assert!(!is_authorized(&request(&[("x-api-token", "")]), ""));
+        assert!(!is_authorized(&request(&[]), ""));
+    }
+
+    #[test]
+    fn routing() {
+        assert_eq!(endpoint("GET", "/status"), Ok(Endpoint::Status));
+        assert_eq!(endpoint("POST", "/login"), Ok(Endpoint::Login));
+        assert_eq!(endpoint("POST", "/logout"), Ok(Endpoint::Logout));
+        assert_eq!(endpoint("GET", "/hotspot"), Ok(Endpoint::HotspotStatus));
+        assert_eq!(endpoint("POST", "/hotspot"), Ok(Endpoint::SetHotspot));
+        assert_eq!(endpoint("GET", "/stats"), Ok(Endpoint::Stats));
+        assert_eq!(endpoint("GET", "/logs"), Ok(Endpoint::Logs));
+
+        let status = |method, path| endpoint(method, path).err().map(|(status, _)| status);
+        assert_eq!(status("GET", "/login"), Some(405));
+        assert_eq!(status("DELETE", "/hotspot"), Some(405));
+        assert_eq!(status("get", "/status"), Some(405));
+        assert_eq!(status("GET", "/"), Some(404));
+        assert_eq!(status("GET", "/status/"), Some(404));
+    }
+
+    #[test]
+    fn log_tail() {
+        assert_eq!(tail("a\nb\nc\n", 2), vec!["b", "c"]);
+        assert_eq!(tail("a\nb", 10), vec!["a", "b"]);
+        assert!(tail("a\nb", 0).is_empty());
+        assert!(tail("", 5).is_empty());
+    }
+
+    #[tokio::test]
+    async fn response_format() {
+        let mut out = Vec::new();
+        write_response(&mut out, 400, &json!({ "ok": false }))
+            .await
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "HTTP/1.1 400 Bad Request\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: 12\r\nConnection: close\r\n\r\n{\"ok\":false}"
+        );
+    }
+}
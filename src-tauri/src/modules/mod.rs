@@ -1,6 +1,22 @@
 pub mod auth;
+pub mod cli;
 pub mod config;
+pub mod dns;
+pub mod history;
 pub mod hotspot_toggle;
+pub mod http;
+pub mod interfaces;
+pub mod ipc;
+pub mod latency;
+pub mod launcher_service;
+pub mod local_api;
 pub mod network;
+pub mod netwatch;
+pub mod portal;
+pub mod profiles;
+pub mod session;
+pub mod speedtest;
 pub mod startup;
+pub mod tls;
 pub mod tray;
+pub mod wifi;
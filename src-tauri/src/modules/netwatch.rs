@@ -0,0 +1,412 @@
+use log::{info, warn};
+use serde::Serialize;
+use std::net::IpAddr;
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::{auth, network, profiles, session, wifi};
+use crate::AppState;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+// Notifications can't see SSID roaming without an address change, so the
+// watcher still re-reads the snapshot this often
+const WATCH_SLICE: Duration = Duration::from_secs(30);
+const SETTLE_DELAY: Duration = Duration::from_secs(1);
+#[cfg(windows)]
+const CANCEL_WAIT_MS: u32 = 5_000;
+const MIN_POLL_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_POLL_BACKOFF: Duration = Duration::from_secs(60);
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterfaceAddress {
+    pub name: String,
+    pub ip: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkSnapshot {
+    pub ssid: Option<String>,
+    pub interfaces: Vec<InterfaceAddress>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkChange {
+    pub ssid: Option<String>,
+    pub previous_ssid: Option<String>,
+    pub interfaces: Vec<InterfaceAddress>,
+    pub added: Vec<InterfaceAddress>,
+    pub removed: Vec<InterfaceAddress>,
+    pub connected: bool,
+}
+
+impl NetworkChange {
+    /// The Wi-Fi that was dropped, when no other one took its place.
+    fn lost_ssid(&self) -> Option<&str> {
+        match (&self.ssid, &self.previous_ssid) {
+            (None, Some(lost)) => Some(lost),
+            _ => None,
+        }
+    }
+
+    /// Moved straight from one Wi-Fi to another.
+    fn switched_ssid(&self) -> bool {
+        matches!((&self.ssid, &self.previous_ssid), (Some(now), Some(before)) if now != before)
+    }
+}
+
+/// What changed between two snapshots; `connected` is filled in by the caller.
+fn diff(last: &NetworkSnapshot, current: &NetworkSnapshot) -> NetworkChange {
+    NetworkChange {
+        ssid: current.ssid.clone(),
+        previous_ssid: last.ssid.clone(),
+        interfaces: current.interfaces.clone(),
+        added: current
+            .interfaces
+            .iter()
+            .filter(|i| !last.interfaces.contains(i))
+            .cloned()
+            .collect(),
+        removed: last
+            .interfaces
+            .iter()
+            .filter(|i| !current.interfaces.contains(i))
+            .cloned()
+            .collect(),
+        connected: false,
+    }
+}
+
+pub fn snapshot() -> NetworkSnapshot {
+    let mut interfaces: Vec<InterfaceAddress> = local_ip_address::list_afinet_netifas()
+        .map(|ifaces| {
+            ifaces
+                .into_iter()
+                .filter(|(_, ip)| !ip.is_loopback())
+                .map(|(name, ip)| InterfaceAddress {
+                    name,
+                    ip: ip.to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    interfaces.sort_by(|a, b| (&a.name, &a.ip).cmp(&(&b.name, &b.ip)));
+
+    NetworkSnapshot {
+        ssid: current_ssid(),
+        interfaces,
+    }
+}
+
+pub fn current_ssid() -> Option<String> {
+    #[cfg(windows)]
+    let output = Command::new("netsh")
+        .args(["wlan", "show", "interfaces"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    #[cfg(not(windows))]
+    let output = Command::new("iwgetid").arg("-r").output().ok()?;
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    #[cfg(windows)]
+    {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with("SSID"))
+            .find_map(|line| line.split_once(':'))
+            .map(|(_, ssid)| ssid.trim().to_string())
+            .filter(|ssid| !ssid.is_empty())
+    }
+    #[cfg(not(windows))]
+    {
+        Some(text.trim().to_string()).filter(|ssid| !ssid.is_empty())
+    }
+}
+
+pub fn start_watcher(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last = snapshot();
+        let mut notifications = true;
+        let mut poll_backoff = MIN_POLL_BACKOFF;
+        info!("Network watcher started: {:?}", last);
+
+        loop {
+            if notifications {
+                match wait_for_address_change(WATCH_SLICE) {
+                    Ok(true) => std::thread::sleep(SETTLE_DELAY),
+                    Ok(false) => {}
+                    Err(e) => {
+                        warn!("Address change notifications unavailable ({}), polling", e);
+                        notifications = false;
+                    }
+                }
+            } else {
+                std::thread::sleep(poll_backoff);
+            }
+
+            let current = snapshot();
+            if current == last {
+                poll_backoff = (poll_backoff * 2).min(MAX_POLL_BACKOFF);
+                continue;
+            }
+            poll_backoff = MIN_POLL_BACKOFF;
+
+            let mut change = diff(&last, &current);
+
+            // Losing the Wi-Fi itself, not just the portal session
+            if let Some(lost) = change.lost_ssid() {
+                if profiles::active_profile(&app).is_some_and(|p| p.auto_reconnect) {
+                    wifi::rejoin(app.state::<AppState>().wifi.as_ref(), lost);
+                }
+            }
+
+            // A ticket belongs to the network it was bought on
+            if change.switched_ssid() {
+                session::clear(&app);
+            }
+
+            // Switch profile first so the check below uses its probes
+            let switched = tauri::async_runtime::block_on(profiles::switch_for_network(
+                &app,
+                change.ssid.clone(),
+            ));
+            change.connected =
+                tauri::async_runtime::block_on(network::is_online(&app.state::<AppState>().http));
+            info!(
+                "Network changed: ssid {:?} -> {:?}, +{:?} -{:?}, connected={}",
+                change.previous_ssid, change.ssid, change.added, change.removed, change.connected
+            );
+
+            auth::publish_status(&app, change.connected);
+            if let Some(profile) = switched {
+                tauri::async_runtime::block_on(profiles::auto_login(
+                    &app,
+                    &profile,
+                    change.connected,
+                ));
+            }
+            let _ = app.emit("network-changed", &change);
+            last = current;
+        }
+    });
+}
+
+/// True once some adapter holds a routable IPv4 address. Loopback and
+/// link-local (169.254.x.x, assigned while DHCP is still failing) don't count.
+pub fn has_network() -> bool {
+    local_ip_address::list_afinet_netifas()
+        .map(|ifaces| {
+            ifaces.iter().any(|(_, ip)| match ip {
+                IpAddr::V4(v4) => !v4.is_loopback() && !v4.is_link_local() && !v4.is_unspecified(),
+                IpAddr::V6(_) => false,
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// Waits until [`has_network`] holds or `timeout` passes. Returns whether the
+/// network came up. OS address-change notifications wake us early; if they
+/// are unavailable we fall back to polling.
+pub async fn wait_for_network(timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    let mut notifications = true;
+
+    loop {
+        if has_network() {
+            return true;
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return false;
+        }
+        let slice = remaining.min(POLL_INTERVAL * 5);
+
+        if notifications {
+            let result = tokio::task::spawn_blocking(move || wait_for_address_change(slice))
+                .await
+                .unwrap_or_else(|e| Err(e.to_string()));
+            match result {
+                Ok(true) => info!("Network address change notification received"),
+                Ok(false) => {}
+                Err(e) => {
+                    warn!("Address change notifications unavailable ({}), polling", e);
+                    notifications = false;
+                }
+            }
+        } else {
+            tokio::time::sleep(remaining.min(POLL_INTERVAL)).await;
+        }
+    }
+}
+
+/// Blocks until the IPv4 address table changes or `timeout` passes.
+/// Returns `Ok(false)` on timeout.
+#[cfg(windows)]
+fn wait_for_address_change(timeout: Duration) -> Result<bool, String> {
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{
+        CloseHandle, ERROR_IO_PENDING, HANDLE, WAIT_OBJECT_0, WAIT_TIMEOUT,
+    };
+    use windows::Win32::NetworkManagement::IpHelper::{CancelIPChangeNotify, NotifyAddrChange};
+    use windows::Win32::System::Threading::{CreateEventW, WaitForSingleObject};
+    use windows::Win32::System::IO::OVERLAPPED;
+
+    unsafe {
+        let event = CreateEventW(None, false, false, PCWSTR::null()).map_err(|e| e.to_string())?;
+        // Boxed so it can outlive this frame if the request never completes
+        let overlapped = Box::new(OVERLAPPED {
+            hEvent: event,
+            ..Default::default()
+        });
+        let mut handle = HANDLE::default();
+
+        let code = NotifyAddrChange(&mut handle, &*overlapped);
+        if code != ERROR_IO_PENDING.0 {
+            let _ = CloseHandle(event);
+            return Err(format!("NotifyAddrChange failed with {}", code));
+        }
+
+        let waited = WaitForSingleObject(event, timeout.as_millis() as u32);
+        if waited != WAIT_OBJECT_0 {
+            // The pending request still owns the OVERLAPPED and its event; the
+            // cancellation completes it and signals the event, so both may
+            // only be freed after that
+            let _ = CancelIPChangeNotify(&*overlapped);
+            if WaitForSingleObject(event, CANCEL_WAIT_MS) != WAIT_OBJECT_0 {
+                Box::leak(overlapped);
+                return Err("address change request did not cancel".to_string());
+            }
+        }
+        let _ = CloseHandle(event);
+
+        match waited {
+            w if w == WAIT_OBJECT_0 => Ok(true),
+            w if w == WAIT_TIMEOUT => Ok(false),
+            w => Err(format!("WaitForSingleObject returned {:?}", w)),
+        }
+    }
+}
+
+/// Netlink route socket subscribed to link and address notifications.
+#[cfg(target_os = "linux")]
+fn wait_for_address_change(timeout: Duration) -> Result<bool, String> {
+    unsafe {
+        let fd = libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+            libc::NETLINK_ROUTE,
+        );
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+
+        let mut addr: libc::sockaddr_nl = std::mem::zeroed();
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        addr.nl_groups =
+            (libc::RTMGRP_LINK | libc::RTMGRP_IPV4_IFADDR | libc::RTMGRP_IPV6_IFADDR) as u32;
+        if libc::bind(
+            fd,
+            &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+            std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+        ) < 0
+        {
+            let error = std::io::Error::last_os_error();
+            libc::close(fd);
+            return Err(error.to_string());
+        }
+
+        let mut pfd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ready = libc::poll(
+            &mut pfd,
+            1,
+            timeout.as_millis().min(i32::MAX as u128) as i32,
+        );
+        let error = std::io::Error::last_os_error();
+        libc::close(fd);
+
+        match ready {
+            n if n > 0 => Ok(true),
+            0 => Ok(false),
+            _ => Err(error.to_string()),
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn wait_for_address_change(_timeout: Duration) -> Result<bool, String> {
+    Err("not supported on this platform".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(name: &str, ip: &str) -> InterfaceAddress {
+        InterfaceAddress {
+            name: name.to_string(),
+            ip: ip.to_string(),
+        }
+    }
+
+    fn snap(ssid: Option<&str>, interfaces: &[InterfaceAddress]) -> NetworkSnapshot {
+        NetworkSnapshot {
+            ssid: ssid.map(str::to_string),
+            interfaces: interfaces.to_vec(),
+        }
+    }
+
+    #[test]
+    fn added_and_removed_addresses() {
+        let lan = address("Ethernet", "192.168.1.20");
+        let old_wifi = address("Wi-Fi", "10.0.0.5");
+        let new_wifi = address("Wi-Fi", "10.0.0.7");
+        let last = snap(Some("Yurt"), &[lan.clone(), old_wifi.clone()]);
+        let current = snap(Some("Yurt"), &[lan.clone(), new_wifi.clone()]);
+
+        let change = diff(&last, &current);
+        assert_eq!(change.added, vec![new_wifi.clone()]);
+        assert_eq!(change.removed, vec![old_wifi]);
+        assert_eq!(change.interfaces, vec![lan, new_wifi]);
+        assert!(!change.switched_ssid());
+        assert_eq!(change.lost_ssid(), None);
+    }
+
+    #[test]
+    fn lost_wifi() {
+        let wifi = address("Wi-Fi", "10.0.0.5");
+        let last = snap(Some("Yurt"), std::slice::from_ref(&wifi));
+        let change = diff(&last, &snap(None, &[]));
+        assert_eq!(change.lost_ssid(), Some("Yurt"));
+        assert!(!change.switched_ssid());
+        assert_eq!(change.removed, vec![wifi]);
+        assert!(change.added.is_empty());
+    }
+
+    #[test]
+    fn switched_wifi() {
+        let change = diff(&snap(Some("Yurt"), &[]), &snap(Some("Kafe"), &[]));
+        assert!(change.switched_ssid());
+        assert_eq!(change.lost_ssid(), None);
+        assert_eq!(change.previous_ssid.as_deref(), Some("Yurt"));
+        assert_eq!(change.ssid.as_deref(), Some("Kafe"));
+    }
+
+    #[test]
+    fn joined_wifi() {
+        let change = diff(&snap(None, &[]), &snap(Some("Yurt"), &[]));
+        assert!(!change.switched_ssid());
+        assert_eq!(change.lost_ssid(), None);
+    }
+}
@@ -1,32 +1,51 @@
+use super::http::HttpClient;
 use crate::AppState;
-use local_ip_address::local_ip;
+use log::warn;
 use serde::Serialize;
-use sysinfo::{NetworkExt, SystemExt};
+use std::net::IpAddr;
+use sysinfo::{NetworkExt, System, SystemExt};
+
+const DEFAULT_PROBE: &str = "http://connectivitycheck.gstatic.com/generate_204";
 
 #[tauri::command]
-pub async fn check_connection() -> bool {
-    let client = reqwest::Client::builder()
+pub async fn check_connection(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    Ok(is_online(&state.http).await)
+}
+
+/// Checks the active profile's probes (or the default one); any probe that
+/// gets through without hitting a portal counts as connected.
+pub async fn is_online(http: &HttpClient) -> bool {
+    let client = http
+        .builder()
         .timeout(std::time::Duration::from_secs(3))
         .redirect(reqwest::redirect::Policy::none())
         .build()
         .unwrap_or_default();
 
-    match client
-        .get("http://connectivitycheck.gstatic.com/generate_204")
-        .send()
-        .await
-    {
+    let mut probes = http.active_profile().map(|p| p.probes).unwrap_or_default();
+    if probes.is_empty() {
+        probes.push(DEFAULT_PROBE.to_string());
+    }
+    for url in &probes {
+        if probe(&client, url).await {
+            return true;
+        }
+    }
+    false
+}
+
+async fn probe(client: &reqwest::Client, url: &str) -> bool {
+    match client.get(url).send().await {
         Ok(resp) => {
             let status = resp.status().as_u16();
             if status == 204 {
                 return true;
             }
-            if status == 302 || status == 301 || status == 200 {
-                let body = resp.text().await.unwrap_or_default();
-                if body.contains("hotspot") || body.contains("maxxarena") || body.contains("login")
-                {
-                    return false;
-                }
+            if status == 200 {
+                let body = resp.text().await.unwrap_or_default().to_lowercase();
+                return !(body.contains("hotspot")
+                    || body.contains("maxxarena")
+                    || body.contains("login"));
             }
             false
         }
@@ -34,24 +53,56 @@ pub async fn check_connection() -> bool {
     }
 }
 
+// Tried in order; each returns the address as plain text
+const PUBLIC_IP_SERVICES: &[&str] = &[
+    "https://api.ipify.org",
+    "https://checkip.amazonaws.com",
+    "https://icanhazip.com",
+    "https://ifconfig.me/ip",
+];
+
 #[tauri::command]
-pub fn get_ip_info() -> String {
-    if let Ok(ip) = local_ip() {
-        ip.to_string()
-    } else {
-        "Bilinmiyor".to_string()
+pub async fn get_public_ip(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    let client = state
+        .http
+        .builder()
+        .timeout(std::time::Duration::from_secs(4))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    for url in PUBLIC_IP_SERVICES {
+        let body = match client.get(*url).send().await {
+            Ok(resp) if resp.status().is_success() => resp.text().await.unwrap_or_default(),
+            Ok(resp) => {
+                warn!("Public IP service {} returned {}", url, resp.status());
+                continue;
+            }
+            Err(e) => {
+                warn!("Public IP service {} failed: {}", url, e);
+                continue;
+            }
+        };
+        // Portals answer with their login page, so only a bare address counts
+        if let Ok(ip) = body.trim().parse::<IpAddr>() {
+            return Ok(ip.to_string());
+        }
     }
+    Err("Genel IP adresi alınamadı".to_string())
 }
 
 #[derive(Serialize)]
 pub struct NetworkStats {
-    total_received: u64,
-    total_transmitted: u64,
+    pub total_received: u64,
+    pub total_transmitted: u64,
 }
 
 #[tauri::command]
 pub fn get_network_stats(state: tauri::State<AppState>) -> NetworkStats {
     let mut sys = state.sys.lock().unwrap();
+    collect_network_stats(&mut sys)
+}
+
+pub fn collect_network_stats(sys: &mut System) -> NetworkStats {
     sys.refresh_all();
 
     let mut total_received = 0;
@@ -0,0 +1,516 @@
+use serde::Serialize;
+use std::time::Duration;
+
+use super::http::{self, HttpClient};
+use super::profiles;
+use crate::AppState;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortalAccountStatus {
+    pub provider: String,
+    pub logged_in: bool,
+    pub remaining_bytes: Option<u64>,
+    pub used_bytes: Option<u64>,
+    pub total_bytes: Option<u64>,
+    pub remaining_secs: Option<u64>,
+    /// As the portal prints it; formats differ between portals.
+    pub valid_until: Option<String>,
+}
+
+/// A form post to the portal; sent with the status page as Origin/Referer.
+pub struct PortalForm {
+    pub url: &'static str,
+    pub fields: Vec<(&'static str, String)>,
+}
+
+/// Knows where a portal shows the account status and how to read it, and
+/// which forms log in and out.
+pub trait PortalProvider: Send + Sync {
+    fn id(&self) -> &'static str;
+    fn status_url(&self) -> &'static str;
+    fn parse_status(&self, html: &str) -> PortalAccountStatus;
+    fn login_form(&self, username: &str, password: &str) -> PortalForm;
+    fn login_succeeded(&self, html: &str) -> bool;
+    fn logout_form(&self) -> PortalForm;
+}
+
+pub fn provider(id: &str) -> Option<&'static dyn PortalProvider> {
+    match id {
+        "maxxarena" => Some(&Maxxarena),
+        _ => None,
+    }
+}
+
+/// The provider of the active network profile, MaxxArena without one.
+/// Profiles without a portal or with an unknown one are rejected.
+pub fn active_provider(http: &HttpClient) -> Result<&'static dyn PortalProvider, String> {
+    let id = http
+        .active_profile()
+        .map(|p| p.provider)
+        .unwrap_or_else(profiles::default_provider);
+    if id == profiles::PROVIDER_NONE {
+        return Err("Bu ağda giriş portalı yok".to_string());
+    }
+    provider(&id).ok_or_else(|| format!("Bilinmeyen portal sağlayıcısı: {}", id))
+}
+
+pub async fn fetch_status(http: &HttpClient) -> Result<PortalAccountStatus, String> {
+    let provider = active_provider(http)?;
+    let body = http
+        .client()
+        .get(provider.status_url())
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Portal durumu alınamadı: {}", http::describe_error(&e)))?
+        .text()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(provider.parse_status(&body))
+}
+
+#[tauri::command]
+pub async fn get_portal_account_status(
+    state: tauri::State<'_, AppState>,
+) -> Result<PortalAccountStatus, String> {
+    fetch_status(&state.http).await
+}
+
+struct Maxxarena;
+
+impl PortalProvider for Maxxarena {
+    fn id(&self) -> &'static str {
+        "maxxarena"
+    }
+
+    fn status_url(&self) -> &'static str {
+        "https://hotspot.maxxarena.de/"
+    }
+
+    fn login_form(&self, username: &str, password: &str) -> PortalForm {
+        PortalForm {
+            url: "https://hotspot.maxxarena.de/?auth=ticket&pageID=page-0",
+            fields: vec![
+                ("auth", "ticket".to_string()),
+                ("lp-screen-size", "1920:1080:1920:1080".to_string()),
+                ("lp-input-username", username.to_string()),
+                ("lp-input-password", password.to_string()),
+                ("submit-login", "Oturum aç".to_string()),
+            ],
+        }
+    }
+
+    // The login page itself has "logout" in its script, so only the visible
+    // text is searched
+    fn login_succeeded(&self, html: &str) -> bool {
+        let text = visible_text(html);
+        ["başarılı", "oturumu kapat", "logout"]
+            .iter()
+            .any(|marker| text.contains(marker))
+    }
+
+    fn logout_form(&self) -> PortalForm {
+        PortalForm {
+            url: "https://hotspot.maxxarena.de/",
+            fields: vec![("logout", "1".to_string())],
+        }
+    }
+
+    // The page is served in Turkish or German depending on the browser, with
+    // English fallbacks; only the visible labels are relied on.
+    fn parse_status(&self, html: &str) -> PortalAccountStatus {
+        let text = visible_text(html);
+        PortalAccountStatus {
+            provider: self.id().to_string(),
+            logged_in: ["oturumu kapat", "abmelden", "logout", "log out"]
+                .iter()
+                .any(|marker| text.contains(marker)),
+            remaining_bytes: after_label(
+                &text,
+                &[
+                    "kalan kota",
+                    "kalan veri",
+                    "restvolumen",
+                    "verbleibendes volumen",
+                    "remaining volume",
+                    "remaining data",
+                ],
+                parse_size,
+            ),
+            used_bytes: after_label(&text, &["kullanılan", "verbraucht", "used"], parse_size),
+            total_bytes: after_label(
+                &text,
+                &[
+                    "toplam kota",
+                    "toplam",
+                    "gesamtvolumen",
+                    "kontingent",
+                    "total",
+                ],
+                parse_size,
+            ),
+            remaining_secs: remaining_from_text(&text).map(|d| d.as_secs()),
+            valid_until: after_label(
+                &text,
+                &[
+                    "geçerlilik",
+                    "son kullanma",
+                    "gültig bis",
+                    "valid until",
+                    "expires",
+                ],
+                parse_date,
+            ),
+        }
+    }
+}
+
+/// Lowercased text content with tags and `&nbsp;` turned into spaces.
+/// Script and style bodies are dropped, so a "logout" in the page's
+/// JavaScript doesn't count as a visible label.
+fn visible_text(html: &str) -> String {
+    // ASCII lowercasing keeps the byte offsets of `html`
+    let lower = html.to_ascii_lowercase();
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    let mut at = 0;
+    while let Some(c) = html[at..].chars().next() {
+        match c {
+            '<' => {
+                in_tag = true;
+                if let Some(end) = raw_text_end(&lower, at) {
+                    at = end;
+                    continue;
+                }
+            }
+            '>' => {
+                in_tag = false;
+                text.push(' ');
+            }
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+        at += c.len_utf8();
+    }
+    text.replace("&nbsp;", " ").to_lowercase()
+}
+
+/// Where the closing tag starts when a `<script>` or `<style>` opens at
+/// `at`; the end of the page if it is never closed.
+fn raw_text_end(lower: &str, at: usize) -> Option<usize> {
+    let after = &lower[at + 1..];
+    let name = ["script", "style"].into_iter().find(|name| {
+        after.strip_prefix(name).is_some_and(|rest| {
+            rest.starts_with(|c: char| c == '>' || c == '/' || c.is_ascii_whitespace())
+        })
+    })?;
+    let close = format!("</{}", name);
+    Some(after.find(&close).map_or(lower.len(), |end| at + 1 + end))
+}
+
+/// Runs `parse` on the text right after the first label it can read a value from.
+fn after_label<T>(text: &str, labels: &[&str], parse: fn(&str) -> Option<T>) -> Option<T> {
+    labels.iter().find_map(|label| {
+        text.match_indices(label).find_map(|(at, _)| {
+            let window: String = text[at + label.len()..].chars().take(80).collect();
+            parse(&window)
+        })
+    })
+}
+
+fn words(window: &str) -> impl Iterator<Item = &str> {
+    window
+        .split(|c: char| c.is_whitespace() || c == '/')
+        .map(|w| w.trim_matches(|c: char| matches!(c, ':' | ',' | ';' | '(' | ')')))
+        .filter(|w| !w.is_empty())
+}
+
+/// Reads "12:34:56"/"12:34" clocks or "1 saat 20 dakika" style durations that
+/// follow a "remaining" label.
+pub fn parse_remaining(html: &str) -> Option<Duration> {
+    remaining_from_text(&visible_text(html))
+}
+
+// "Valid until" labels are left out on purpose, they precede a time of day
+fn remaining_from_text(text: &str) -> Option<Duration> {
+    after_label(
+        text,
+        &[
+            "kalan süre",
+            "kalan zaman",
+            "restzeit",
+            "verbleibende zeit",
+            "remaining time",
+            "kalan",
+            "remaining",
+            "verbleibend",
+        ],
+        |window| parse_clock(window).or_else(|| parse_units(window)),
+    )
+}
+
+/// Only a clock that is the first number after the label counts.
+fn parse_clock(window: &str) -> Option<Duration> {
+    let token = words(window).find(|w| w.chars().any(|c| c.is_ascii_digit()))?;
+    let parts: Vec<u64> = token
+        .split(':')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+    let secs = match parts[..] {
+        [h, m, s] if m < 60 && s < 60 => h.checked_mul(3600)?.checked_add(m * 60 + s)?,
+        [h, m] if m < 60 => h.checked_mul(3600)?.checked_add(m * 60)?,
+        _ => return None,
+    };
+    Some(Duration::from_secs(secs))
+}
+
+fn parse_units(window: &str) -> Option<Duration> {
+    const UNITS: &[(&str, u64)] = &[
+        ("gün", 86400),
+        ("day", 86400),
+        ("tag", 86400),
+        ("d", 86400),
+        ("saat", 3600),
+        ("sa", 3600),
+        ("hour", 3600),
+        ("stunde", 3600),
+        ("std", 3600),
+        ("h", 3600),
+        ("dakika", 60),
+        ("dk", 60),
+        ("minute", 60),
+        ("min", 60),
+        ("m", 60),
+        ("saniye", 1),
+        ("sn", 1),
+        ("second", 1),
+        ("sekunde", 1),
+        ("sec", 1),
+        ("s", 1),
+    ];
+
+    let mut total: u64 = 0;
+    let mut words = words(window);
+    while let Some(word) = words.next() {
+        // Both "20 dakika" and "20dk"; words without a number are filler
+        let digits = word.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 {
+            continue;
+        }
+        let Ok(value) = word[..digits].parse::<u64>() else {
+            break;
+        };
+        let unit = match &word[digits..] {
+            "" => words.next().unwrap_or_default(),
+            inline => inline,
+        };
+        // Short units must match exactly so "500 mb" isn't read as minutes
+        let unit: String = unit.chars().take_while(|c| c.is_alphabetic()).collect();
+        let Some((_, secs)) = UNITS
+            .iter()
+            .find(|(name, _)| unit == *name || (name.len() >= 3 && unit.starts_with(name)))
+        else {
+            break;
+        };
+        // Absurd numbers in the page text are not a duration
+        total = value.checked_mul(*secs)?.checked_add(total)?;
+    }
+    (total > 0).then(|| Duration::from_secs(total))
+}
+
+/// "1,5 GB", "750mb", "2.3 GiB". Portals mean binary units either way, which
+/// is also how the UI formats them.
+fn parse_size(window: &str) -> Option<u64> {
+    let mut words = words(window);
+    let word = words.find(|w| w.starts_with(|c: char| c.is_ascii_digit()))?;
+    let number_len = word
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+        .unwrap_or(word.len());
+    let number = &word[..number_len];
+    let number = if number.contains('.') && number.contains(',') {
+        number.replace('.', "").replace(',', ".")
+    } else {
+        number.replace(',', ".")
+    };
+    let value: f64 = number.parse().ok()?;
+    let unit = match &word[number_len..] {
+        "" => words.next()?,
+        inline => inline,
+    };
+    let power = match unit.trim_end_matches('.') {
+        "b" | "byte" | "bytes" => 0,
+        "kb" | "kib" => 1,
+        "mb" | "mib" => 2,
+        "gb" | "gib" => 3,
+        "tb" | "tib" => 4,
+        _ => return None,
+    };
+    Some((value * 1024f64.powi(power)).round() as u64)
+}
+
+/// A date such as "31.12.2025" or "2025-12-31", with the time after it if any.
+fn parse_date(window: &str) -> Option<String> {
+    let mut words = words(window).skip_while(|w| !w.starts_with(|c: char| c.is_ascii_digit()));
+    let date = words.next()?;
+    if !(date.contains('.') || date.contains('-')) || date.contains(':') {
+        return None;
+    }
+    let date = date.trim_end_matches('.');
+    match words.next() {
+        Some(time)
+            if time.contains(':') && time.chars().all(|c| c.is_ascii_digit() || c == ':') =>
+        {
+            Some(format!("{} {}", date, time))
+        }
+        _ => Some(date.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: u64 = 1024 * 1024;
+    const GIB: u64 = 1024 * MIB;
+
+    const STATUS_TR: &str = r#"<html><head><title>MaxxArena Hotspot</title>
+<style>.logout { color: red }</style>
+<script>function logout() { document.forms[0].submit(); }</script>
+</head><body>
+<h1>Hesap durumu</h1>
+<table>
+<tr><td>Kalan süre:</td><td>01:20:30</td></tr>
+<tr><td>Kalan kota:</td><td>1,5&nbsp;GB</td></tr>
+<tr><td>Kullanılan:</td><td>512 MB</td></tr>
+<tr><td>Toplam kota:</td><td>2 GB</td></tr>
+<tr><td>Geçerlilik:</td><td>31.12.2026 23:59</td></tr>
+</table>
+<a href="?logout=1">Oturumu kapat</a>
+</body></html>"#;
+
+    const STATUS_DE: &str = r#"<html><body>
+<p>Restzeit: 2 Std 15 Min</p>
+<p>Restvolumen: 1.234,5 MB</p>
+<p>Verbraucht: 765,5 MB</p>
+<p>Gesamtvolumen: 2 GB</p>
+<p>Gültig bis: 2026-12-31</p>
+<a href="?logout=1">Abmelden</a>
+</body></html>"#;
+
+    const STATUS_EN: &str = r#"<html><body>
+<div>Remaining time: 45 min</div>
+<div>Remaining data: 500 MB</div>
+<div>Used: 1.5 GB</div>
+<div>Total: 2 GB</div>
+<div>Valid until: 2026-12-31 18:00</div>
+<form method="post"><button name="logout" value="1">Log out</button></form>
+</body></html>"#;
+
+    // The login page carries the logout handler in its script only
+    const LOGIN_PAGE: &str = r#"<html><head>
+<SCRIPT type="text/javascript">
+  var logoutUrl = "/?logout=1"; // Kalan süre: 99:00:00
+</SCRIPT>
+<style>
+  #logout { display: none }
+</style>
+</head><body>
+<form><input name="lp-input-username"><button>Oturum aç</button></form>
+</body></html>"#;
+
+    #[test]
+    fn status_page_turkish() {
+        let status = Maxxarena.parse_status(STATUS_TR);
+        assert!(status.logged_in);
+        assert_eq!(status.remaining_secs, Some(3600 + 20 * 60 + 30));
+        assert_eq!(status.remaining_bytes, Some(GIB + GIB / 2));
+        assert_eq!(status.used_bytes, Some(512 * MIB));
+        assert_eq!(status.total_bytes, Some(2 * GIB));
+        assert_eq!(status.valid_until.as_deref(), Some("31.12.2026 23:59"));
+    }
+
+    #[test]
+    fn status_page_german() {
+        let status = Maxxarena.parse_status(STATUS_DE);
+        assert!(status.logged_in);
+        assert_eq!(status.remaining_secs, Some(2 * 3600 + 15 * 60));
+        assert_eq!(status.remaining_bytes, Some(1234 * MIB + MIB / 2));
+        assert_eq!(status.used_bytes, Some(765 * MIB + MIB / 2));
+        assert_eq!(status.total_bytes, Some(2 * GIB));
+        assert_eq!(status.valid_until.as_deref(), Some("2026-12-31"));
+    }
+
+    #[test]
+    fn status_page_english() {
+        let status = Maxxarena.parse_status(STATUS_EN);
+        assert!(status.logged_in);
+        assert_eq!(status.remaining_secs, Some(45 * 60));
+        assert_eq!(status.remaining_bytes, Some(500 * MIB));
+        assert_eq!(status.used_bytes, Some(GIB + GIB / 2));
+        assert_eq!(status.total_bytes, Some(2 * GIB));
+        assert_eq!(status.valid_until.as_deref(), Some("2026-12-31 18:00"));
+    }
+
+    #[test]
+    fn script_and_style_are_not_text() {
+        let status = Maxxarena.parse_status(LOGIN_PAGE);
+        assert!(!status.logged_in);
+        assert_eq!(status.remaining_secs, None);
+        assert!(!visible_text(LOGIN_PAGE).contains("logout"));
+        // Never closed: the rest of the page is script
+        assert_eq!(visible_text("a<script>logout b").trim(), "a");
+    }
+
+    #[test]
+    fn sizes_are_not_durations() {
+        assert_eq!(parse_remaining("<p>Kalan: 500 mb</p>"), None);
+        assert_eq!(parse_remaining("<p>Remaining: 500MB</p>"), None);
+        assert_eq!(
+            parse_remaining("<p>Kalan: 1 saat 20 dakika</p>"),
+            Some(Duration::from_secs(4800))
+        );
+        assert_eq!(
+            parse_remaining("<p>Kalan süre: 1sa 20dk</p>"),
+            Some(Duration::from_secs(4800))
+        );
+    }
+
+    #[test]
+    fn huge_numbers_do_not_overflow() {
+        assert_eq!(parse_units(" 18446744073709551615 gün"), None);
+        assert_eq!(parse_units(" 5124095576030431 saat 10 saat"), None);
+        assert_eq!(
+            parse_units(" 2 saat 30 dk"),
+            Some(Duration::from_secs(9000))
+        );
+    }
+
+    // Windows come from `visible_text`, already lowercased
+    #[test]
+    fn size_formats() {
+        assert_eq!(parse_size("1.234,5 mb"), Some(1234 * MIB + MIB / 2));
+        assert_eq!(parse_size("1,5 gb"), Some(GIB + GIB / 2));
+        assert_eq!(parse_size("750mb"), Some(750 * MIB));
+        assert_eq!(parse_size("2 gib"), Some(2 * GIB));
+        assert_eq!(parse_size("12 dakika"), None);
+    }
+
+    #[test]
+    fn clock_must_come_first() {
+        assert_eq!(parse_clock(" 12:34:56"), Some(Duration::from_secs(45296)));
+        assert_eq!(parse_clock(" 02:30"), Some(Duration::from_secs(9000)));
+        assert_eq!(parse_clock(" 3 gün, 12:00"), None);
+        assert_eq!(parse_clock(" 12:75"), None);
+        assert_eq!(parse_clock(" 18446744073709551615:00"), None);
+    }
+
+    #[test]
+    fn login_result_markers() {
+        assert!(Maxxarena.login_succeeded("<p>Oturum açma başarılı</p>"));
+        assert!(!Maxxarena.login_succeeded("<p>Kullanıcı adı veya şifre hatalı</p>"));
+        assert!(!Maxxarena.login_succeeded(LOGIN_PAGE));
+        assert!(Maxxarena.login_succeeded(STATUS_TR));
+    }
+}
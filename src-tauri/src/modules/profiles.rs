@@ -0,0 +1,469 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+
+use super::auth;
+use super::config::{self, Credentials};
+use super::{netwatch, network, tls};
+use crate::AppState;
+
+pub const PROVIDER_NONE: &str = "none";
+const PROFILES_STORE: &str = "profiles.json";
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// The profile of the current network, held in `AppState` and shared with
+/// the HTTP client's resolver and TLS setup.
+pub type ActiveProfile = Arc<Mutex<Option<NetworkProfile>>>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkProfile {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub ssids: Vec<String>,
+    #[serde(default)]
+    pub gateway_macs: Vec<String>,
+    #[serde(default = "default_provider")]
+    pub provider: String,
+    /// Key of a credential set saved with `save_profile_credentials`;
+    /// `None` uses the main credentials.
+    #[serde(default)]
+    pub credentials_ref: Option<String>,
+    #[serde(default)]
+    pub auto_login: bool,
+    #[serde(default)]
+    pub auto_reconnect: bool,
+    #[serde(default)]
+    pub keep_hotspot_on: bool,
+    #[serde(default)]
+    pub probes: Vec<String>,
+    /// Used instead of the system resolver, e.g. the DHCP server of a portal
+    /// that only resolves through its own DNS.
+    #[serde(default)]
+    pub dns_servers: Vec<String>,
+    #[serde(default)]
+    pub tls: PortalTls,
+}
+
+/// Exceptions for portals with self-signed or expired certificates. Pins and
+/// the CA bundle only apply to the portal's own host.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortalTls {
+    /// SHA-256 of the portal certificate, hex with or without colons.
+    #[serde(default)]
+    pub pinned_sha256: Vec<String>,
+    /// Path to a PEM file trusted in addition to the system store.
+    #[serde(default)]
+    pub ca_bundle: String,
+    /// Hosts whose certificate isn't checked at all.
+    #[serde(default)]
+    pub insecure_hosts: Vec<String>,
+}
+
+impl PortalTls {
+    pub fn is_empty(&self) -> bool {
+        self.pinned_sha256.is_empty() && self.ca_bundle.is_empty() && self.insecure_hosts.is_empty()
+    }
+}
+
+pub fn default_provider() -> String {
+    "maxxarena".to_string()
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentNetwork {
+    pub ssid: Option<String>,
+    pub gateway_mac: Option<String>,
+}
+
+pub fn active_profile(app: &AppHandle) -> Option<NetworkProfile> {
+    app.state::<AppState>()
+        .active_profile
+        .lock()
+        .ok()
+        .and_then(|p| p.clone())
+}
+
+fn normalize_mac(mac: &str) -> String {
+    mac.chars()
+        .filter(|c| c.is_ascii_hexdigit())
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+pub fn gateway_mac() -> Option<String> {
+    #[cfg(windows)]
+    {
+        let script = "$g = (Get-NetRoute -DestinationPrefix '0.0.0.0/0' -ErrorAction SilentlyContinue | Sort-Object RouteMetric | Select-Object -First 1).NextHop; if ($g) { (Get-NetNeighbor -IPAddress $g -ErrorAction SilentlyContinue | Select-Object -First 1).LinkLayerAddress }";
+        let output = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", script])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .ok()?;
+        let mac = normalize_mac(String::from_utf8_lossy(&output.stdout).trim());
+        (mac.len() == 12 && mac != "000000000000").then_some(mac)
+    }
+    #[cfg(not(windows))]
+    {
+        let output = Command::new("ip")
+            .args(["route", "show", "default"])
+            .output()
+            .ok()?;
+        let routes = String::from_utf8_lossy(&output.stdout).to_string();
+        let gateway = routes
+            .split_whitespace()
+            .skip_while(|w| *w != "via")
+            .nth(1)?
+            .to_string();
+        let arp = std::fs::read_to_string("/proc/net/arp").ok()?;
+        arp.lines()
+            .skip(1)
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .find(|cols| cols.first() == Some(&gateway.as_str()))
+            .and_then(|cols| cols.get(3).map(|mac| normalize_mac(mac)))
+            .filter(|mac| mac.len() == 12 && mac != "000000000000")
+    }
+}
+
+pub fn current_network(ssid: Option<String>) -> CurrentNetwork {
+    CurrentNetwork {
+        ssid,
+        gateway_mac: gateway_mac(),
+    }
+}
+
+/// A gateway MAC match is more specific than an SSID, so it wins.
+fn match_profile<'a>(
+    profiles: &'a [NetworkProfile],
+    network: &CurrentNetwork,
+) -> Option<&'a NetworkProfile> {
+    let by_mac = network.gateway_mac.as_ref().and_then(|mac| {
+        profiles
+            .iter()
+            .find(|p| p.gateway_macs.iter().any(|m| normalize_mac(m) == *mac))
+    });
+    by_mac.or_else(|| {
+        let ssid = network.ssid.as_ref()?;
+        profiles.iter().find(|p| p.ssids.iter().any(|s| s == ssid))
+    })
+}
+
+pub fn load_profiles(app: &AppHandle) -> Vec<NetworkProfile> {
+    app.store(PROFILES_STORE)
+        .ok()
+        .and_then(|store| store.get("profiles"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// The profile matching the current network, read straight from the store
+/// file for when the app isn't running (CLI).
+pub fn stored_match() -> Option<NetworkProfile> {
+    let profiles: Vec<NetworkProfile> = config::read_store_file(PROFILES_STORE)
+        .and_then(|store| store.get("profiles").cloned())
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+    let network = current_network(netwatch::current_ssid());
+    match_profile(&profiles, &network).cloned()
+}
+
+/// Re-evaluates which profile applies to the current network. Returns the
+/// new profile when the selection changed.
+pub fn refresh_active(app: &AppHandle, ssid: Option<String>) -> Option<Option<NetworkProfile>> {
+    let profiles = load_profiles(app);
+    let network = current_network(ssid);
+    let matched = match_profile(&profiles, &network).cloned();
+    set_active(app, matched, &network)
+}
+
+/// Activates the profile given with `--profile` (by name or id) regardless of
+/// the network, and falls back to matching when no profile has that name.
+fn activate_named(
+    app: &AppHandle,
+    name: &str,
+    ssid: Option<String>,
+) -> Option<Option<NetworkProfile>> {
+    let profiles = load_profiles(app);
+    let network = current_network(ssid);
+    let selected = match find_named(&profiles, name) {
+        Some(profile) => {
+            info!("Using profile {} from the launch options", profile.name);
+            Some(profile.clone())
+        }
+        None => {
+            warn!("Launch profile {:?} not found, matching instead", name);
+            match_profile(&profiles, &network).cloned()
+        }
+    };
+    set_active(app, selected, &network)
+}
+
+fn find_named<'a>(profiles: &'a [NetworkProfile], name: &str) -> Option<&'a NetworkProfile> {
+    let name = name.trim();
+    profiles
+        .iter()
+        .find(|p| p.id == name || p.name.eq_ignore_ascii_case(name))
+}
+
+fn set_active(
+    app: &AppHandle,
+    matched: Option<NetworkProfile>,
+    network: &CurrentNetwork,
+) -> Option<Option<NetworkProfile>> {
+    let state = app.state::<AppState>();
+    let mut active = state.active_profile.lock().ok()?;
+    if *active == matched {
+        return None;
+    }
+    info!(
+        "Active profile: {:?} -> {:?} (ssid {:?}, gateway {:?})",
+        active.as_ref().map(|p| &p.name),
+        matched.as_ref().map(|p| &p.name),
+        network.ssid,
+        network.gateway_mac
+    );
+    *active = matched.clone();
+    drop(active);
+
+    let _ = app.emit("active-profile-changed", &matched);
+    Some(matched)
+}
+
+/// Switches to the profile matching the current network. Returns the newly
+/// activated profile, if the selection changed to one.
+pub async fn switch_for_network(app: &AppHandle, ssid: Option<String>) -> Option<NetworkProfile> {
+    let handle = app.clone();
+    tokio::task::spawn_blocking(move || refresh_active(&handle, ssid))
+        .await
+        .ok()
+        .flatten()
+        .flatten()
+}
+
+/// Initial selection at launch: the `--profile` one when given, otherwise
+/// the same matching as on network changes.
+pub async fn switch_for_launch(
+    app: &AppHandle,
+    ssid: Option<String>,
+    profile: Option<String>,
+) -> Option<NetworkProfile> {
+    let Some(name) = profile else {
+        return switch_for_network(app, ssid).await;
+    };
+    let handle = app.clone();
+    tokio::task::spawn_blocking(move || activate_named(&handle, &name, ssid))
+        .await
+        .ok()
+        .flatten()
+        .flatten()
+}
+
+/// Whether a login nobody asked for may run: never after a manual logout,
+/// and at a known network only when its profile wants auto-login.
+pub fn unattended_login_allowed(app: &AppHandle, profile: Option<&NetworkProfile>) -> bool {
+    if auth::manual_disconnect(app) {
+        info!("Manual logout, skipping automatic login");
+        return false;
+    }
+    profile.is_none_or(|p| p.auto_login && p.provider != PROVIDER_NONE)
+}
+
+/// Logs in right away at networks whose profile asks for it.
+pub async fn auto_login(app: &AppHandle, profile: &NetworkProfile, connected: bool) {
+    if connected || !unattended_login_allowed(app, Some(profile)) {
+        return;
+    }
+    info!("Profile {} wants auto-login, logging in", profile.name);
+    if let Err(e) = login_for_profile(app, profile).await {
+        warn!("Auto-login for profile {} failed: {}", profile.name, e);
+    }
+}
+
+/// The single login attempt made at launch: the profile's auto-login, or
+/// "connect on startup" from the settings or `--connect`.
+pub async fn startup_login(
+    app: &AppHandle,
+    profile: Option<&NetworkProfile>,
+    connect: bool,
+    connected: bool,
+) {
+    if connected {
+        return;
+    }
+    if !(connect || profile.is_some_and(|p| p.auto_login)) {
+        return;
+    }
+    if auth::manual_disconnect(app) {
+        info!("Manual logout, skipping the startup login");
+        return;
+    }
+    let result = match profile {
+        Some(p) if p.provider == PROVIDER_NONE => return,
+        Some(p) => {
+            info!("Logging in at startup with profile {}", p.name);
+            login_for_profile(app, p).await
+        }
+        None => {
+            info!("Connect on startup, logging in");
+            auth::login_with_saved_credentials(app).await
+        }
+    };
+    if let Err(e) = result {
+        warn!("Startup login failed: {}", e);
+    }
+}
+
+pub async fn login_for_profile(app: &AppHandle, profile: &NetworkProfile) -> Result<bool, String> {
+    let credentials = profile_credentials(app, profile.credentials_ref.as_deref()).await?;
+    let result = auth::perform_login(app.clone(), credentials.username, credentials.password).await;
+    auth::publish_status(app, result.is_ok());
+    result
+}
+
+async fn profile_credentials(
+    app: &AppHandle,
+    credentials_ref: Option<&str>,
+) -> Result<Credentials, String> {
+    let Some(key) = credentials_ref else {
+        return config::load_credentials(app.clone()).await;
+    };
+    let store = app.store("credentials.json").map_err(|e| e.to_string())?;
+    store
+        .get(format!("profile:{}", key))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .ok_or_else(|| format!("'{}' için kayıtlı kimlik bilgisi yok", key))
+}
+
+#[tauri::command]
+pub async fn get_profiles(app: AppHandle) -> Vec<NetworkProfile> {
+    load_profiles(&app)
+}
+
+#[tauri::command]
+pub async fn save_profiles(app: AppHandle, profiles: Vec<NetworkProfile>) -> Result<(), String> {
+    for profile in &profiles {
+        tls::validate(&profile.tls).map_err(|e| format!("{}: {}", profile.name, e))?;
+    }
+    let store = app.store(PROFILES_STORE).map_err(|e| e.to_string())?;
+    store.set("profiles", serde_json::json!(profiles));
+    store.save().map_err(|e| e.to_string())?;
+
+    let ssid = tokio::task::spawn_blocking(netwatch::current_ssid)
+        .await
+        .unwrap_or(None);
+    if let Some(profile) = switch_for_network(&app, ssid).await {
+        let connected = network::is_online(&app.state::<AppState>().http).await;
+        auto_login(&app, &profile, connected).await;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_active_profile(app: AppHandle) -> Option<NetworkProfile> {
+    active_profile(&app)
+}
+
+#[tauri::command]
+pub async fn detect_current_network() -> CurrentNetwork {
+    tokio::task::spawn_blocking(|| current_network(netwatch::current_ssid()))
+        .await
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn save_profile_credentials(
+    app: AppHandle,
+    credentials_ref: String,
+    username: String,
+    password: String,
+) -> Result<(), String> {
+    let store = app.store("credentials.json").map_err(|e| e.to_string())?;
+    store.set(
+        format!("profile:{}", credentials_ref),
+        serde_json::json!(Credentials { username, password }),
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(id: &str, ssids: &[&str], gateway_macs: &[&str]) -> NetworkProfile {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": format!("Profil {}", id),
+            "ssids": ssids,
+            "gatewayMacs": gateway_macs,
+        }))
+        .unwrap()
+    }
+
+    fn network(ssid: Option<&str>, gateway_mac: Option<&str>) -> CurrentNetwork {
+        CurrentNetwork {
+            ssid: ssid.map(str::to_string),
+            gateway_mac: gateway_mac.map(normalize_mac),
+        }
+    }
+
+    #[test]
+    fn mac_normalization() {
+        assert_eq!(normalize_mac("aa:bb:cc:dd:ee:0f"), "AABBCCDDEE0F");
+        assert_eq!(normalize_mac("AA-BB-CC-DD-EE-0F"), "AABBCCDDEE0F");
+        assert_eq!(normalize_mac(" aabb.ccdd.ee0f\n"), "AABBCCDDEE0F");
+    }
+
+    #[test]
+    fn matches_by_ssid() {
+        let profiles = [
+            profile("yurt", &["Yurt-WiFi"], &[]),
+            profile("ev", &["Ev"], &[]),
+        ];
+        let matched = match_profile(&profiles, &network(Some("Ev"), None));
+        assert_eq!(matched.map(|p| p.id.as_str()), Some("ev"));
+        // SSIDs are case-sensitive
+        assert!(match_profile(&profiles, &network(Some("ev"), None)).is_none());
+    }
+
+    #[test]
+    fn gateway_mac_wins_over_ssid() {
+        let profiles = [
+            profile("yurt", &["Yurt-WiFi"], &[]),
+            profile("kat3", &[], &["aa-bb-cc-dd-ee-0f"]),
+        ];
+        let current = network(Some("Yurt-WiFi"), Some("AA:BB:CC:DD:EE:0F"));
+        let matched = match_profile(&profiles, &current);
+        assert_eq!(matched.map(|p| p.id.as_str()), Some("kat3"));
+    }
+
+    #[test]
+    fn no_match() {
+        let profiles = [profile("yurt", &["Yurt-WiFi"], &["AABBCCDDEE0F"])];
+        assert!(match_profile(&profiles, &network(Some("Kafe"), Some("112233445566"))).is_none());
+        assert!(match_profile(&profiles, &network(None, None)).is_none());
+        assert!(match_profile(&[], &network(Some("Yurt-WiFi"), None)).is_none());
+    }
+
+    #[test]
+    fn named_profiles() {
+        let profiles = [profile("yurt", &[], &[]), profile("ev", &[], &[])];
+        assert_eq!(
+            find_named(&profiles, "ev").map(|p| p.id.as_str()),
+            Some("ev")
+        );
+        assert_eq!(
+            find_named(&profiles, " profil YURT ").map(|p| p.id.as_str()),
+            Some("yurt")
+        );
+        assert!(find_named(&profiles, "kafe").is_none());
+    }
+}
@@ -0,0 +1,322 @@
+use chrono::{DateTime, Local};
+use log::{info, warn};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::{auth, portal, profiles, tray};
+use crate::AppState;
+
+const TICK: Duration = Duration::from_secs(15);
+/// How often the portal is asked for the remaining time while we don't know it.
+const RESYNC_EVERY: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExpirySource {
+    /// Parsed from the portal page.
+    Portal,
+    /// Login time plus the configured ticket length.
+    Settings,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub logged_in_at: String,
+    pub expires_at: Option<String>,
+    pub remaining_secs: Option<u64>,
+    pub source: Option<ExpirySource>,
+}
+
+/// A login made by the app; kept in `AppState`.
+pub struct Session {
+    logged_in_at: DateTime<Local>,
+    expires_at: Option<DateTime<Local>>,
+    source: Option<ExpirySource>,
+    relogin_attempted: bool,
+}
+
+impl Session {
+    fn new(now: DateTime<Local>, remaining: Option<(Duration, ExpirySource)>) -> Self {
+        Self {
+            logged_in_at: now,
+            expires_at: remaining
+                .and_then(|(r, _)| chrono::Duration::from_std(r).ok())
+                .map(|r| now + r),
+            source: remaining.map(|(_, source)| source),
+            relogin_attempted: false,
+        }
+    }
+
+    fn remaining(&self) -> Option<Duration> {
+        self.remaining_at(Local::now())
+    }
+
+    fn remaining_at(&self, now: DateTime<Local>) -> Option<Duration> {
+        self.expires_at
+            .map(|at| (at - now).to_std().unwrap_or(Duration::ZERO))
+    }
+
+    /// An expiry the portal hasn't confirmed is asked for again every
+    /// `RESYNC_EVERY`.
+    fn resync_due(&self, last_resync: Option<Instant>, now: Instant) -> bool {
+        self.source != Some(ExpirySource::Portal)
+            && last_resync.is_none_or(|at| now.saturating_duration_since(at) >= RESYNC_EVERY)
+    }
+
+    /// Within `before` of the expiry, once per session.
+    fn relogin_due(&self, now: DateTime<Local>, before: Duration) -> bool {
+        !self.relogin_attempted && self.remaining_at(now).is_some_and(|r| r <= before)
+    }
+
+    fn info(&self) -> SessionInfo {
+        SessionInfo {
+            logged_in_at: self.logged_in_at.to_rfc3339(),
+            expires_at: self.expires_at.map(|t| t.to_rfc3339()),
+            remaining_secs: self.remaining().map(|r| r.as_secs()),
+            source: self.source,
+        }
+    }
+}
+
+pub fn info(app: &AppHandle) -> Option<SessionInfo> {
+    let state = app.state::<AppState>();
+    let session = state.session.lock().ok()?;
+    session.as_ref().map(Session::info)
+}
+
+/// "1 sa 20 dk" style countdown for the tray tooltip.
+pub fn tooltip_suffix(app: &AppHandle) -> Option<String> {
+    let state = app.state::<AppState>();
+    let remaining = state.session.lock().ok()?.as_ref()?.remaining()?;
+    Some(countdown(remaining))
+}
+
+fn countdown(remaining: Duration) -> String {
+    let remaining = remaining.as_secs();
+    let (hours, minutes) = (remaining / 3600, (remaining % 3600) / 60);
+    if hours > 0 {
+        format!("Kalan {} sa {} dk", hours, minutes)
+    } else {
+        format!("Kalan {} dk", minutes.max(1))
+    }
+}
+
+fn publish(app: &AppHandle) {
+    let _ = app.emit("session-update", info(app));
+    tray::refresh_tooltip(app);
+}
+
+/// Records a fresh login. `remaining` is what the login response showed; when
+/// it didn't show anything the status page and then the settings are tried.
+pub async fn start(app: &AppHandle, remaining: Option<Duration>) {
+    let remaining = match remaining {
+        Some(r) => Some((r, ExpirySource::Portal)),
+        None => fetch_remaining(app)
+            .await
+            .map(|r| (r, ExpirySource::Portal)),
+    }
+    .or_else(|| {
+        let minutes = app
+            .state::<AppState>()
+            .settings
+            .lock()
+            .map(|s| s.session.ticket_minutes)
+            .unwrap_or(0);
+        (minutes > 0).then(|| (Duration::from_secs(minutes * 60), ExpirySource::Settings))
+    });
+
+    let session = Session::new(Local::now(), remaining);
+    match session.expires_at {
+        Some(at) => info!("Session started, ticket expires at {}", at.to_rfc3339()),
+        None => info!("Session started, ticket expiry unknown"),
+    }
+    if let Ok(mut current) = app.state::<AppState>().session.lock() {
+        *current = Some(session);
+    }
+    publish(app);
+}
+
+pub fn clear(app: &AppHandle) {
+    let had_session = app
+        .state::<AppState>()
+        .session
+        .lock()
+        .map(|mut s| s.take().is_some())
+        .unwrap_or(false);
+    if had_session {
+        publish(app);
+    }
+}
+
+async fn fetch_remaining(app: &AppHandle) -> Option<Duration> {
+    let status = portal::fetch_status(&app.state::<AppState>().http)
+        .await
+        .ok()?;
+    status.remaining_secs.map(Duration::from_secs)
+}
+
+/// Only a profile naming a known portal has a status page to ask. Without
+/// one there's no telling which portal, if any, we're behind.
+fn can_resync(app: &AppHandle) -> bool {
+    profiles::active_profile(app).is_some_and(|p| portal::provider(&p.provider).is_some())
+}
+
+async fn relogin(app: &AppHandle) -> Result<bool, String> {
+    match profiles::active_profile(app) {
+        Some(profile) if profile.provider == profiles::PROVIDER_NONE => Ok(false),
+        Some(profile) => profiles::login_for_profile(app, &profile).await,
+        None => auth::login_with_saved_credentials(app).await,
+    }
+}
+
+/// Publishes the countdown and logs in again shortly before the ticket runs
+/// out, so a running download doesn't hit the portal page.
+pub fn start_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        info!("Session scheduler started");
+        let mut last_resync: Option<Instant> = None;
+        loop {
+            tokio::time::sleep(TICK).await;
+
+            let settings = app
+                .state::<AppState>()
+                .settings
+                .lock()
+                .map(|s| s.session.clone())
+                .unwrap_or_default();
+
+            let resync = app
+                .state::<AppState>()
+                .session
+                .lock()
+                .map(|s| {
+                    s.as_ref()
+                        .is_some_and(|s| s.resync_due(last_resync, Instant::now()))
+                })
+                .unwrap_or(false);
+            if resync && can_resync(&app) {
+                last_resync = Some(Instant::now());
+                if let Some(remaining) = fetch_remaining(&app).await {
+                    let expires_at = chrono::Duration::from_std(remaining)
+                        .ok()
+                        .map(|r| Local::now() + r);
+                    if let Ok(mut session) = app.state::<AppState>().session.lock() {
+                        // Cleared while the request was in flight
+                        if let Some(session) = session.as_mut() {
+                            session.expires_at = expires_at;
+                            session.source = Some(ExpirySource::Portal);
+                        }
+                    }
+                }
+            }
+
+            let due = app
+                .state::<AppState>()
+                .session
+                .lock()
+                .map(|mut session| match session.as_mut() {
+                    Some(s) => {
+                        let before = Duration::from_secs(settings.relogin_before_secs);
+                        let due = s.relogin_due(Local::now(), before);
+                        if due && settings.proactive_relogin {
+                            s.relogin_attempted = true;
+                        }
+                        due
+                    }
+                    None => false,
+                })
+                .unwrap_or(false);
+            publish(&app);
+
+            if due && settings.proactive_relogin && !auth::manual_disconnect(&app) {
+                info!("Ticket is about to expire, logging in again");
+                match relogin(&app).await {
+                    Ok(true) => info!("Proactive relogin done"),
+                    Ok(false) => {}
+                    Err(e) => warn!("Proactive relogin failed: {}", e),
+                }
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_session_info(app: AppHandle) -> Option<SessionInfo> {
+    info(&app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn at(now: DateTime<Local>, offset: Duration) -> DateTime<Local> {
+        now + chrono::Duration::from_std(offset).unwrap()
+    }
+
+    #[test]
+    fn expiry_counts_down_from_login() {
+        let now = Local::now();
+        let session = Session::new(now, Some((90 * MINUTE, ExpirySource::Settings)));
+        assert_eq!(session.expires_at, Some(at(now, 90 * MINUTE)));
+        assert_eq!(session.source, Some(ExpirySource::Settings));
+        assert_eq!(
+            session.remaining_at(at(now, 30 * MINUTE)),
+            Some(60 * MINUTE)
+        );
+        // Past the expiry it stays at zero
+        assert_eq!(
+            session.remaining_at(at(now, 120 * MINUTE)),
+            Some(Duration::ZERO)
+        );
+
+        let unknown = Session::new(now, None);
+        assert_eq!(unknown.remaining_at(now), None);
+        assert_eq!(unknown.source, None);
+    }
+
+    #[test]
+    fn countdown_text() {
+        assert_eq!(
+            countdown(80 * MINUTE + Duration::from_secs(59)),
+            "Kalan 1 sa 20 dk"
+        );
+        assert_eq!(countdown(45 * MINUTE), "Kalan 45 dk");
+        assert_eq!(countdown(Duration::from_secs(20)), "Kalan 1 dk");
+    }
+
+    #[test]
+    fn relogin_due_once_before_expiry() {
+        let now = Local::now();
+        let mut session = Session::new(now, Some((60 * MINUTE, ExpirySource::Portal)));
+        let before = 2 * MINUTE;
+
+        assert!(!session.relogin_due(at(now, 57 * MINUTE), before));
+        assert!(session.relogin_due(at(now, 58 * MINUTE), before));
+        assert!(session.relogin_due(at(now, 70 * MINUTE), before));
+        session.relogin_attempted = true;
+        assert!(!session.relogin_due(at(now, 59 * MINUTE), before));
+
+        // Without a known expiry there's nothing to be early for
+        assert!(!Session::new(now, None).relogin_due(at(now, 600 * MINUTE), before));
+    }
+
+    #[test]
+    fn resync_until_the_portal_confirms() {
+        let now = Instant::now();
+        let later = now + RESYNC_EVERY;
+        let settings = Session::new(Local::now(), Some((60 * MINUTE, ExpirySource::Settings)));
+        assert!(settings.resync_due(None, now));
+        assert!(!settings.resync_due(Some(now), now + RESYNC_EVERY / 2));
+        assert!(settings.resync_due(Some(now), later));
+
+        let unknown = Session::new(Local::now(), None);
+        assert!(unknown.resync_due(Some(now), later));
+
+        let portal = Session::new(Local::now(), Some((60 * MINUTE, ExpirySource::Portal)));
+        assert!(!portal.resync_due(None, now));
+    }
+}
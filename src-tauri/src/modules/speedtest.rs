@@ -0,0 +1,436 @@
+use log::{info, warn};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::config::SpeedTestSettings;
+use super::{history, latency};
+use crate::AppState;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+const UPLOAD_REQUEST: u64 = 16 * 1024 * 1024;
+const UPLOAD_CHUNK: usize = 64 * 1024;
+
+static ZEROS: [u8; UPLOAD_CHUNK] = [0; UPLOAD_CHUNK];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Phase {
+    Download,
+    Upload,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedTestProgress {
+    pub phase: Phase,
+    pub mbps: f64,
+    /// 0-1 over the whole phase, warm-up included.
+    pub progress: f64,
+    pub warming_up: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedTestResult {
+    pub download_mbps: f64,
+    pub upload_mbps: f64,
+    pub download_bytes: u64,
+    pub upload_bytes: u64,
+    pub streams: usize,
+    pub server: String,
+}
+
+fn mbps(bytes: u64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs <= 0.0 {
+        return 0.0;
+    }
+    (bytes as f64 * 8.0 / secs / 1_000_000.0 * 100.0).round() / 100.0
+}
+
+fn host_of(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| url.to_string())
+}
+
+async fn download_stream(
+    client: reqwest::Client,
+    url: String,
+    bytes: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+) -> Result<(), String> {
+    while !stop.load(Ordering::Relaxed) {
+        let mut resp = client
+            .get(&url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| e.to_string())?;
+        while let Some(chunk) = resp.chunk().await.map_err(|e| e.to_string())? {
+            bytes.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+            if stop.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A body of `len` zero bytes, counted chunk by chunk as the connection
+/// takes them. A slow uplink still moves the counter, and a request cut off
+/// at the deadline counts what it sent.
+fn counted_body(len: u64, bytes: Arc<AtomicU64>) -> reqwest::Body {
+    let chunks = (0..len).step_by(UPLOAD_CHUNK).map(move |sent| {
+        let n = (len - sent).min(UPLOAD_CHUNK as u64);
+        bytes.fetch_add(n, Ordering::Relaxed);
+        Ok::<_, std::io::Error>(&ZEROS[..n as usize])
+    });
+    reqwest::Body::wrap_stream(futures_util::stream::iter(chunks))
+}
+
+async fn upload_stream(
+    client: reqwest::Client,
+    url: String,
+    bytes: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+) -> Result<(), String> {
+    while !stop.load(Ordering::Relaxed) {
+        client
+            .post(&url)
+            .header(reqwest::header::CONTENT_LENGTH, UPLOAD_REQUEST)
+            .body(counted_body(UPLOAD_REQUEST, bytes.clone()))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Runs one phase on `streams` parallel connections. Bytes moved during the
+/// warm-up are discarded so TCP slow start doesn't drag the result down.
+async fn run_phase(
+    client: &reqwest::Client,
+    phase: Phase,
+    url: &str,
+    settings: &SpeedTestSettings,
+    cancel: &AtomicBool,
+    on_progress: &(dyn Fn(SpeedTestProgress) + Sync),
+) -> Result<(f64, u64), String> {
+    let bytes = Arc::new(AtomicU64::new(0));
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut tasks = tokio::task::JoinSet::new();
+    for _ in 0..settings.streams.max(1) {
+        let (client, url, bytes, stop) =
+            (client.clone(), url.to_string(), bytes.clone(), stop.clone());
+        match phase {
+            Phase::Download => tasks.spawn(download_stream(client, url, bytes, stop)),
+            Phase::Upload => tasks.spawn(upload_stream(client, url, bytes, stop)),
+        };
+    }
+
+    let warmup = Duration::from_secs(settings.warmup_secs);
+    let total = warmup + Duration::from_secs(settings.duration_secs.max(1));
+    let start = Instant::now();
+    let mut measured_from: Option<(Instant, u64)> = None;
+    let mut failure = None;
+
+    loop {
+        tokio::time::sleep(SAMPLE_INTERVAL).await;
+        let elapsed = start.elapsed();
+        let moved = bytes.load(Ordering::Relaxed);
+
+        if cancel.load(Ordering::Relaxed) {
+            failure = Some("Hız testi iptal edildi".to_string());
+            break;
+        }
+        // A stream that dies early fails the phase only if none are left
+        while let Some(joined) = tasks.try_join_next() {
+            if let Ok(Err(e)) = joined {
+                warn!("Speed test {:?} stream failed: {}", phase, e);
+                failure.get_or_insert(e);
+            }
+        }
+        if tasks.is_empty() {
+            break;
+        }
+
+        if measured_from.is_none() && elapsed >= warmup {
+            measured_from = Some((Instant::now(), moved));
+        }
+        let current = match measured_from {
+            Some((since, base)) => mbps(moved - base, since.elapsed()),
+            None => mbps(moved, elapsed),
+        };
+        on_progress(SpeedTestProgress {
+            phase,
+            mbps: current,
+            progress: (elapsed.as_secs_f64() / total.as_secs_f64()).min(1.0),
+            warming_up: measured_from.is_none(),
+        });
+
+        if elapsed >= total {
+            break;
+        }
+    }
+
+    stop.store(true, Ordering::Relaxed);
+    tasks.abort_all();
+
+    let moved = bytes.load(Ordering::Relaxed);
+    match (measured_from, failure) {
+        (_, Some(e)) if cancel.load(Ordering::Relaxed) || measured_from.is_none() => Err(e),
+        (Some((since, base)), _) => Ok((mbps(moved - base, since.elapsed()), moved)),
+        (None, _) => Ok((mbps(moved, start.elapsed()), moved)),
+    }
+}
+
+#[tauri::command]
+pub async fn run_speed_test(app: AppHandle) -> Result<SpeedTestResult, String> {
+    let state = app.state::<AppState>();
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut running = state.speed_test.lock().map_err(|e| e.to_string())?;
+        if running.is_some() {
+            return Err("Hız testi zaten çalışıyor".to_string());
+        }
+        *running = Some(cancel.clone());
+    }
+
+    let settings = state
+        .settings
+        .lock()
+        .map(|s| s.speed_test.clone())
+        .unwrap_or_default();
+    let result = run(&app, &settings, &cancel).await;
+
+    if let Ok(mut running) = state.speed_test.lock() {
+        *running = None;
+    }
+
+    if let Ok(result) = &result {
+        let stats = state.latency.lock().map(|h| h.stats()).unwrap_or_default();
+        let (app, result) = (app.clone(), result.clone());
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = history::record_speed_test(&app, &result, latency::headline(&stats)) {
+                warn!("Could not record speed test history: {}", e);
+            }
+        });
+    }
+    result
+}
+
+async fn run(
+    app: &AppHandle,
+    settings: &SpeedTestSettings,
+    cancel: &AtomicBool,
+) -> Result<SpeedTestResult, String> {
+    let client = app
+        .state::<AppState>()
+        .http
+        .builder()
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    info!(
+        "Speed test: {} streams, download {}, upload {}",
+        settings.streams, settings.download_url, settings.upload_url
+    );
+    let emit = |progress: SpeedTestProgress| {
+        let _ = app.emit("speed-test-progress", progress);
+    };
+    let (download_mbps, download_bytes) = run_phase(
+        &client,
+        Phase::Download,
+        &settings.download_url,
+        settings,
+        cancel,
+        &emit,
+    )
+    .await?;
+    let (upload_mbps, upload_bytes) = run_phase(
+        &client,
+        Phase::Upload,
+        &settings.upload_url,
+        settings,
+        cancel,
+        &emit,
+    )
+    .await?;
+    info!(
+        "Speed test done: {} Mbps down, {} Mbps up",
+        download_mbps, upload_mbps
+    );
+
+    Ok(SpeedTestResult {
+        download_mbps,
+        upload_mbps,
+        download_bytes,
+        upload_bytes,
+        streams: settings.streams.max(1),
+        server: host_of(&settings.download_url),
+    })
+}
+
+#[tauri::command]
+pub fn cancel_speed_test(state: tauri::State<AppState>) -> bool {
+    match state.speed_test.lock().ok().and_then(|r| r.clone()) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Keep-alive HTTP/1.1 server on loopback. GETs get `download` zero
+    /// bytes; POST bodies are drained, with `pause` after every read.
+    async fn serve(download: usize, pause: Option<Duration>) -> (String, Arc<AtomicU64>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let received = Arc::new(AtomicU64::new(0));
+        let counter = received.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(handle(socket, download, pause, counter.clone()));
+            }
+        });
+        (url, received)
+    }
+
+    async fn handle(
+        socket: TcpStream,
+        download: usize,
+        pause: Option<Duration>,
+        received: Arc<AtomicU64>,
+    ) -> std::io::Result<()> {
+        let mut reader = BufReader::new(socket);
+        let mut buf = vec![0u8; 16 * 1024];
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).await? == 0 {
+                return Ok(());
+            }
+            let get = line.starts_with("GET");
+            let mut length = 0u64;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).await?;
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+            }
+            while length > 0 {
+                let want = length.min(buf.len() as u64) as usize;
+                let n = reader.read(&mut buf[..want]).await?;
+                if n == 0 {
+                    return Ok(());
+                }
+                length -= n as u64;
+                received.fetch_add(n as u64, Ordering::Relaxed);
+                if let Some(pause) = pause {
+                    tokio::time::sleep(pause).await;
+                }
+            }
+            let body = if get { download } else { 0 };
+            let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", body);
+            let socket = reader.get_mut();
+            socket.write_all(head.as_bytes()).await?;
+            socket.write_all(&vec![0u8; body]).await?;
+        }
+    }
+
+    fn settings(url: &str) -> SpeedTestSettings {
+        SpeedTestSettings {
+            download_url: url.to_string(),
+            upload_url: url.to_string(),
+            streams: 2,
+            warmup_secs: 0,
+            duration_secs: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn upload_counts_bytes_before_the_request_finishes() {
+        // ~800 KB/s, so not even one request can complete in the window
+        let (url, received) = serve(0, Some(Duration::from_millis(20))).await;
+        let bytes = Arc::new(AtomicU64::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let task = tokio::spawn(upload_stream(
+            reqwest::Client::new(),
+            url,
+            bytes.clone(),
+            stop.clone(),
+        ));
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        stop.store(true, Ordering::Relaxed);
+        task.abort();
+
+        let counted = bytes.load(Ordering::Relaxed);
+        let received = received.load(Ordering::Relaxed);
+        assert!(received > 0);
+        assert!(counted >= received, "{} < {}", counted, received);
+        assert!(counted < UPLOAD_REQUEST, "counted {}", counted);
+    }
+
+    #[tokio::test]
+    async fn phases_against_local_server() {
+        let (url, received) = serve(1024 * 1024, None).await;
+        let client = reqwest::Client::new();
+        let settings = settings(&url);
+        let cancel = AtomicBool::new(false);
+        let samples = std::sync::Mutex::new(Vec::new());
+        let record = |p: SpeedTestProgress| samples.lock().unwrap().push(p.phase);
+
+        let (down_mbps, down_bytes) =
+            run_phase(&client, Phase::Download, &url, &settings, &cancel, &record)
+                .await
+                .unwrap();
+        assert!(down_mbps > 0.0);
+        assert!(down_bytes > 0);
+
+        let (up_mbps, up_bytes) =
+            run_phase(&client, Phase::Upload, &url, &settings, &cancel, &record)
+                .await
+                .unwrap();
+        assert!(up_mbps > 0.0);
+        assert!(up_bytes > 0);
+        assert!(received.load(Ordering::Relaxed) > 0);
+
+        let samples = samples.lock().unwrap();
+        assert!(samples.contains(&Phase::Download) && samples.contains(&Phase::Upload));
+    }
+
+    #[tokio::test]
+    async fn cancel_fails_the_phase() {
+        let (url, _) = serve(1024 * 1024, None).await;
+        let cancel = AtomicBool::new(true);
+        let result = run_phase(
+            &reqwest::Client::new(),
+            Phase::Download,
+            &url,
+            &settings(&url),
+            &cancel,
+            &|_| {},
+        )
+        .await;
+        assert_eq!(result, Err("Hız testi iptal edildi".to_string()));
+    }
+}
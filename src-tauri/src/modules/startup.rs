@@ -1,192 +1,1379 @@
-#[cfg(windows)]
-use std::os::windows::process::CommandExt;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
 use tauri::utils::platform::current_exe;
+use windows::core::{BSTR, HRESULT};
+use windows::Win32::Foundation::{ERROR_FILE_NOT_FOUND, E_ACCESSDENIED};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED,
+};
+use windows::Win32::System::TaskScheduler::{
+    ITaskFolder, ITaskService, TaskScheduler, TASK_CREATE_OR_UPDATE, TASK_LOGON_INTERACTIVE_TOKEN,
+};
+use windows::Win32::System::Variant::VARIANT;
 use winreg::{enums::*, RegKey};
 
-const CREATE_NO_WINDOW: u32 = 0x08000000;
+use super::config::Settings;
+use super::launcher_service::{self, LauncherConfig};
+use crate::AppState;
 
-#[tauri::command]
-pub async fn enable_startup(_minimized: bool) -> Result<(), String> {
-    let exe_path = current_exe().map_err(|e| e.to_string())?;
-    // We expect the launcher to be in the same directory as the main executable
-    // In dev: src-tauri/target/debug/hotspot-launcher.exe
-    // In prod: The installer places them together
-    let launcher_path = exe_path
-        .parent()
-        .ok_or("Invalid parent")?
-        .join("hotspot-launcher.exe");
-
-    let raw_path = launcher_path.to_str().ok_or("Invalid path")?;
+const TASK_NAME: &str = "HotspotManager";
+const RUN_KEY_PATH: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+const RUN_VALUE_NAME: &str = "HotspotManager";
+// Values written by the old tauri-plugin-autostart based builds
+const LEGACY_RUN_VALUES: &[&str] = &["Hotspot Manager", "com.hotspot.app", "hotspot"];
+const LAUNCHER_EXE: &str = "hotspot-launcher.exe";
+const ELEVATED_TIMEOUT_MS: u32 = 60_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StartupMethod {
+    #[default]
+    Service,
+    Task,
+    RunKey,
+}
+
+/// Launch arguments shared by every startup method and parsed back on launch.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupOptions {
+    pub minimized: bool,
+    pub delay_secs: u64,
+    pub connect: bool,
+    pub profile: Option<String>,
+}
+
+impl StartupOptions {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            minimized: settings.start_in_tray,
+            delay_secs: settings.startup_delay_secs,
+            connect: settings.connect_on_startup,
+            profile: settings
+                .startup_profile
+                .clone()
+                .filter(|p| !p.trim().is_empty()),
+        }
+    }
+
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.minimized {
+            args.push("--minimized".to_string());
+        }
+        if self.delay_secs > 0 {
+            args.push("--startup-delay".to_string());
+            args.push(self.delay_secs.to_string());
+        }
+        if self.connect {
+            args.push("--connect".to_string());
+        }
+        if let Some(profile) = &self.profile {
+            args.push("--profile".to_string());
+            args.push(profile.clone());
+        }
+        args
+    }
+
+    pub fn parse(args: &[String]) -> Self {
+        let mut options = Self::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--minimized" => options.minimized = true,
+                "--connect" => options.connect = true,
+                "--startup-delay" => {
+                    options.delay_secs = iter.next().and_then(|v| v.parse().ok()).unwrap_or(0)
+                }
+                "--profile" => options.profile = iter.next().cloned(),
+                _ => {}
+            }
+        }
+        options
+    }
+
+    fn arguments(&self) -> String {
+        self.to_args()
+            .iter()
+            .map(|arg| launcher_service::quote_arg(arg))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn command_line(&self, exe: &Path) -> String {
+        launcher_service::command_line(exe, &self.to_args())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StartupErrorKind {
+    Cancelled,
+    AccessDenied,
+    LauncherMissing,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupError {
+    pub kind: StartupErrorKind,
+    pub method: StartupMethod,
+    pub message: String,
+}
+
+impl StartupError {
+    fn new(kind: StartupErrorKind, method: StartupMethod, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            method,
+            message: message.into(),
+        }
+    }
+
+    fn failed(method: StartupMethod, message: impl Into<String>) -> Self {
+        Self::new(StartupErrorKind::Failed, method, message)
+    }
+
+    fn from_io(method: StartupMethod, error: std::io::Error) -> Self {
+        let kind = if error.kind() == std::io::ErrorKind::PermissionDenied {
+            StartupErrorKind::AccessDenied
+        } else {
+            StartupErrorKind::Failed
+        };
+        Self::new(kind, method, error.to_string())
+    }
+}
+
+impl std::fmt::Display for StartupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}: {}", self.method, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupArtifact {
+    pub method: StartupMethod,
+    pub name: String,
+    pub command: String,
+    pub target: Option<PathBuf>,
+    pub target_exists: bool,
+    pub legacy: bool,
+    /// Registered with other launch options than the saved settings.
+    pub outdated: bool,
+}
+
+impl StartupArtifact {
+    fn new(method: StartupMethod, name: &str, command: String, legacy: bool) -> Self {
+        let target = command_target(&command);
+        let target_exists = target.as_ref().map(|t| t.exists()).unwrap_or(false);
+        Self {
+            method,
+            name: name.to_string(),
+            command,
+            target,
+            target_exists,
+            legacy,
+            outdated: false,
+        }
+    }
+}
+
+pub trait StartupBackend: Send + Sync {
+    fn method(&self) -> StartupMethod;
+    fn enable(&self, options: &StartupOptions) -> Result<(), StartupError>;
+    fn disable(&self) -> Result<(), StartupError>;
+    fn is_enabled(&self) -> Result<bool, StartupError>;
+    fn artifacts(&self) -> Result<Vec<StartupArtifact>, StartupError>;
+
+    fn remove(&self, _artifact: &StartupArtifact) -> Result<(), StartupError> {
+        self.disable()
+    }
+
+    /// Whether `artifact` starts its target with exactly `options`.
+    fn launches_with(&self, artifact: &StartupArtifact, options: &StartupOptions) -> bool {
+        artifact
+            .target
+            .as_ref()
+            .is_some_and(|target| artifact.command.trim() == options.command_line(target))
+    }
+}
+
+/// The machine the startup entries live on. Held in `AppState` so the
+/// fallback and repair decisions can run against a fake one.
+pub trait StartupHost: Send + Sync {
+    fn backend(&self, method: StartupMethod) -> Box<dyn StartupBackend>;
+    fn elevation(&self) -> Elevation;
+}
+
+pub struct SystemHost;
+
+impl StartupHost for SystemHost {
+    fn backend(&self, method: StartupMethod) -> Box<dyn StartupBackend> {
+        match method {
+            StartupMethod::Service => Box::new(ServiceBackend),
+            StartupMethod::Task => Box::new(TaskBackend),
+            StartupMethod::RunKey => Box::new(RunKeyBackend),
+        }
+    }
+
+    fn elevation(&self) -> Elevation {
+        elevation()
+    }
+}
+
+const METHODS: [StartupMethod; 3] = [
+    StartupMethod::Service,
+    StartupMethod::Task,
+    StartupMethod::RunKey,
+];
+
+fn installed_backends(host: &dyn StartupHost) -> Vec<Box<dyn StartupBackend>> {
+    METHODS.into_iter().map(|m| host.backend(m)).collect()
+}
+
+/// Extracts the executable from a command line such as `"C:\x\app.exe" --flag`.
+fn command_target(command: &str) -> Option<PathBuf> {
+    let command = command.trim();
+    let path = match command.strip_prefix('"') {
+        Some(rest) => rest.split('"').next()?,
+        None => match command.to_ascii_lowercase().find(".exe") {
+            Some(end) => &command[..end + 4],
+            None => command.split_whitespace().next()?,
+        },
+    };
+    if path.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(path))
+    }
+}
+
+fn app_exe() -> Result<PathBuf, String> {
+    let exe = current_exe().map_err(|e| e.to_string())?;
+    let raw = exe.to_str().ok_or("Invalid path")?;
     // Remove UNC prefix if present
-    let launcher_str = raw_path.strip_prefix("\\\\?\\").unwrap_or(raw_path);
-
-    // Create batch script for reliable execution via sc.exe
-    let temp_dir = std::env::temp_dir();
-    let batch_path = temp_dir.join("hotspot_service_install.bat");
-
-    let batch_content = format!(
-        r#"@echo off
-sc stop HotspotLauncher >nul 2>&1
-sc delete HotspotLauncher >nul 2>&1
-sc create HotspotLauncher binPath= "{}" start= auto DisplayName= "Hotspot Manager Launcher"
-sc start HotspotLauncher
-"#,
-        launcher_str
-    );
+    Ok(PathBuf::from(raw.strip_prefix(r"\\?\").unwrap_or(raw)))
+}
 
-    std::fs::write(&batch_path, batch_content).map_err(|e| e.to_string())?;
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Elevation {
+    pub elevated: bool,
+    pub can_elevate: bool,
+}
 
-    // Run batch as Admin
-    let batch_path_str = batch_path.to_str().ok_or("Invalid batch path")?;
-    // This part requires interaction if UAC is not elevated, but we use RunAs
-    let ps_command = format!(
-        "Start-Process cmd.exe -Verb RunAs -WindowStyle Hidden -Wait -ArgumentList '/c \"{}\"'",
-        batch_path_str
-    );
+/// A split (limited) token means the user is an administrator behind UAC;
+/// a default token without elevation means the prompt would ask for
+/// credentials the user does not have.
+pub fn elevation() -> Elevation {
+    #[cfg(windows)]
+    unsafe {
+        use windows::Win32::Foundation::{CloseHandle, HANDLE};
+        use windows::Win32::Security::{
+            GetTokenInformation, TokenElevation, TokenElevationType, TokenElevationTypeLimited,
+            TOKEN_ELEVATION, TOKEN_ELEVATION_TYPE, TOKEN_QUERY,
+        };
+        use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+        let mut token = HANDLE::default();
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token).is_err() {
+            return Elevation {
+                elevated: false,
+                can_elevate: false,
+            };
+        }
+
+        let mut elevation = TOKEN_ELEVATION::default();
+        let mut elevation_type = TOKEN_ELEVATION_TYPE::default();
+        let mut len = 0u32;
+        let elevated = GetTokenInformation(
+            token,
+            TokenElevation,
+            Some(&mut elevation as *mut _ as *mut std::ffi::c_void),
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut len,
+        )
+        .is_ok()
+            && elevation.TokenIsElevated != 0;
+        let limited = GetTokenInformation(
+            token,
+            TokenElevationType,
+            Some(&mut elevation_type as *mut _ as *mut std::ffi::c_void),
+            std::mem::size_of::<TOKEN_ELEVATION_TYPE>() as u32,
+            &mut len,
+        )
+        .is_ok()
+            && elevation_type == TokenElevationTypeLimited;
+        let _ = CloseHandle(token);
+
+        Elevation {
+            elevated,
+            can_elevate: elevated || limited,
+        }
+    }
+    #[cfg(not(windows))]
+    {
+        Elevation {
+            elevated: false,
+            can_elevate: false,
+        }
+    }
+}
+
+/// Runs the launcher with `command` through a UAC prompt and returns its exit code.
+fn run_launcher_elevated(launcher: &Path, command: &str) -> Result<u32, StartupError> {
+    use windows::core::{HRESULT, PCWSTR};
+    use windows::Win32::Foundation::{CloseHandle, ERROR_CANCELLED, WAIT_OBJECT_0};
+    use windows::Win32::System::Threading::{GetExitCodeProcess, WaitForSingleObject};
+    use windows::Win32::UI::Shell::{
+        ShellExecuteExW, SEE_MASK_NOASYNC, SEE_MASK_NOCLOSEPROCESS, SHELLEXECUTEINFOW,
+    };
+
+    let wide = |s: &str| s.encode_utf16().chain(Some(0)).collect::<Vec<u16>>();
+    let verb = wide("runas");
+    let file = wide(&launcher.to_string_lossy());
+    let params = wide(command);
+
+    let mut info = SHELLEXECUTEINFOW {
+        cbSize: std::mem::size_of::<SHELLEXECUTEINFOW>() as u32,
+        fMask: SEE_MASK_NOCLOSEPROCESS | SEE_MASK_NOASYNC,
+        lpVerb: PCWSTR(verb.as_ptr()),
+        lpFile: PCWSTR(file.as_ptr()),
+        lpParameters: PCWSTR(params.as_ptr()),
+        nShow: 0,
+        ..Default::default()
+    };
+
+    unsafe {
+        if let Err(e) = ShellExecuteExW(&mut info) {
+            let kind = if e.code() == HRESULT::from_win32(ERROR_CANCELLED.0) {
+                StartupErrorKind::Cancelled
+            } else {
+                StartupErrorKind::Failed
+            };
+            return Err(StartupError::new(kind, StartupMethod::Service, e.message()));
+        }
+        if info.hProcess.is_invalid() {
+            return Err(StartupError::failed(
+                StartupMethod::Service,
+                "Yönetici işlemi başlatılamadı",
+            ));
+        }
+
+        let waited = WaitForSingleObject(info.hProcess, ELEVATED_TIMEOUT_MS);
+        let mut code = 1u32;
+        let _ = GetExitCodeProcess(info.hProcess, &mut code);
+        let _ = CloseHandle(info.hProcess);
+
+        if waited != WAIT_OBJECT_0 {
+            return Err(StartupError::failed(
+                StartupMethod::Service,
+                format!("hotspot-launcher {} zaman aşımına uğradı", command),
+            ));
+        }
+        Ok(code)
+    }
+}
+
+struct ServiceBackend;
+
+impl ServiceBackend {
+    fn launcher_path() -> Result<PathBuf, StartupError> {
+        // The installer places the launcher next to the main executable,
+        // in dev both live in src-tauri/target/debug
+        let exe = app_exe().map_err(|e| StartupError::failed(StartupMethod::Service, e))?;
+        let launcher = exe
+            .parent()
+            .map(|dir| dir.join(LAUNCHER_EXE))
+            .ok_or_else(|| StartupError::failed(StartupMethod::Service, "Invalid parent"))?;
+        if !launcher.exists() {
+            return Err(StartupError::new(
+                StartupErrorKind::LauncherMissing,
+                StartupMethod::Service,
+                format!("{} bulunamadı", launcher.display()),
+            ));
+        }
+        Ok(launcher)
+    }
+
+    fn config() -> Result<(LauncherConfig, PathBuf), StartupError> {
+        let path = LauncherConfig::default_path();
+        LauncherConfig::load(&path)
+            .map(|config| (config, path))
+            .map_err(|e| StartupError::failed(StartupMethod::Service, e))
+    }
+
+    fn from_service_error(error: windows_service::Error) -> StartupError {
+        let kind = match &error {
+            windows_service::Error::Winapi(e)
+                if e.kind() == std::io::ErrorKind::PermissionDenied =>
+            {
+                StartupErrorKind::AccessDenied
+            }
+            _ => StartupErrorKind::Failed,
+        };
+        StartupError::new(kind, StartupMethod::Service, error.to_string())
+    }
+
+    fn delegate(command: &str) -> Result<(), StartupError> {
+        let launcher = Self::launcher_path()?;
+        info!(
+            "Delegating service command `{}` to elevated launcher",
+            command
+        );
+        match run_launcher_elevated(&launcher, command)? {
+            0 => Ok(()),
+            code => Err(StartupError::failed(
+                StartupMethod::Service,
+                format!("hotspot-launcher {} başarısız oldu (kod {})", command, code),
+            )),
+        }
+    }
+}
+
+impl StartupBackend for ServiceBackend {
+    fn method(&self) -> StartupMethod {
+        StartupMethod::Service
+    }
+
+    fn enable(&self, options: &StartupOptions) -> Result<(), StartupError> {
+        if !elevation().elevated {
+            // Everything after `--` replaces the app arguments in launcher.toml
+            return Self::delegate(&format!("install -- {}", options.arguments()));
+        }
+
+        let launcher = Self::launcher_path()?;
+        // Saving rewrites a rejected config and locks its folder down
+        let (mut config, path) = Self::config().unwrap_or_else(|e| {
+            warn!("{}, replacing it with the defaults", e.message);
+            (LauncherConfig::default(), LauncherConfig::default_path())
+        });
+        config.args = options.to_args();
+        config
+            .save(&path)
+            .map_err(|e| StartupError::failed(StartupMethod::Service, e))?;
+        launcher_service::install(&config, &launcher, &path).map_err(Self::from_service_error)
+    }
+
+    fn disable(&self) -> Result<(), StartupError> {
+        if !elevation().elevated {
+            return Self::delegate("uninstall");
+        }
+
+        let (config, _) = Self::config()?;
+        launcher_service::uninstall(&config.service_name)
+            .map(|_| ())
+            .map_err(Self::from_service_error)
+    }
+
+    fn is_enabled(&self) -> Result<bool, StartupError> {
+        let (config, _) = Self::config()?;
+        launcher_service::query(&config.service_name)
+            .map(|summary| summary.is_some())
+            .map_err(Self::from_service_error)
+    }
+
+    fn artifacts(&self) -> Result<Vec<StartupArtifact>, StartupError> {
+        let (config, _) = Self::config()?;
+        let summary =
+            launcher_service::query(&config.service_name).map_err(Self::from_service_error)?;
+        Ok(summary
+            .map(|summary| {
+                // The SCM image path already quotes the launcher and carries
+                // `run --config …`, like the task and Run key commands
+                StartupArtifact::new(
+                    StartupMethod::Service,
+                    &config.service_name,
+                    summary.executable_path.to_string_lossy().into_owned(),
+                    false,
+                )
+            })
+            .into_iter()
+            .collect())
+    }
+
+    /// The service command line only points at launcher.toml; the app
+    /// arguments are the ones stored there.
+    fn launches_with(&self, _artifact: &StartupArtifact, options: &StartupOptions) -> bool {
+        Self::config().is_ok_and(|(config, _)| config.args == options.to_args())
+    }
+}
+
+/// Talks to the Task Scheduler over COM, so failures come back as HRESULTs
+/// rather than localized schtasks output.
+struct TaskBackend;
+
+impl TaskBackend {
+    /// Runs `f` against the root task folder on a COM-initialized thread.
+    fn with_root<T>(
+        f: impl FnOnce(&ITaskFolder) -> windows::core::Result<T>,
+    ) -> Result<T, StartupError> {
+        unsafe {
+            // S_FALSE still needs the matching uninitialize, RPC_E_CHANGED_MODE
+            // means the thread already runs COM in another mode
+            let init = CoInitializeEx(None, COINIT_MULTITHREADED);
+            let result = (|| {
+                let service: ITaskService =
+                    CoCreateInstance(&TaskScheduler, None, CLSCTX_INPROC_SERVER)?;
+                let none = VARIANT::default();
+                service.Connect(&none, &none, &none, &none)?;
+                f(&service.GetFolder(&BSTR::from("\\"))?)
+            })();
+            if init.is_ok() {
+                CoUninitialize();
+            }
+            result.map_err(Self::error)
+        }
+    }
+
+    fn error(error: windows::core::Error) -> StartupError {
+        let kind = if error.code() == E_ACCESSDENIED {
+            StartupErrorKind::AccessDenied
+        } else {
+            StartupErrorKind::Failed
+        };
+        StartupError::new(kind, StartupMethod::Task, error.message())
+    }
+
+    fn is_not_found(error: &windows::core::Error) -> bool {
+        error.code() == HRESULT::from_win32(ERROR_FILE_NOT_FOUND.0)
+    }
+
+    /// The registered task's XML, `None` when there is no task.
+    fn xml() -> Result<Option<String>, StartupError> {
+        let name = BSTR::from(TASK_NAME);
+        Self::with_root(|root| match unsafe { root.GetTask(&name) } {
+            Ok(task) => unsafe { task.Xml() }.map(|xml| Some(xml.to_string())),
+            Err(e) if Self::is_not_found(&e) => Ok(None),
+            Err(e) => Err(e),
+        })
+    }
+
+    /// Same task `schtasks /sc ONLOGON /rl LIMITED` used to create, without
+    /// the 72 hour run limit.
+    fn definition(exe: &Path, arguments: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-16"?>
+<Task version="1.2" xmlns="http://schemas.microsoft.com/windows/2004/02/mit/task">
+  <Triggers>
+    <LogonTrigger>
+      <Enabled>true</Enabled>
+    </LogonTrigger>
+  </Triggers>
+  <Principals>
+    <Principal id="Author">
+      <LogonType>InteractiveToken</LogonType>
+      <RunLevel>LeastPrivilege</RunLevel>
+    </Principal>
+  </Principals>
+  <Settings>
+    <DisallowStartIfOnBatteries>false</DisallowStartIfOnBatteries>
+    <StopIfGoingOnBatteries>false</StopIfGoingOnBatteries>
+    <ExecutionTimeLimit>PT0S</ExecutionTimeLimit>
+  </Settings>
+  <Actions Context="Author">
+    <Exec>
+      <Command>{}</Command>
+      <Arguments>{}</Arguments>
+    </Exec>
+  </Actions>
+</Task>"#,
+            xml_escape(&exe.to_string_lossy()),
+            xml_escape(arguments)
+        )
+    }
+}
+
+impl StartupBackend for TaskBackend {
+    fn method(&self) -> StartupMethod {
+        StartupMethod::Task
+    }
+
+    fn enable(&self, options: &StartupOptions) -> Result<(), StartupError> {
+        let exe = app_exe().map_err(|e| StartupError::failed(StartupMethod::Task, e))?;
+        let definition = BSTR::from(Self::definition(&exe, &options.arguments()));
+        Self::with_root(|root| unsafe {
+            let none = VARIANT::default();
+            root.RegisterTask(
+                &BSTR::from(TASK_NAME),
+                &definition,
+                TASK_CREATE_OR_UPDATE.0,
+                &none,
+                &none,
+                TASK_LOGON_INTERACTIVE_TOKEN,
+                &none,
+            )
+            .map(|_| ())
+        })
+    }
+
+    fn disable(&self) -> Result<(), StartupError> {
+        let name = BSTR::from(TASK_NAME);
+        Self::with_root(|root| match unsafe { root.DeleteTask(&name, 0) } {
+            Err(e) if !Self::is_not_found(&e) => Err(e),
+            _ => Ok(()),
+        })
+    }
+
+    fn is_enabled(&self) -> Result<bool, StartupError> {
+        Ok(Self::xml()?.is_some())
+    }
+
+    fn artifacts(&self) -> Result<Vec<StartupArtifact>, StartupError> {
+        let Some(xml) = Self::xml()? else {
+            return Ok(Vec::new());
+        };
+
+        let tag = |name: &str| {
+            let open = format!("<{}>", name);
+            let close = format!("</{}>", name);
+            let start = xml.find(&open)? + open.len();
+            let end = xml[start..].find(&close)? + start;
+            Some(xml[start..end].trim().replace("&quot;", "\""))
+        };
+        let command = match (tag("Command"), tag("Arguments")) {
+            (Some(command), Some(args)) => format!("\"{}\" {}", command.trim_matches('"'), args),
+            (Some(command), None) => format!("\"{}\"", command.trim_matches('"')),
+            (None, _) => String::new(),
+        };
+        // Tasks created with /rl HIGHEST come from the old silent migration
+        let legacy = xml.contains("<RunLevel>HighestAvailable</RunLevel>");
+
+        Ok(vec![StartupArtifact::new(
+            StartupMethod::Task,
+            TASK_NAME,
+            command,
+            legacy,
+        )])
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+struct RunKeyBackend;
 
-    let _ = Command::new("powershell")
-        .args(&["-Command", &ps_command])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output();
+impl StartupBackend for RunKeyBackend {
+    fn method(&self) -> StartupMethod {
+        StartupMethod::RunKey
+    }
 
-    // Cleanup
-    let _ = std::fs::remove_file(batch_path);
+    fn enable(&self, options: &StartupOptions) -> Result<(), StartupError> {
+        let exe = app_exe().map_err(|e| StartupError::failed(StartupMethod::RunKey, e))?;
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        let (key, _) = hkcu
+            .create_subkey(RUN_KEY_PATH)
+            .map_err(|e| StartupError::from_io(StartupMethod::RunKey, e))?;
+        key.set_value(RUN_VALUE_NAME, &options.command_line(&exe))
+            .map_err(|e| StartupError::from_io(StartupMethod::RunKey, e))
+    }
 
-    // Clean up legacy methods
-    let _ = disable_registry_startup();
-    let _ = Command::new("powershell")
-        .args(&["-Command", "Start-Process schtasks -Verb RunAs -WindowStyle Hidden -Wait -ArgumentList '/delete /tn HotspotManager /f'"])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output();
+    fn disable(&self) -> Result<(), StartupError> {
+        delete_run_value(RUN_VALUE_NAME)
+    }
 
-    Ok(())
+    fn is_enabled(&self) -> Result<bool, StartupError> {
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        match hkcu.open_subkey_with_flags(RUN_KEY_PATH, KEY_READ) {
+            Ok(key) => Ok(key.get_value::<String, _>(RUN_VALUE_NAME).is_ok()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(StartupError::from_io(StartupMethod::RunKey, e)),
+        }
+    }
+
+    fn artifacts(&self) -> Result<Vec<StartupArtifact>, StartupError> {
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        let key = match hkcu.open_subkey_with_flags(RUN_KEY_PATH, KEY_READ) {
+            Ok(key) => key,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(StartupError::from_io(StartupMethod::RunKey, e)),
+        };
+
+        Ok(std::iter::once(RUN_VALUE_NAME)
+            .chain(LEGACY_RUN_VALUES.iter().copied())
+            .filter_map(|name| {
+                let command = key.get_value::<String, _>(name).ok()?;
+                Some(StartupArtifact::new(
+                    StartupMethod::RunKey,
+                    name,
+                    command,
+                    name != RUN_VALUE_NAME,
+                ))
+            })
+            .collect())
+    }
+
+    fn remove(&self, artifact: &StartupArtifact) -> Result<(), StartupError> {
+        delete_run_value(&artifact.name)
+    }
 }
 
-fn disable_registry_startup() -> Result<(), String> {
+fn delete_run_value(name: &str) -> Result<(), StartupError> {
     let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    let path = r"Software\Microsoft\Windows\CurrentVersion\Run";
-    if let Ok(key) = hkcu.open_subkey_with_flags(path, KEY_WRITE) {
-        let _ = key.delete_value("HotspotManager");
+    let key = match hkcu.open_subkey_with_flags(RUN_KEY_PATH, KEY_WRITE) {
+        Ok(key) => key,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(StartupError::from_io(StartupMethod::RunKey, e)),
+    };
+    match key.delete_value(name) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(StartupError::from_io(StartupMethod::RunKey, e))
+        }
+        _ => Ok(()),
     }
-    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupStatus {
+    pub enabled: bool,
+    pub method: Option<StartupMethod>,
+    pub elevation: Elevation,
+}
+
+/// Picks the backend that will actually work for this user: the service needs
+/// an administrator, so standard accounts go straight to the Run key.
+fn effective_method(host: &dyn StartupHost, requested: StartupMethod) -> StartupMethod {
+    if requested == StartupMethod::Service && !host.elevation().can_elevate {
+        info!("Cannot elevate, using the Run key instead of the service");
+        return StartupMethod::RunKey;
+    }
+    requested
+}
+
+fn enable_with_fallback(
+    host: &dyn StartupHost,
+    requested: StartupMethod,
+    options: &StartupOptions,
+) -> Result<StartupMethod, StartupError> {
+    let method = effective_method(host, requested);
+    match host.backend(method).enable(options) {
+        Ok(()) => Ok(method),
+        Err(e) if e.kind == StartupErrorKind::AccessDenied && method != StartupMethod::RunKey => {
+            warn!(
+                "{:?} startup denied ({}), falling back to the Run key",
+                method, e
+            );
+            host.backend(StartupMethod::RunKey).enable(options)?;
+            Ok(StartupMethod::RunKey)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Removes every other registration so the app is never started twice.
+fn disable_others(host: &dyn StartupHost, active: StartupMethod) {
+    for other in installed_backends(host) {
+        if other.method() == active || !matches!(other.is_enabled(), Ok(true)) {
+            continue;
+        }
+        if let Err(e) = other.disable() {
+            warn!("Could not remove {:?} startup entry: {}", other.method(), e);
+        }
+    }
+}
+
+fn active_method(host: &dyn StartupHost) -> Option<StartupMethod> {
+    installed_backends(host)
+        .iter()
+        .find(|backend| match backend.is_enabled() {
+            Ok(enabled) => enabled,
+            Err(e) => {
+                warn!("Could not query {:?} startup: {}", backend.method(), e);
+                false
+            }
+        })
+        .map(|backend| backend.method())
+}
+
+#[tauri::command]
+pub async fn enable_startup(
+    state: tauri::State<'_, AppState>,
+) -> Result<StartupMethod, StartupError> {
+    // The saved settings are the only source of the registered options
+    let (requested, options) = startup_config(&state);
+    let host = state.startup.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let method = enable_with_fallback(host.as_ref(), requested, &options)?;
+        info!(
+            "Startup enabled via {:?} with {:?}",
+            method,
+            options.to_args()
+        );
+        disable_others(host.as_ref(), method);
+        Ok(method)
+    })
+    .await
+    .map_err(|e| StartupError::failed(requested, e.to_string()))?
 }
 
 #[tauri::command]
-pub async fn disable_startup() -> Result<(), String> {
-    // 1. Stop and Delete Service
-    let _ = Command::new("powershell")
-        .args(&["-Command", "Start-Process sc.exe -Verb RunAs -WindowStyle Hidden -Wait -ArgumentList 'stop HotspotLauncher'"])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output();
+pub async fn disable_startup(state: tauri::State<'_, AppState>) -> Result<(), StartupError> {
+    let host = state.startup.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut first_error = None;
+        for backend in installed_backends(host.as_ref()) {
+            let result = match backend.is_enabled() {
+                Ok(false) => continue,
+                Ok(true) => backend.disable(),
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(()) => info!("Startup disabled for {:?}", backend.method()),
+                Err(e) => {
+                    warn!("Could not disable {:?} startup: {}", backend.method(), e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    })
+    .await
+    .map_err(|e| StartupError::failed(StartupMethod::default(), e.to_string()))?
+}
 
-    let _ = Command::new("powershell")
-        .args(&["-Command", "Start-Process sc.exe -Verb RunAs -WindowStyle Hidden -Wait -ArgumentList 'delete HotspotLauncher'"])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output();
+#[tauri::command]
+pub async fn is_startup_enabled(
+    state: tauri::State<'_, AppState>,
+) -> Result<StartupStatus, String> {
+    let host = state.startup.clone();
+    let fallback = host.clone();
+    Ok(tauri::async_runtime::spawn_blocking(move || {
+        let method = active_method(host.as_ref());
+        StartupStatus {
+            enabled: method.is_some(),
+            method,
+            elevation: host.elevation(),
+        }
+    })
+    .await
+    .unwrap_or_else(|_| StartupStatus {
+        enabled: false,
+        method: None,
+        elevation: fallback.elevation(),
+    }))
+}
 
-    // 2. Delete Scheduled Task (legacy cleanup)
-    let _ = Command::new("powershell")
-        .args(&["-Command", "Start-Process schtasks -Verb RunAs -WindowStyle Hidden -Wait -ArgumentList '/delete /tn HotspotManager /f'"])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output();
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupDiagnosis {
+    pub configured: StartupMethod,
+    pub effective: StartupMethod,
+    pub artifacts: Vec<StartupArtifact>,
+    pub errors: Vec<StartupError>,
+    pub consistent: bool,
+}
 
-    // 3. Delete Registry Key (legacy cleanup)
-    let _ = disable_registry_startup();
+pub fn diagnose(
+    host: &dyn StartupHost,
+    configured: StartupMethod,
+    options: &StartupOptions,
+) -> StartupDiagnosis {
+    let effective = effective_method(host, configured);
+    let mut artifacts = Vec::new();
+    let mut errors = Vec::new();
 
-    Ok(())
+    for backend in installed_backends(host) {
+        match backend.artifacts() {
+            Ok(found) => artifacts.extend(found.into_iter().map(|mut artifact| {
+                artifact.outdated = !backend.launches_with(&artifact, options);
+                artifact
+            })),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    // Either nothing is registered, or exactly one healthy entry for the
+    // configured method with the saved options
+    let consistent = errors.is_empty()
+        && match artifacts.as_slice() {
+            [] => true,
+            [only] => {
+                only.method == effective && !only.legacy && only.target_exists && !only.outdated
+            }
+            _ => false,
+        };
+
+    StartupDiagnosis {
+        configured,
+        effective,
+        artifacts,
+        errors,
+        consistent,
+    }
+}
+
+fn repair(
+    host: &dyn StartupHost,
+    configured: StartupMethod,
+    options: &StartupOptions,
+) -> Result<StartupDiagnosis, StartupError> {
+    let before = diagnose(host, configured, options);
+    if before.consistent {
+        return Ok(before);
+    }
+    if let Some(e) = before.errors.first() {
+        return Err(e.clone());
+    }
+
+    let effective = before.effective;
+    let healthy = |a: &StartupArtifact| a.method == effective && !a.legacy && a.target_exists;
+    // An outdated entry of the right kind is rewritten in place below
+    let keep_existing = before.artifacts.iter().any(|a| healthy(a) && !a.outdated);
+
+    for artifact in before.artifacts.iter().filter(|a| !healthy(a)) {
+        info!(
+            "Removing startup artifact {:?} {} ({})",
+            artifact.method, artifact.name, artifact.command
+        );
+        host.backend(artifact.method).remove(artifact)?;
+    }
+
+    // The user had some form of autostart, so make sure the configured one exists
+    if !keep_existing && !before.artifacts.is_empty() {
+        let method = enable_with_fallback(host, configured, options)?;
+        info!("Startup re-registered via {:?}", method);
+    }
+
+    Ok(diagnose(host, configured, options))
+}
+
+fn startup_config(state: &tauri::State<'_, AppState>) -> (StartupMethod, StartupOptions) {
+    state
+        .settings
+        .lock()
+        .map(|s| (s.startup_method, StartupOptions::from_settings(&s)))
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn diagnose_startup(
+    state: tauri::State<'_, AppState>,
+) -> Result<StartupDiagnosis, String> {
+    let (configured, options) = startup_config(&state);
+    let host = state.startup.clone();
+    tauri::async_runtime::spawn_blocking(move || diagnose(host.as_ref(), configured, &options))
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn is_startup_enabled() -> bool {
-    // Check for Windows Service first
-    let service_exists = Command::new("sc")
-        .args(&["query", "HotspotLauncher"])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false);
+pub async fn repair_startup(
+    state: tauri::State<'_, AppState>,
+) -> Result<StartupDiagnosis, StartupError> {
+    let (configured, options) = startup_config(&state);
+    let host = state.startup.clone();
+    tauri::async_runtime::spawn_blocking(move || repair(host.as_ref(), configured, &options))
+        .await
+        .map_err(|e| StartupError::failed(configured, e.to_string()))?
+}
 
-    if service_exists {
-        return true;
+/// Logs leftovers at launch instead of rewriting them behind the user's back;
+/// `repair_startup` is the explicit fix.
+pub fn log_startup_diagnosis(
+    host: &dyn StartupHost,
+    configured: StartupMethod,
+    options: &StartupOptions,
+) {
+    let diagnosis = diagnose(host, configured, options);
+    if diagnosis.consistent {
+        return;
+    }
+    warn!(
+        "Startup configuration is inconsistent (configured {:?}):",
+        diagnosis.configured
+    );
+    for artifact in &diagnosis.artifacts {
+        warn!(
+            "  {:?} {} -> {} (exists: {}, legacy: {}, outdated: {})",
+            artifact.method,
+            artifact.name,
+            artifact.command,
+            artifact.target_exists,
+            artifact.legacy,
+            artifact.outdated
+        );
+    }
+    for error in &diagnosis.errors {
+        warn!("  query failed: {}", error);
     }
+}
 
-    // Fallback: Check Task Scheduler
-    let task_exists = Command::new("schtasks")
-        .args(&["/query", "/tn", "HotspotManager"])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false);
+#[tauri::command]
+pub fn get_launch_options(state: tauri::State<'_, AppState>) -> StartupOptions {
+    state
+        .launch_options
+        .lock()
+        .map(|o| o.clone())
+        .unwrap_or_default()
+}
 
-    if task_exists {
-        return true;
+#[tauri::command]
+pub fn is_startup_ready(state: tauri::State<'_, AppState>) -> bool {
+    state.startup_ready.lock().map(|r| *r).unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Registrations shared by every backend the fake host hands out.
+    #[derive(Default)]
+    struct Machine {
+        entries: Mutex<Vec<StartupArtifact>>,
+        denied: Vec<StartupMethod>,
+        can_elevate: bool,
     }
 
-    // Fallback: Check Registry
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    let path = r"Software\Microsoft\Windows\CurrentVersion\Run";
-    if let Ok(key) = hkcu.open_subkey_with_flags(path, KEY_READ) {
-        return key.get_value::<String, _>("HotspotManager").is_ok();
-    }
-
-    false
-}
-
-pub fn check_and_migrate_legacy_autostart() {
-    // Check for standard Tauri autostart registry keys
-    // Try both product name and bundle identifier just in case
-    let keys_to_check = ["Hotspot Manager", "com.hotspot.app", "hotspot"];
-
-    for key in keys_to_check {
-        let check = Command::new("reg")
-            .args(&[
-                "query",
-                "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Run",
-                "/v",
-                key,
-            ])
-            .creation_flags(CREATE_NO_WINDOW)
-            .output();
-
-        if let Ok(output) = check {
-            if output.status.success() {
-                println!("Found legacy autostart key: {}. Migrating...", key);
-
-                // 1. Create new Scheduled Task (preserve the user's "enabled" preference)
-                // We run this synchronously here since we are in setup
-                if let Ok(exe_path) = current_exe() {
-                    if let Some(exe_str) = exe_path.to_str() {
-                        let _ = Command::new("schtasks")
-                            .args(&[
-                                "/create",
-                                "/tn",
-                                "HotspotManager",
-                                "/tr",
-                                &format!("\"{}\"", exe_str),
-                                "/sc",
-                                "ONLOGON",
-                                "/rl",
-                                "HIGHEST",
-                                "/f",
-                            ])
-                            .creation_flags(CREATE_NO_WINDOW)
-                            .output();
-                    }
-                }
+    impl Machine {
+        fn methods(&self) -> Vec<StartupMethod> {
+            self.entries
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|a| a.method)
+                .collect()
+        }
+
+        fn add(&self, method: StartupMethod, name: &str, target_exists: bool, legacy: bool) {
+            self.register(
+                method,
+                name,
+                &StartupOptions::default(),
+                target_exists,
+                legacy,
+            );
+        }
+
+        fn register(
+            &self,
+            method: StartupMethod,
+            name: &str,
+            options: &StartupOptions,
+            target_exists: bool,
+            legacy: bool,
+        ) {
+            let target = PathBuf::from(format!("C:\\app\\{}.exe", name));
+            self.entries.lock().unwrap().push(StartupArtifact {
+                method,
+                name: name.to_string(),
+                command: options.command_line(&target),
+                target: Some(target),
+                target_exists,
+                legacy,
+                outdated: false,
+            });
+        }
 
-                // 2. Delete the old registry key
-                let _ = Command::new("reg")
-                    .args(&[
-                        "delete",
-                        "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Run",
-                        "/v",
-                        key,
-                        "/f",
-                    ])
-                    .creation_flags(CREATE_NO_WINDOW)
-                    .output();
+        fn commands(&self) -> Vec<String> {
+            self.entries
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|a| a.command.clone())
+                .collect()
+        }
+    }
+
+    struct FakeHost(Arc<Machine>);
+
+    impl StartupHost for FakeHost {
+        fn backend(&self, method: StartupMethod) -> Box<dyn StartupBackend> {
+            Box::new(FakeBackend {
+                method,
+                machine: self.0.clone(),
+            })
+        }
+
+        fn elevation(&self) -> Elevation {
+            Elevation {
+                elevated: false,
+                can_elevate: self.0.can_elevate,
             }
         }
     }
+
+    struct FakeBackend {
+        method: StartupMethod,
+        machine: Arc<Machine>,
+    }
+
+    impl StartupBackend for FakeBackend {
+        fn method(&self) -> StartupMethod {
+            self.method
+        }
+
+        fn enable(&self, options: &StartupOptions) -> Result<(), StartupError> {
+            if self.machine.denied.contains(&self.method) {
+                return Err(StartupError::new(
+                    StartupErrorKind::AccessDenied,
+                    self.method,
+                    "denied",
+                ));
+            }
+            self.disable()?;
+            self.machine
+                .register(self.method, "app", options, true, false);
+            Ok(())
+        }
+
+        fn disable(&self) -> Result<(), StartupError> {
+            self.machine
+                .entries
+                .lock()
+                .unwrap()
+                .retain(|a| a.method != self.method);
+            Ok(())
+        }
+
+        fn is_enabled(&self) -> Result<bool, StartupError> {
+            Ok(self.machine.methods().contains(&self.method))
+        }
+
+        fn artifacts(&self) -> Result<Vec<StartupArtifact>, StartupError> {
+            Ok(self
+                .machine
+                .entries
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|a| a.method == self.method)
+                .cloned()
+                .collect())
+        }
+
+        fn remove(&self, artifact: &StartupArtifact) -> Result<(), StartupError> {
+            self.machine
+                .entries
+                .lock()
+                .unwrap()
+                .retain(|a| !(a.method == artifact.method && a.name == artifact.name));
+            Ok(())
+        }
+    }
+
+    fn fake(machine: Machine) -> (FakeHost, Arc<Machine>) {
+        let machine = Arc::new(machine);
+        (FakeHost(machine.clone()), machine)
+    }
+
+    #[test]
+    fn launch_options_round_trip_through_arguments() {
+        let options = StartupOptions {
+            minimized: true,
+            delay_secs: 15,
+            connect: true,
+            profile: Some("Yurt A".to_string()),
+        };
+        let mut args = vec!["hotspot.exe".to_string()];
+        args.extend(options.to_args());
+        assert_eq!(StartupOptions::parse(&args), options);
+    }
+
+    #[test]
+    fn command_target_splits_quoted_path_with_arguments() {
+        let image = r#""C:\Program Files\Hotspot\hotspot-launcher.exe" run --config "C:\ProgramData\Hotspot\launcher.toml""#;
+        assert_eq!(
+            command_target(image),
+            Some(PathBuf::from(
+                r"C:\Program Files\Hotspot\hotspot-launcher.exe"
+            ))
+        );
+    }
+
+    #[test]
+    fn command_target_handles_unquoted_paths() {
+        assert_eq!(
+            command_target(r"C:\Program Files\Hotspot\hotspot.exe --minimized"),
+            Some(PathBuf::from(r"C:\Program Files\Hotspot\hotspot.exe"))
+        );
+        assert_eq!(
+            command_target("hotspot --minimized"),
+            Some(PathBuf::from("hotspot"))
+        );
+        assert_eq!(command_target("  "), None);
+    }
+
+    #[test]
+    fn task_definition_escapes_command_and_arguments() {
+        let options = StartupOptions {
+            minimized: true,
+            profile: Some("Yurt <A&B>".to_string()),
+            ..Default::default()
+        };
+        let xml = TaskBackend::definition(Path::new(r"C:\R&D\hotspot.exe"), &options.arguments());
+        assert!(xml.contains(r"<Command>C:\R&amp;D\hotspot.exe</Command>"));
+        assert!(xml.contains(
+            "<Arguments>--minimized --profile &quot;Yurt &lt;A&amp;B&gt;&quot;</Arguments>"
+        ));
+    }
+
+    #[test]
+    fn command_line_quotes_exe_and_spaced_arguments() {
+        let options = StartupOptions {
+            delay_secs: 30,
+            profile: Some("Yurt A".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.command_line(Path::new(r"C:\Apps\hotspot.exe")),
+            r#""C:\Apps\hotspot.exe" --startup-delay 30 --profile "Yurt A""#
+        );
+        assert_eq!(
+            StartupOptions::default().command_line(Path::new(r"C:\hotspot.exe")),
+            r#""C:\hotspot.exe""#
+        );
+    }
+
+    #[test]
+    fn enable_uses_requested_method() {
+        let (host, machine) = fake(Machine {
+            can_elevate: true,
+            ..Default::default()
+        });
+        let method =
+            enable_with_fallback(&host, StartupMethod::Service, &StartupOptions::default())
+                .unwrap();
+        assert_eq!(method, StartupMethod::Service);
+        assert_eq!(machine.methods(), vec![StartupMethod::Service]);
+    }
+
+    #[test]
+    fn service_without_admin_goes_to_run_key() {
+        let (host, machine) = fake(Machine::default());
+        let method =
+            enable_with_fallback(&host, StartupMethod::Service, &StartupOptions::default())
+                .unwrap();
+        assert_eq!(method, StartupMethod::RunKey);
+        assert_eq!(machine.methods(), vec![StartupMethod::RunKey]);
+    }
+
+    #[test]
+    fn denied_task_falls_back_to_run_key() {
+        let (host, machine) = fake(Machine {
+            denied: vec![StartupMethod::Task],
+            ..Default::default()
+        });
+        let method =
+            enable_with_fallback(&host, StartupMethod::Task, &StartupOptions::default()).unwrap();
+        assert_eq!(method, StartupMethod::RunKey);
+        assert_eq!(machine.methods(), vec![StartupMethod::RunKey]);
+    }
+
+    #[test]
+    fn denied_run_key_is_an_error() {
+        let (host, machine) = fake(Machine {
+            denied: vec![StartupMethod::RunKey],
+            ..Default::default()
+        });
+        let error = enable_with_fallback(&host, StartupMethod::RunKey, &StartupOptions::default())
+            .unwrap_err();
+        assert_eq!(error.kind, StartupErrorKind::AccessDenied);
+        assert!(machine.methods().is_empty());
+    }
+
+    #[test]
+    fn disable_others_keeps_only_the_active_method() {
+        let (host, machine) = fake(Machine::default());
+        machine.add(StartupMethod::Task, "app", true, false);
+        machine.add(StartupMethod::RunKey, "app", true, false);
+        disable_others(&host, StartupMethod::RunKey);
+        assert_eq!(machine.methods(), vec![StartupMethod::RunKey]);
+    }
+
+    #[test]
+    fn diagnose_accepts_nothing_or_one_healthy_entry() {
+        let (host, machine) = fake(Machine::default());
+        assert!(diagnose(&host, StartupMethod::Task, &StartupOptions::default()).consistent);
+
+        machine.add(StartupMethod::Task, "app", true, false);
+        assert!(diagnose(&host, StartupMethod::Task, &StartupOptions::default()).consistent);
+        assert!(!diagnose(&host, StartupMethod::RunKey, &StartupOptions::default()).consistent);
+    }
+
+    #[test]
+    fn diagnose_flags_legacy_missing_and_duplicate_entries() {
+        let (host, machine) = fake(Machine::default());
+        machine.add(StartupMethod::RunKey, "Hotspot Manager", true, true);
+        assert!(!diagnose(&host, StartupMethod::RunKey, &StartupOptions::default()).consistent);
+
+        let (host, machine) = fake(Machine::default());
+        machine.add(StartupMethod::RunKey, "app", false, false);
+        assert!(!diagnose(&host, StartupMethod::RunKey, &StartupOptions::default()).consistent);
+
+        let (host, machine) = fake(Machine::default());
+        machine.add(StartupMethod::Task, "app", true, false);
+        machine.add(StartupMethod::RunKey, "app", true, false);
+        assert!(!diagnose(&host, StartupMethod::Task, &StartupOptions::default()).consistent);
+    }
+
+    #[test]
+    fn diagnose_uses_the_effective_method() {
+        let (host, machine) = fake(Machine::default());
+        machine.add(StartupMethod::RunKey, "app", true, false);
+        let diagnosis = diagnose(&host, StartupMethod::Service, &StartupOptions::default());
+        assert_eq!(diagnosis.effective, StartupMethod::RunKey);
+        assert!(diagnosis.consistent);
+    }
+
+    #[test]
+    fn repair_replaces_legacy_entry_with_configured_method() {
+        let (host, machine) = fake(Machine::default());
+        machine.add(StartupMethod::RunKey, "Hotspot Manager", true, true);
+        let diagnosis = repair(&host, StartupMethod::Task, &StartupOptions::default()).unwrap();
+        assert!(diagnosis.consistent);
+        assert_eq!(machine.methods(), vec![StartupMethod::Task]);
+    }
+
+    #[test]
+    fn repair_keeps_healthy_entry_and_drops_the_rest() {
+        let (host, machine) = fake(Machine::default());
+        machine.add(StartupMethod::Task, "app", true, false);
+        machine.add(StartupMethod::RunKey, "Hotspot Manager", true, true);
+        let diagnosis = repair(&host, StartupMethod::Task, &StartupOptions::default()).unwrap();
+        assert!(diagnosis.consistent);
+        assert_eq!(machine.methods(), vec![StartupMethod::Task]);
+    }
+
+    #[test]
+    fn repair_rewrites_entry_with_outdated_options() {
+        let (host, machine) = fake(Machine::default());
+        machine.add(StartupMethod::Task, "app", true, false);
+        let options = StartupOptions {
+            minimized: true,
+            profile: Some("Yurt A".to_string()),
+            ..Default::default()
+        };
+
+        let before = diagnose(&host, StartupMethod::Task, &options);
+        assert!(!before.consistent);
+        assert!(before.artifacts[0].outdated);
+
+        let diagnosis = repair(&host, StartupMethod::Task, &options).unwrap();
+        assert!(diagnosis.consistent);
+        assert_eq!(machine.methods(), vec![StartupMethod::Task]);
+        assert_eq!(
+            machine.commands(),
+            vec![r#""C:\app\app.exe" --minimized --profile "Yurt A""#.to_string()]
+        );
+    }
+
+    #[test]
+    fn repair_does_not_enable_startup_from_scratch() {
+        let (host, machine) = fake(Machine::default());
+        let diagnosis = repair(&host, StartupMethod::Task, &StartupOptions::default()).unwrap();
+        assert!(diagnosis.consistent);
+        assert!(machine.methods().is_empty());
+    }
 }
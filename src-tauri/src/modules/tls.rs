@@ -0,0 +1,282 @@
+use log::warn;
+use ring::digest::{digest, SHA256};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, Error, RootCertStore, SignatureScheme};
+use std::sync::Arc;
+
+use super::portal;
+use super::profiles::{NetworkProfile, PortalTls};
+
+/// The active profile's TLS exceptions and the portal host they belong to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scope {
+    pub portal_host: String,
+    pub tls: PortalTls,
+}
+
+pub fn active_scope(profile: Option<&NetworkProfile>) -> Option<Scope> {
+    let profile = profile?;
+    if profile.tls.is_empty() {
+        return None;
+    }
+    let provider = portal::provider(&profile.provider)?;
+    let portal_host = reqwest::Url::parse(provider.status_url())
+        .ok()?
+        .host_str()?
+        .to_string();
+    Some(Scope {
+        portal_host,
+        tls: profile.tls.clone(),
+    })
+}
+
+/// rustls config that applies the exceptions and verifies every other host
+/// against the system store as usual.
+pub fn client_config(scope: &Scope) -> Result<rustls::ClientConfig, String> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier = PortalVerifier::new(scope, provider.clone())?;
+    Ok(rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth())
+}
+
+/// Checks what `client_config` would reject, so a profile with a bad pin or
+/// CA file isn't saved.
+pub fn validate(tls: &PortalTls) -> Result<(), String> {
+    for pin in &tls.pinned_sha256 {
+        parse_pin(pin)?;
+    }
+    match tls.ca_bundle.trim() {
+        "" => Ok(()),
+        path => load_bundle(path).map(|_| ()),
+    }
+}
+
+fn load_bundle(path: &str) -> Result<Vec<CertificateDer<'static>>, String> {
+    let pem = std::fs::read(path).map_err(|e| format!("CA dosyası okunamadı ({}): {}", path, e))?;
+    let certs: Vec<_> = CertificateDer::pem_slice_iter(&pem)
+        .filter_map(Result::ok)
+        .collect();
+    if certs.is_empty() {
+        return Err(format!("CA dosyasında sertifika yok: {}", path));
+    }
+    Ok(certs)
+}
+
+fn parse_pin(pin: &str) -> Result<Vec<u8>, String> {
+    let hex: String = pin
+        .chars()
+        .filter(|c| !matches!(c, ':' | ' ' | '-'))
+        .collect();
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+        })
+        .collect::<Option<Vec<u8>>>();
+    match bytes {
+        Some(bytes) if bytes.len() == 32 => Ok(bytes),
+        _ => Err(format!("Geçersiz SHA-256 parmak izi: {}", pin)),
+    }
+}
+
+fn system_roots() -> RootCertStore {
+    let native = rustls_native_certs::load_native_certs();
+    for e in &native.errors {
+        warn!("Could not load a system certificate: {}", e);
+    }
+    let mut roots = RootCertStore::empty();
+    roots.add_parsable_certificates(native.certs);
+    roots
+}
+
+#[derive(Debug)]
+struct PortalVerifier {
+    portal_host: String,
+    pins: Vec<Vec<u8>>,
+    /// System roots plus the bundle, used for the portal host only.
+    with_bundle: Option<Arc<WebPkiServerVerifier>>,
+    insecure_hosts: Vec<String>,
+    system: Arc<WebPkiServerVerifier>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl PortalVerifier {
+    fn new(scope: &Scope, provider: Arc<CryptoProvider>) -> Result<Self, String> {
+        let build = |roots: RootCertStore| {
+            WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+                .build()
+                .map_err(|e| e.to_string())
+        };
+        let roots = system_roots();
+
+        let with_bundle = match scope.tls.ca_bundle.trim() {
+            "" => None,
+            path => {
+                let mut roots = roots.clone();
+                let (added, _) = roots.add_parsable_certificates(load_bundle(path)?);
+                if added == 0 {
+                    return Err(format!("CA dosyasında geçerli sertifika yok: {}", path));
+                }
+                Some(build(roots)?)
+            }
+        };
+
+        Ok(Self {
+            portal_host: scope.portal_host.to_lowercase(),
+            pins: scope
+                .tls
+                .pinned_sha256
+                .iter()
+                .map(|pin| parse_pin(pin))
+                .collect::<Result<_, _>>()?,
+            with_bundle,
+            insecure_hosts: scope
+                .tls
+                .insecure_hosts
+                .iter()
+                .map(|h| h.trim().to_lowercase())
+                .collect(),
+            system: build(roots)?,
+            provider,
+        })
+    }
+}
+
+impl ServerCertVerifier for PortalVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, Error> {
+        let host = server_name.to_str().to_lowercase();
+
+        if self.insecure_hosts.contains(&host) {
+            warn!(
+                "Not verifying the certificate of {}, listed as insecure",
+                host
+            );
+            return Ok(ServerCertVerified::assertion());
+        }
+
+        if host == self.portal_host {
+            if !self.pins.is_empty() {
+                let fingerprint = digest(&SHA256, end_entity.as_ref());
+                if self.pins.iter().any(|pin| pin == fingerprint.as_ref()) {
+                    return Ok(ServerCertVerified::assertion());
+                }
+                // A pin is a promise about this exact certificate, so a
+                // mismatch isn't rescued by any CA
+                return Err(Error::General(
+                    "sertifika parmak izi sabitlenen değerle eşleşmiyor".to_string(),
+                ));
+            }
+            if let Some(verifier) = &self.with_bundle {
+                return verifier.verify_server_cert(
+                    end_entity,
+                    intermediates,
+                    server_name,
+                    ocsp_response,
+                    now,
+                );
+            }
+        }
+
+        self.system
+            .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PIN: &str = "AB:CD:EF:01:23:45:67:89:AB:CD:EF:01:23:45:67:89:AB:CD:EF:01:23:45:67:89:AB:CD:EF:01:23:45:67:89";
+
+    #[test]
+    fn pins() {
+        assert_eq!(parse_pin(PIN).unwrap().len(), 32);
+        assert_eq!(
+            parse_pin(&PIN.replace(':', "").to_lowercase()),
+            parse_pin(PIN)
+        );
+        assert!(parse_pin("AB:CD").is_err());
+        assert!(parse_pin(&PIN.replace("AB", "ZZ")).is_err());
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        let valid = PortalTls {
+            pinned_sha256: vec![PIN.to_string()],
+            ..Default::default()
+        };
+        assert_eq!(validate(&valid), Ok(()));
+
+        let bad_pin = PortalTls {
+            pinned_sha256: vec!["1234".to_string()],
+            ..Default::default()
+        };
+        assert!(validate(&bad_pin).unwrap_err().contains("parmak izi"));
+
+        let missing = PortalTls {
+            ca_bundle: "/nonexistent/portal-ca.pem".to_string(),
+            ..Default::default()
+        };
+        assert!(validate(&missing).unwrap_err().contains("okunamadı"));
+
+        let path = std::env::temp_dir().join(format!("portal-ca-{}.pem", std::process::id()));
+        std::fs::write(&path, "bu bir sertifika değil").unwrap();
+        let empty = PortalTls {
+            ca_bundle: path.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        let result = validate(&empty);
+        let _ = std::fs::remove_file(&path);
+        assert!(result.unwrap_err().contains("sertifika yok"));
+    }
+}
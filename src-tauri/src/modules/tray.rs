@@ -1,6 +1,27 @@
 use image::imageops::FilterType;
+use std::sync::Mutex;
 use tauri::image::Image;
 
+use super::session;
+
+/// Status part of the tooltip, kept so the session countdown can be
+/// refreshed without touching the icon.
+static STATUS_TOOLTIP: Mutex<&str> = Mutex::new("Hotspot Manager - Başlatılıyor...");
+
+fn tooltip_text(app: &tauri::AppHandle, status: &str) -> String {
+    match session::tooltip_suffix(app) {
+        Some(suffix) => format!("{} ({})", status, suffix),
+        None => status.to_string(),
+    }
+}
+
+pub fn refresh_tooltip(app: &tauri::AppHandle) {
+    let status = STATUS_TOOLTIP.lock().map(|s| *s).unwrap_or_default();
+    if let Some(tray) = app.tray_by_id("main-tray") {
+        let _ = tray.set_tooltip(Some(tooltip_text(app, status)));
+    }
+}
+
 #[tauri::command]
 pub fn update_tray_icon(app: tauri::AppHandle, status: String) {
     if let Some(tray) = app.tray_by_id("main-tray") {
@@ -19,7 +40,10 @@ pub fn update_tray_icon(app: tauri::AppHandle, status: String) {
             ),
         };
 
-        let _ = tray.set_tooltip(Some(tooltip));
+        if let Ok(mut current) = STATUS_TOOLTIP.lock() {
+            *current = tooltip;
+        }
+        let _ = tray.set_tooltip(Some(tooltip_text(&app, tooltip)));
 
         match image::load_from_memory(&icon_bytes) {
             Ok(img) => {
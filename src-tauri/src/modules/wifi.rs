@@ -0,0 +1,551 @@
+use log::{info, warn};
+use serde::Serialize;
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use super::{netwatch, profiles};
+use crate::AppState;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScannedNetwork {
+    pub ssid: String,
+    /// Percent, 0-100.
+    pub signal: u8,
+    pub security: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WifiNetwork {
+    pub ssid: String,
+    pub signal: u8,
+    pub security: String,
+    /// The OS has a saved Wi-Fi profile, so `connect_wifi` can join it.
+    pub known: bool,
+    pub connected: bool,
+    /// Name of the matching app network profile.
+    pub profile: Option<String>,
+}
+
+pub trait WifiBackend: Send + Sync {
+    fn scan(&self) -> Result<Vec<ScannedNetwork>, String>;
+    fn known_networks(&self) -> Result<Vec<String>, String>;
+    fn connect(&self, ssid: &str) -> Result<(), String>;
+    fn current_ssid(&self) -> Option<String>;
+}
+
+/// Merges scan results with saved OS and app profiles. Access points sharing
+/// an SSID collapse into the strongest one.
+pub fn list_networks(
+    backend: &dyn WifiBackend,
+    app_profiles: &[profiles::NetworkProfile],
+) -> Result<Vec<WifiNetwork>, String> {
+    let known = backend.known_networks().unwrap_or_else(|e| {
+        warn!("Could not read saved Wi-Fi profiles: {}", e);
+        Vec::new()
+    });
+    let current = backend.current_ssid();
+
+    let mut networks: Vec<WifiNetwork> = Vec::new();
+    for scanned in backend.scan()? {
+        if scanned.ssid.is_empty() {
+            continue;
+        }
+        if let Some(existing) = networks.iter_mut().find(|n| n.ssid == scanned.ssid) {
+            existing.signal = existing.signal.max(scanned.signal);
+            continue;
+        }
+        networks.push(WifiNetwork {
+            known: known.contains(&scanned.ssid),
+            connected: current.as_deref() == Some(scanned.ssid.as_str()),
+            profile: app_profiles
+                .iter()
+                .find(|p| p.ssids.contains(&scanned.ssid))
+                .map(|p| p.name.clone()),
+            ssid: scanned.ssid,
+            signal: scanned.signal,
+            security: scanned.security,
+        });
+    }
+    networks.sort_by(|a, b| b.signal.cmp(&a.signal).then_with(|| a.ssid.cmp(&b.ssid)));
+    Ok(networks)
+}
+
+/// Joins a network the OS already has a profile for and waits until the
+/// adapter reports that SSID.
+pub fn join(backend: &dyn WifiBackend, ssid: &str) -> Result<(), String> {
+    if backend.current_ssid().as_deref() == Some(ssid) {
+        return Ok(());
+    }
+    let known = backend.known_networks()?;
+    if !known.iter().any(|k| k == ssid) {
+        return Err(format!("'{}' için kayıtlı Wi-Fi profili yok", ssid));
+    }
+
+    info!("Connecting to Wi-Fi {}", ssid);
+    backend.connect(ssid)?;
+
+    let deadline = Instant::now() + CONNECT_TIMEOUT;
+    while Instant::now() < deadline {
+        if backend.current_ssid().as_deref() == Some(ssid) {
+            info!("Connected to Wi-Fi {}", ssid);
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+    Err(format!("'{}' ağına bağlanılamadı (zaman aşımı)", ssid))
+}
+
+/// Rejoins the Wi-Fi we just lost, used when the adapter drops the network
+/// entirely instead of only the portal session.
+pub fn rejoin(backend: &dyn WifiBackend, ssid: &str) {
+    if let Err(e) = join(backend, ssid) {
+        warn!("Could not rejoin Wi-Fi {}: {}", ssid, e);
+    }
+}
+
+#[tauri::command]
+pub async fn scan_wifi(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<WifiNetwork>, String> {
+    let app_profiles = profiles::load_profiles(&app);
+    let backend = state.wifi.clone();
+    tokio::task::spawn_blocking(move || list_networks(backend.as_ref(), &app_profiles))
+        .await
+        .map_err(|e| format!("Task hatası: {}", e))?
+}
+
+#[tauri::command]
+pub async fn connect_wifi(state: tauri::State<'_, AppState>, ssid: String) -> Result<(), String> {
+    let backend = state.wifi.clone();
+    tokio::task::spawn_blocking(move || join(backend.as_ref(), &ssid))
+        .await
+        .map_err(|e| format!("Task hatası: {}", e))?
+}
+
+/// The OS tools: netsh on Windows, nmcli elsewhere.
+pub struct SystemBackend;
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(windows)]
+    command.creation_flags(CREATE_NO_WINDOW);
+    let output = command
+        .output()
+        .map_err(|e| format!("{} çalıştırılamadı: {}", program, e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if output.status.success() {
+        Ok(stdout)
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        Err(format!(
+            "{} hatası: {}",
+            program,
+            if stderr.is_empty() {
+                stdout.trim()
+            } else {
+                &stderr
+            }
+        ))
+    }
+}
+
+/// `netsh wlan show networks mode=bssid`. The output is localized, so only
+/// the structure is relied on: "SSID n" starts a network, the value with a
+/// '%' is the signal (Turkish puts it in front) and the authentication line
+/// is the first one after the SSID's network type.
+#[cfg(any(windows, test))]
+fn parse_netsh_networks(text: &str) -> Vec<ScannedNetwork> {
+    let mut networks: Vec<ScannedNetwork> = Vec::new();
+    let mut fields_seen = 0;
+
+    for line in text.lines().map(str::trim) {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+
+        if key.starts_with("SSID") {
+            networks.push(ScannedNetwork {
+                ssid: value.to_string(),
+                signal: 0,
+                security: String::new(),
+            });
+            fields_seen = 0;
+            continue;
+        }
+        let Some(network) = networks.last_mut() else {
+            continue;
+        };
+        fields_seen += 1;
+        if fields_seen == 2 {
+            network.security = value.to_string();
+        }
+        if let Some(percent) = value.strip_suffix('%').or_else(|| value.strip_prefix('%')) {
+            let signal = percent.trim().parse().unwrap_or(0);
+            network.signal = network.signal.max(signal);
+        }
+    }
+    networks
+}
+
+/// `netsh wlan show profiles`: "All User Profile" / "Tüm Kullanıcı Profili".
+#[cfg(any(windows, test))]
+fn parse_netsh_profiles(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(key, _)| {
+            key.trim_end().ends_with("Profile") || key.trim_end().ends_with("Profili")
+        })
+        .map(|(_, name)| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+#[cfg(windows)]
+impl WifiBackend for SystemBackend {
+    fn scan(&self) -> Result<Vec<ScannedNetwork>, String> {
+        let text = run("netsh", &["wlan", "show", "networks", "mode=bssid"])?;
+        Ok(parse_netsh_networks(&text))
+    }
+
+    fn known_networks(&self) -> Result<Vec<String>, String> {
+        let text = run("netsh", &["wlan", "show", "profiles"])?;
+        Ok(parse_netsh_profiles(&text))
+    }
+
+    fn connect(&self, ssid: &str) -> Result<(), String> {
+        run(
+            "netsh",
+            &[
+                "wlan",
+                "connect",
+                &format!("name={}", ssid),
+                &format!("ssid={}", ssid),
+            ],
+        )
+        .map(|_| ())
+    }
+
+    fn current_ssid(&self) -> Option<String> {
+        netwatch::current_ssid()
+    }
+}
+
+/// nmcli terse output separates fields with ':' and escapes literal ones.
+#[cfg(any(not(windows), test))]
+fn split_terse(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    fields.last_mut().unwrap().push(next);
+                }
+            }
+            ':' => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+/// `nmcli -t -f SSID,SIGNAL,SECURITY device wifi list`.
+#[cfg(any(not(windows), test))]
+fn parse_nmcli_networks(text: &str) -> Vec<ScannedNetwork> {
+    text.lines()
+        .map(split_terse)
+        .filter(|fields| fields.len() >= 3)
+        .map(|fields| ScannedNetwork {
+            ssid: fields[0].clone(),
+            signal: fields[1].parse().unwrap_or(0),
+            security: fields[2].clone(),
+        })
+        .collect()
+}
+
+/// `nmcli -t -f NAME,TYPE connection show`, Wi-Fi connections only.
+#[cfg(any(not(windows), test))]
+fn parse_nmcli_connections(text: &str) -> Vec<String> {
+    text.lines()
+        .map(split_terse)
+        .filter(|fields| fields.get(1).map(String::as_str) == Some("802-11-wireless"))
+        .map(|fields| fields[0].clone())
+        .collect()
+}
+
+#[cfg(not(windows))]
+impl WifiBackend for SystemBackend {
+    fn scan(&self) -> Result<Vec<ScannedNetwork>, String> {
+        let text = run(
+            "nmcli",
+            &["-t", "-f", "SSID,SIGNAL,SECURITY", "device", "wifi", "list"],
+        )?;
+        Ok(parse_nmcli_networks(&text))
+    }
+
+    fn known_networks(&self) -> Result<Vec<String>, String> {
+        let text = run("nmcli", &["-t", "-f", "NAME,TYPE", "connection", "show"])?;
+        Ok(parse_nmcli_connections(&text))
+    }
+
+    fn connect(&self, ssid: &str) -> Result<(), String> {
+        run("nmcli", &["connection", "up", "id", ssid]).map(|_| ())
+    }
+
+    fn current_ssid(&self) -> Option<String> {
+        netwatch::current_ssid()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    const NETSH_NETWORKS_EN: &str = "
+Interface name : Wi-Fi
+There are 3 networks currently visible.
+
+SSID 1 : Yurt-WiFi
+    Network type            : Infrastructure
+    Authentication          : WPA2-Personal
+    Encryption              : CCMP
+    BSSID 1                 : a4:2b:b0:11:22:33
+         Signal             : 40%
+         Radio type         : 802.11n
+         Channel            : 6
+    BSSID 2                 : a4:2b:b0:11:22:34
+         Signal             : 82%
+         Radio type         : 802.11ac
+         Channel            : 44
+
+SSID 2 : Misafir
+    Network type            : Infrastructure
+    Authentication          : Open
+    Encryption              : None
+    BSSID 1                 : 10:fe:ed:00:00:01
+         Signal             : 30%
+
+SSID 3 : 
+    Network type            : Infrastructure
+    Authentication          : WPA2-Personal
+    Encryption              : CCMP
+    BSSID 1                 : 10:fe:ed:00:00:02
+         Signal             : 12%
+";
+
+    const NETSH_NETWORKS_TR: &str = "
+Arabirim adı : Wi-Fi
+Şu anda 2 ağ görünür durumda.
+
+SSID 1 : Yurt-WiFi
+    Ağ türü                 : Altyapı
+    Kimlik doğrulama        : WPA2-Kişisel
+    Şifreleme               : CCMP
+    BSSID 1                 : a4:2b:b0:11:22:33
+         Sinyal             : %82
+         Radyo türü         : 802.11n
+         Kanal              : 6
+
+SSID 2 : Ev
+    Ağ türü                 : Altyapı
+    Kimlik doğrulama        : WPA3-Kişisel
+    Şifreleme               : CCMP
+    BSSID 1                 : 3c:84:6a:00:00:01
+         Sinyal             : %65
+";
+
+    const NETSH_PROFILES_EN: &str = "
+Profiles on interface Wi-Fi:
+
+Group policy profiles (read only)
+---------------------------------
+    <None>
+
+User profiles
+-------------
+    All User Profile     : Yurt-WiFi
+    All User Profile     : Ev
+";
+
+    const NETSH_PROFILES_TR: &str = "
+Wi-Fi arabirimindeki profiller:
+
+Grup ilkesi profilleri (salt okunur)
+---------------------------------
+    <Yok>
+
+Kullanıcı profilleri
+-------------
+    Tüm Kullanıcı Profili : Yurt-WiFi
+    Tüm Kullanıcı Profili : Ev
+";
+
+    const NMCLI_NETWORKS: &str = "Yurt-WiFi:82:WPA2
+Yurt-WiFi:40:WPA2
+Kat\\:3:55:WPA1 WPA2
+Misafir:30:
+";
+
+    const NMCLI_CONNECTIONS: &str = "Yurt-WiFi:802-11-wireless
+Wired connection 1:802-3-ethernet
+Kat\\:3:802-11-wireless
+lo:loopback
+";
+
+    fn network(ssid: &str, signal: u8, security: &str) -> ScannedNetwork {
+        ScannedNetwork {
+            ssid: ssid.to_string(),
+            signal,
+            security: security.to_string(),
+        }
+    }
+
+    #[test]
+    fn netsh_networks_english() {
+        assert_eq!(
+            parse_netsh_networks(NETSH_NETWORKS_EN),
+            vec![
+                network("Yurt-WiFi", 82, "WPA2-Personal"),
+                network("Misafir", 30, "Open"),
+                network("", 12, "WPA2-Personal"),
+            ]
+        );
+    }
+
+    #[test]
+    fn netsh_networks_turkish() {
+        assert_eq!(
+            parse_netsh_networks(NETSH_NETWORKS_TR),
+            vec![
+                network("Yurt-WiFi", 82, "WPA2-Kişisel"),
+                network("Ev", 65, "WPA3-Kişisel"),
+            ]
+        );
+    }
+
+    #[test]
+    fn netsh_profiles_both_locales() {
+        let expected = vec!["Yurt-WiFi".to_string(), "Ev".to_string()];
+        assert_eq!(parse_netsh_profiles(NETSH_PROFILES_EN), expected);
+        assert_eq!(parse_netsh_profiles(NETSH_PROFILES_TR), expected);
+    }
+
+    #[test]
+    fn nmcli_output() {
+        assert_eq!(
+            parse_nmcli_networks(NMCLI_NETWORKS),
+            vec![
+                network("Yurt-WiFi", 82, "WPA2"),
+                network("Yurt-WiFi", 40, "WPA2"),
+                network("Kat:3", 55, "WPA1 WPA2"),
+                network("Misafir", 30, ""),
+            ]
+        );
+        assert_eq!(
+            parse_nmcli_connections(NMCLI_CONNECTIONS),
+            vec!["Yurt-WiFi".to_string(), "Kat:3".to_string()]
+        );
+    }
+
+    /// Joins instantly, or never when `stuck` is set.
+    struct FakeBackend {
+        scanned: Vec<ScannedNetwork>,
+        known: Vec<String>,
+        connected: Mutex<Option<String>>,
+        stuck: bool,
+    }
+
+    impl FakeBackend {
+        fn new() -> Self {
+            Self {
+                scanned: parse_netsh_networks(NETSH_NETWORKS_EN),
+                known: vec!["Yurt-WiFi".to_string(), "Ev".to_string()],
+                connected: Mutex::new(None),
+                stuck: false,
+            }
+        }
+    }
+
+    impl WifiBackend for FakeBackend {
+        fn scan(&self) -> Result<Vec<ScannedNetwork>, String> {
+            Ok(self.scanned.clone())
+        }
+
+        fn known_networks(&self) -> Result<Vec<String>, String> {
+            Ok(self.known.clone())
+        }
+
+        fn connect(&self, ssid: &str) -> Result<(), String> {
+            if !self.stuck {
+                *self.connected.lock().unwrap() = Some(ssid.to_string());
+            }
+            Ok(())
+        }
+
+        fn current_ssid(&self) -> Option<String> {
+            self.connected.lock().unwrap().clone()
+        }
+    }
+
+    #[test]
+    fn list_merges_scan_with_profiles() {
+        let backend = FakeBackend::new();
+        *backend.connected.lock().unwrap() = Some("Misafir".to_string());
+        let profile: profiles::NetworkProfile = serde_json::from_value(serde_json::json!({
+            "id": "yurt",
+            "name": "Yurt",
+            "ssids": ["Yurt-WiFi"],
+        }))
+        .unwrap();
+
+        let networks = list_networks(&backend, &[profile]).unwrap();
+        let summary: Vec<_> = networks
+            .iter()
+            .map(|n| {
+                (
+                    n.ssid.as_str(),
+                    n.signal,
+                    n.known,
+                    n.connected,
+                    n.profile.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Yurt-WiFi", 82, true, false, Some("Yurt")),
+                ("Misafir", 30, false, true, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn join_known_network() {
+        let backend = FakeBackend::new();
+        join(&backend, "Ev").unwrap();
+        assert_eq!(backend.current_ssid().as_deref(), Some("Ev"));
+        // Already there, nothing to do
+        join(&backend, "Ev").unwrap();
+    }
+
+    #[test]
+    fn join_rejects_unknown_network() {
+        let backend = FakeBackend::new();
+        assert!(join(&backend, "Misafir").is_err());
+        assert_eq!(backend.current_ssid(), None);
+    }
+}
@@ -10,11 +10,7 @@ function App() {
     loadSkippedVersion();
     loadPendingUpdate();
 
-    const { checkForUpdates, checkConnection } = useHotspotStore.getState();
-
-    setTimeout(() => {
-      checkConnection(false);
-    }, 1500);
+    const { checkForUpdates } = useHotspotStore.getState();
 
     checkForUpdates(true, true);
 
@@ -2,11 +2,18 @@ import { useState, useEffect, useRef } from 'react'
 import { Wifi, WifiOff, Loader2, Settings, X } from 'lucide-react'
 import { Button } from '@/components/ui/button'
 import { Badge } from '@/components/ui/badge'
-import { useHotspotStore, type ConnectionStatus } from '@/store/hotspot'
+import { useHotspotStore, type ConnectionStatus, type LatencyStats, type SessionInfo } from '@/store/hotspot'
+import { effectiveFlags } from '@/store/slices/profileSlice'
 import { getVersion } from '@tauri-apps/api/app'
+import { invoke } from '@tauri-apps/api/core'
 import { LoginCard } from './dashboard/LoginCard'
 import { StatusCard } from './dashboard/StatusCard'
 import { SettingsCard } from './dashboard/SettingsCard'
+import { ProfilesCard } from './dashboard/ProfilesCard'
+import { HistoryCard } from './dashboard/HistoryCard'
+
+const MIN_POLL_DELAY = 10000
+const MAX_POLL_DELAY = 60000
 
 const StatusBadge = ({ status, isChecking }: { status: ConnectionStatus, isChecking: boolean }) => {
     if (isChecking) {
@@ -75,57 +82,102 @@ export function Dashboard() {
         checkConnection,
         updateNetworkInfo,
         fetchPublicIp,
-        performPingTest,
         performLogout,
-        performLogin,
         updateInfo,
         isSettingsOpen,
         setSettingsOpen,
-        settings,
         checkHotspotStatus,
         hotspotEnabled,
         userManuallyDisabledHotspot,
-        toggleHotspot
+        toggleHotspot,
+        loadLaunchOptions,
+        loadProfiles,
+        setLatencyStats,
+        setSession
     } = useHotspotStore()
-
-    const startupConnectRef = useRef(false)
+    const keepHotspotOn = useHotspotStore((state) => effectiveFlags(state).keepHotspotOn)
 
     useEffect(() => {
         loadCredentials()
         loadSettings()
+        loadProfiles()
         checkHotspotStatus()
-        if (!hasCheckedRef.current) {
-            checkConnection(false)
-            hasCheckedRef.current = true
+
+        let timer: ReturnType<typeof setTimeout> | undefined
+        let unlistenChanges: (() => void) | undefined
+        let unlistenProfile: (() => void) | undefined
+        let cancelled = false
+        let pollDelay = MIN_POLL_DELAY
+
+        // Network changes are pushed by the backend, so polling only has to
+        // catch portal-side session expiry and can back off while idle
+        const schedulePoll = () => {
+            timer = setTimeout(async () => {
+                const before = useHotspotStore.getState().status
+                await checkConnection(true)
+                const changed = useHotspotStore.getState().status !== before
+                pollDelay = changed ? MIN_POLL_DELAY : Math.min(pollDelay * 2, MAX_POLL_DELAY)
+                if (!cancelled) schedulePoll()
+            }, pollDelay)
         }
 
-        const interval = setInterval(() => {
-            checkConnection(true)
-        }, 10000)
+        const start = async () => {
+            loadLaunchOptions()
+            // The backend holds the first check until the network is up
+            const { listen } = await import('@tauri-apps/api/event')
+            await new Promise<void>((resolve) => {
+                const unlisten = listen('startup-ready', () => {
+                    unlisten.then(f => f())
+                    resolve()
+                })
+                invoke<boolean>('is_startup_ready').then((ready) => {
+                    if (ready) {
+                        unlisten.then(f => f())
+                        resolve()
+                    }
+                })
+            })
+            if (cancelled) return
 
-        return () => clearInterval(interval)
-    }, [])
+            if (!hasCheckedRef.current) {
+                checkConnection(false)
+                hasCheckedRef.current = true
+            }
+            schedulePoll()
 
-    useEffect(() => {
-        if (
-            !startupConnectRef.current &&
-            settings.connectOnStartup &&
-            status === 'disconnected' &&
-            hasCheckedRef.current
-        ) {
-            startupConnectRef.current = true
-            performLogin()
+            unlistenChanges = await listen('network-changed', () => {
+                checkConnection(true)
+                useHotspotStore.getState().loadInterfaces()
+                useHotspotStore.getState().fetchPublicIp()
+                pollDelay = MIN_POLL_DELAY
+                if (timer) clearTimeout(timer)
+                if (!cancelled) schedulePoll()
+            })
+            if (cancelled) unlistenChanges()
+
+            unlistenProfile = await listen('active-profile-changed', () => {
+                useHotspotStore.getState().loadProfiles()
+            })
+            if (cancelled) unlistenProfile()
         }
-    }, [settings.connectOnStartup, status])
+        start()
+
+        return () => {
+            cancelled = true
+            if (timer) clearTimeout(timer)
+            if (unlistenChanges) unlistenChanges()
+            if (unlistenProfile) unlistenProfile()
+        }
+    }, [])
 
     useEffect(() => {
-        if (!settings.keepHotspotOn) return
+        if (!keepHotspotOn) return
 
         const monitor = setInterval(async () => {
             await checkHotspotStatus()
             const state = useHotspotStore.getState()
             if (
-                state.settings.keepHotspotOn &&
+                effectiveFlags(state).keepHotspotOn &&
                 !state.hotspotEnabled &&
                 !state.userManuallyDisabledHotspot &&
                 !state.isTogglingHotspot
@@ -140,15 +192,16 @@ export function Dashboard() {
         }
 
         return () => clearInterval(monitor)
-    }, [settings.keepHotspotOn, userManuallyDisabledHotspot])
+    }, [keepHotspotOn, userManuallyDisabledHotspot])
 
     useEffect(() => {
         // Network stats loop (every 2s)
         updateNetworkInfo()
         fetchPublicIp()
         const interval = setInterval(() => {
+            // Skip while the window sits hidden in the tray
+            if (document.hidden) return
             updateNetworkInfo()
-            if (status === 'connected') performPingTest()
         }, 2000)
         return () => clearInterval(interval)
     }, [status])
@@ -164,6 +217,36 @@ export function Dashboard() {
         })
     }, [])
 
+    useEffect(() => {
+        let unlisten: (() => void) | undefined
+        let cancelled = false
+        invoke<LatencyStats[]>('get_latency_stats').then(setLatencyStats).catch(console.error)
+        import('@tauri-apps/api/event').then(async ({ listen }) => {
+            const stop = await listen<LatencyStats[]>('latency-update', (event) => setLatencyStats(event.payload))
+            if (cancelled) stop()
+            else unlisten = stop
+        })
+        return () => {
+            cancelled = true
+            if (unlisten) unlisten()
+        }
+    }, [])
+
+    useEffect(() => {
+        let unlisten: (() => void) | undefined
+        let cancelled = false
+        invoke<SessionInfo | null>('get_session_info').then(setSession).catch(console.error)
+        import('@tauri-apps/api/event').then(async ({ listen }) => {
+            const stop = await listen<SessionInfo | null>('session-update', (event) => setSession(event.payload))
+            if (cancelled) stop()
+            else unlisten = stop
+        })
+        return () => {
+            cancelled = true
+            if (unlisten) unlisten()
+        }
+    }, [])
+
     useEffect(() => {
         getVersion().then(setAppVersion).catch(console.error)
     }, [])
@@ -189,6 +272,7 @@ export function Dashboard() {
                     </div>
 
                     <SettingsCard appVersion={appVersion} />
+                    <ProfilesCard />
 
                     <Button variant="outline" className="w-full" onClick={() => setSettingsOpen(false)}>
                         Geri Dön
@@ -221,6 +305,8 @@ export function Dashboard() {
                     <LoginCard onLogout={handleLogout} isLoggingOut={isLoggingOut} />
                     <StatusCard />
                 </div>
+
+                <HistoryCard />
             </div>
         </div>
     )
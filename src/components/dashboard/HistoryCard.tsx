@@ -0,0 +1,118 @@
+import { useEffect, useState } from 'react'
+import { Download, Trash2 } from 'lucide-react'
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card'
+import { Button } from '@/components/ui/button'
+import { Label } from '@/components/ui/label'
+import { useHotspotStore, HistoryEntry } from '@/store/hotspot'
+
+const average = (values: (number | null)[]) => {
+    const present = values.filter((v): v is number => v != null)
+    return present.length > 0 ? present.reduce((a, b) => a + b, 0) / present.length : null
+}
+
+// Hour-of-day buckets make the evening slowdown visible at a glance
+const hourlyTrend = (history: HistoryEntry[]) => {
+    const buckets = new Map<number, HistoryEntry[]>()
+    for (const entry of history) {
+        const hour = new Date(entry.timestamp).getHours()
+        buckets.set(hour, [...(buckets.get(hour) ?? []), entry])
+    }
+    return [...buckets.entries()]
+        .sort(([a], [b]) => a - b)
+        .map(([hour, entries]) => ({
+            hour,
+            download: average(entries.map(e => e.downloadMbps)),
+            latency: average(entries.map(e => e.latencyMs)),
+            loss: average(entries.map(e => e.lossPercent)),
+            count: entries.length
+        }))
+}
+
+export function HistoryCard() {
+    const { history, loadHistory, exportHistory, clearHistory } = useHotspotStore()
+    const [exportedPath, setExportedPath] = useState<string | null>(null)
+
+    useEffect(() => {
+        loadHistory()
+    }, [loadHistory])
+
+    const speedTests = history.filter(e => e.kind === 'speedTest')
+    const trend = hourlyTrend(history)
+
+    const handleExport = async (format: 'csv' | 'json') => {
+        setExportedPath(await exportHistory(format))
+    }
+
+    const handleClear = async () => {
+        if (!window.confirm('Tüm ölçüm geçmişi silinsin mi?')) return
+        await clearHistory()
+        setExportedPath(null)
+    }
+
+    return (
+        <Card>
+            <CardHeader>
+                <CardTitle>Ölçüm Geçmişi</CardTitle>
+                <CardDescription>Hız testleri ve gecikme örnekleri, saat bazında ortalamalar</CardDescription>
+            </CardHeader>
+            <CardContent className="space-y-4">
+                {history.length === 0 ? (
+                    <p className="text-sm text-muted-foreground">Henüz kayıtlı ölçüm yok</p>
+                ) : (
+                    <>
+                        <div className="space-y-2">
+                            <Label>Saatlik Eğilim</Label>
+                            <div className="grid grid-cols-4 gap-x-4 gap-y-1 text-xs font-mono">
+                                <span className="text-muted-foreground">Saat</span>
+                                <span className="text-muted-foreground">İndirme</span>
+                                <span className="text-muted-foreground">Gecikme</span>
+                                <span className="text-muted-foreground">Kayıp</span>
+                                {trend.map(row => (
+                                    <div key={row.hour} className="contents" title={`${row.count} ölçüm`}>
+                                        <span>{String(row.hour).padStart(2, '0')}:00</span>
+                                        <span>{row.download != null ? `${row.download.toFixed(1)} Mbps` : '-'}</span>
+                                        <span>{row.latency != null ? `${row.latency.toFixed(0)} ms` : '-'}</span>
+                                        <span>{row.loss != null ? `%${row.loss.toFixed(0)}` : '-'}</span>
+                                    </div>
+                                ))}
+                            </div>
+                        </div>
+
+                        {speedTests.length > 0 && (
+                            <div className="space-y-2">
+                                <Label>Son Hız Testleri</Label>
+                                <div className="max-h-40 overflow-y-auto space-y-1 text-xs font-mono">
+                                    {speedTests.slice(-20).reverse().map(entry => (
+                                        <p key={entry.timestamp} className="text-muted-foreground">
+                                            {new Date(entry.timestamp).toLocaleString('tr-TR')} · {entry.downloadMbps?.toFixed(1)}↓ / {entry.uploadMbps?.toFixed(1)}↑ Mbps
+                                            {entry.latencyMs != null && ` · ${entry.latencyMs.toFixed(0)} ms`}
+                                            {(entry.profile || entry.ssid) && ` · ${entry.profile ?? entry.ssid}`}
+                                        </p>
+                                    ))}
+                                </div>
+                            </div>
+                        )}
+                    </>
+                )}
+
+                <div className="flex gap-2">
+                    <Button variant="outline" size="sm" onClick={() => handleExport('csv')} disabled={history.length === 0}>
+                        <Download className="h-4 w-4 mr-2" />
+                        CSV
+                    </Button>
+                    <Button variant="outline" size="sm" onClick={() => handleExport('json')} disabled={history.length === 0}>
+                        <Download className="h-4 w-4 mr-2" />
+                        JSON
+                    </Button>
+                    <Button variant="ghost" size="sm" onClick={handleClear} disabled={history.length === 0} className="ml-auto">
+                        <Trash2 className="h-4 w-4 mr-2" />
+                        Temizle
+                    </Button>
+                </div>
+                {exportedPath && (
+                    <p className="text-xs text-muted-foreground break-all">Kaydedildi: {exportedPath}</p>
+                )}
+            </CardContent>
+        </Card>
+    )
+}
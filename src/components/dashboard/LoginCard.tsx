@@ -27,7 +27,8 @@ export function LoginCard({ onLogout, isLoggingOut }: LoginCardProps) {
         saveSettings,
         hotspotEnabled,
         isTogglingHotspot,
-        toggleHotspot
+        toggleHotspot,
+        applyStartupOptions
     } = useHotspotStore()
 
     const handleSubmit = async (e: React.FormEvent) => {
@@ -49,7 +50,7 @@ export function LoginCard({ onLogout, isLoggingOut }: LoginCardProps) {
     const handleConnectOnStartupToggle = async () => {
         const newValue = !settings.connectOnStartup;
         setSettings({ ...settings, connectOnStartup: newValue });
-        setTimeout(() => saveSettings(), 100);
+        setTimeout(() => applyStartupOptions().catch((e) => console.error('Failed to apply startup options', e)), 100);
     }
 
     return (
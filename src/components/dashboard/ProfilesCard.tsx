@@ -0,0 +1,311 @@
+import { useEffect, useState } from 'react'
+import { Plus, Trash2, Loader2, Save, RefreshCw, Wifi } from 'lucide-react'
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card'
+import { Button } from '@/components/ui/button'
+import { Badge } from '@/components/ui/badge'
+import { Input } from '@/components/ui/input'
+import { Label } from '@/components/ui/label'
+import { Switch } from '@/components/ui/switch'
+import { useHotspotStore, NetworkProfile } from '@/store/hotspot'
+
+const PROVIDERS: { value: string; label: string }[] = [
+    { value: 'maxxarena', label: 'MaxxArena' },
+    { value: 'none', label: 'Portal Yok' }
+]
+
+const FLAGS: { key: 'autoLogin' | 'autoReconnect' | 'keepHotspotOn'; label: string }[] = [
+    { key: 'autoLogin', label: 'Ağa bağlanınca giriş yap' },
+    { key: 'autoReconnect', label: 'Otomatik yeniden bağlan' },
+    { key: 'keepHotspotOn', label: 'Etkin noktayı açık tut' }
+]
+
+const splitList = (value: string) => value.split(',').map(v => v.trim()).filter(Boolean)
+
+export function ProfilesCard() {
+    const {
+        profiles,
+        activeProfile,
+        loadProfiles,
+        saveProfiles,
+        detectCurrentNetwork,
+        saveProfileCredentials,
+        wifiNetworks,
+        isScanningWifi,
+        scanWifi,
+        connectWifi
+    } = useHotspotStore()
+    const [drafts, setDrafts] = useState<NetworkProfile[]>(profiles)
+    const [credentialDrafts, setCredentialDrafts] = useState<Record<string, { username: string; password: string }>>({})
+    const [isSaving, setIsSaving] = useState(false)
+    const [isDetecting, setIsDetecting] = useState(false)
+
+    useEffect(() => {
+        loadProfiles()
+        scanWifi()
+    }, [])
+
+    useEffect(() => {
+        setDrafts(profiles)
+    }, [profiles])
+
+    const updateDraft = (id: string, patch: Partial<NetworkProfile>) => {
+        setDrafts(drafts.map(p => (p.id === id ? { ...p, ...patch } : p)))
+    }
+
+    const handleAddCurrent = async () => {
+        setIsDetecting(true)
+        const network = await detectCurrentNetwork()
+        setIsDetecting(false)
+        setDrafts([
+            ...drafts,
+            {
+                id: crypto.randomUUID(),
+                name: network?.ssid ?? 'Yeni Ağ',
+                ssids: network?.ssid ? [network.ssid] : [],
+                gatewayMacs: network?.gatewayMac ? [network.gatewayMac] : [],
+                provider: 'maxxarena',
+                credentialsRef: null,
+                autoLogin: false,
+                autoReconnect: false,
+                keepHotspotOn: false,
+                probes: [],
+                dnsServers: [],
+                tls: { pinnedSha256: [], caBundle: '', insecureHosts: [] }
+            }
+        ])
+    }
+
+    const handleConnectWifi = async (ssid: string) => {
+        try {
+            await connectWifi(ssid)
+        } catch (e) {
+            alert(`Wi-Fi bağlantısı başarısız: ${e}`)
+        }
+    }
+
+    const handleSave = async () => {
+        setIsSaving(true)
+        try {
+            const saved: NetworkProfile[] = []
+            for (const profile of drafts) {
+                const creds = credentialDrafts[profile.id]
+                if (creds?.username) {
+                    await saveProfileCredentials(profile.id, creds)
+                    saved.push({ ...profile, credentialsRef: profile.id })
+                } else {
+                    saved.push(profile)
+                }
+            }
+            await saveProfiles(saved)
+            setCredentialDrafts({})
+        } catch (e) {
+            alert(`Profiller kaydedilemedi: ${e}`)
+        } finally {
+            setIsSaving(false)
+        }
+    }
+
+    return (
+        <Card>
+            <CardHeader>
+                <CardTitle>Ağ Profilleri</CardTitle>
+                <CardDescription>
+                    {profiles.length === 0
+                        ? "Profil yokken genel ayarlar her ağda geçerlidir"
+                        : activeProfile
+                            ? `Etkin profil: ${activeProfile.name}`
+                            : "Bu ağ için profil yok, otomatik işlem yapılmıyor"}
+                </CardDescription>
+            </CardHeader>
+            <CardContent className="space-y-6">
+                {drafts.map(profile => (
+                    <div key={profile.id} className="space-y-3 rounded-md border p-3">
+                        <div className="flex items-center gap-2">
+                            <Input
+                                value={profile.name}
+                                onChange={(e) => updateDraft(profile.id, { name: e.target.value })}
+                            />
+                            {activeProfile?.id === profile.id && (
+                                <Badge variant="success">Etkin</Badge>
+                            )}
+                            <Button
+                                variant="outline"
+                                size="icon"
+                                title="Profili sil"
+                                onClick={() => setDrafts(drafts.filter(p => p.id !== profile.id))}
+                            >
+                                <Trash2 className="h-4 w-4" />
+                            </Button>
+                        </div>
+
+                        <div className="space-y-1">
+                            <Label>Wi-Fi Adları (SSID)</Label>
+                            <Input
+                                defaultValue={profile.ssids.join(', ')}
+                                onBlur={(e) => updateDraft(profile.id, { ssids: splitList(e.target.value) })}
+                            />
+                        </div>
+                        <div className="space-y-1">
+                            <Label>Ağ Geçidi MAC Adresleri</Label>
+                            <Input
+                                defaultValue={profile.gatewayMacs.join(', ')}
+                                onBlur={(e) => updateDraft(profile.id, { gatewayMacs: splitList(e.target.value) })}
+                            />
+                        </div>
+                        <div className="space-y-1">
+                            <Label>Bağlantı Kontrol Adresleri</Label>
+                            <Input
+                                placeholder="Boş bırakılırsa varsayılan kullanılır"
+                                defaultValue={profile.probes.join(', ')}
+                                onBlur={(e) => updateDraft(profile.id, { probes: splitList(e.target.value) })}
+                            />
+                        </div>
+                        <div className="space-y-1">
+                            <Label>DNS Sunucuları</Label>
+                            <Input
+                                placeholder="Boş bırakılırsa sistem DNS'i kullanılır"
+                                defaultValue={profile.dnsServers.join(', ')}
+                                onBlur={(e) => updateDraft(profile.id, { dnsServers: splitList(e.target.value) })}
+                            />
+                        </div>
+
+                        <div className="flex gap-2">
+                            {PROVIDERS.map(({ value, label }) => (
+                                <Button
+                                    key={value}
+                                    size="sm"
+                                    variant={profile.provider === value ? "default" : "outline"}
+                                    onClick={() => updateDraft(profile.id, { provider: value })}
+                                >
+                                    {label}
+                                </Button>
+                            ))}
+                        </div>
+
+                        {FLAGS.map(({ key, label }) => (
+                            <div key={key} className="flex items-center justify-between">
+                                <Label>{label}</Label>
+                                <Switch
+                                    checked={profile[key]}
+                                    disabled={key !== 'keepHotspotOn' && profile.provider === 'none'}
+                                    onCheckedChange={(checked) => updateDraft(profile.id, { [key]: checked })}
+                                />
+                            </div>
+                        ))}
+
+                        {profile.provider !== 'none' && (
+                            <div className="space-y-2">
+                                <Label>Portal Sertifikası</Label>
+                                <p className="text-sm text-muted-foreground">
+                                    Yalnızca portal kendinden imzalı ya da süresi dolmuş bir sertifika kullanıyorsa doldurun.
+                                </p>
+                                <Input
+                                    placeholder="SHA-256 parmak izi, ör. AB:CD:..."
+                                    defaultValue={profile.tls.pinnedSha256.join(', ')}
+                                    onBlur={(e) => updateDraft(profile.id, { tls: { ...profile.tls, pinnedSha256: splitList(e.target.value) } })}
+                                />
+                                {profile.tls.pinnedSha256.length > 0 && (
+                                    <p className="text-sm text-orange-500">
+                                        Portal yalnızca bu parmak izine sahip sertifikayla kabul edilir; sertifika yenilenince giriş başarısız olur.
+                                    </p>
+                                )}
+                                <Input
+                                    placeholder="Güvenilir CA dosyası (PEM) yolu"
+                                    defaultValue={profile.tls.caBundle}
+                                    onBlur={(e) => updateDraft(profile.id, { tls: { ...profile.tls, caBundle: e.target.value.trim() } })}
+                                />
+                                {profile.tls.caBundle && (
+                                    <p className="text-sm text-orange-500">
+                                        Bu CA'nın imzaladığı her sertifika portal adresi için geçerli sayılır. Yalnızca ağ yöneticisinden aldığınız dosyaları kullanın.
+                                    </p>
+                                )}
+                                <Input
+                                    placeholder="Sertifikası denetlenmeyecek adresler (virgülle)"
+                                    defaultValue={profile.tls.insecureHosts.join(', ')}
+                                    onBlur={(e) => updateDraft(profile.id, { tls: { ...profile.tls, insecureHosts: splitList(e.target.value) } })}
+                                />
+                                {profile.tls.insecureHosts.length > 0 && (
+                                    <p className="text-sm text-red-500">
+                                        Bu adreslere giden trafik, şifreniz dahil, araya giren biri tarafından okunabilir. Yalnızca başka çare yoksa kullanın.
+                                    </p>
+                                )}
+                            </div>
+                        )}
+
+                        {profile.provider !== 'none' && (
+                            <div className="space-y-1">
+                                <Label>
+                                    {profile.credentialsRef
+                                        ? "Profile özel kimlik bilgileri kayıtlı"
+                                        : "Ana kimlik bilgileri kullanılıyor"}
+                                </Label>
+                                <div className="flex gap-2">
+                                    <Input
+                                        placeholder="Kullanıcı adı"
+                                        value={credentialDrafts[profile.id]?.username ?? ''}
+                                        onChange={(e) => setCredentialDrafts({
+                                            ...credentialDrafts,
+                                            [profile.id]: { password: '', ...credentialDrafts[profile.id], username: e.target.value }
+                                        })}
+                                    />
+                                    <Input
+                                        type="password"
+                                        placeholder="Şifre"
+                                        value={credentialDrafts[profile.id]?.password ?? ''}
+                                        onChange={(e) => setCredentialDrafts({
+                                            ...credentialDrafts,
+                                            [profile.id]: { username: '', ...credentialDrafts[profile.id], password: e.target.value }
+                                        })}
+                                    />
+                                </div>
+                            </div>
+                        )}
+                    </div>
+                ))}
+
+                <div className="space-y-2">
+                    <div className="flex items-center justify-between">
+                        <Label>Yakındaki Wi-Fi Ağları</Label>
+                        <Button variant="ghost" size="icon" title="Yeniden tara" onClick={() => scanWifi()} disabled={isScanningWifi}>
+                            <RefreshCw className={`h-4 w-4 ${isScanningWifi ? "animate-spin" : ""}`} />
+                        </Button>
+                    </div>
+                    {wifiNetworks.map(network => (
+                        <div key={network.ssid} className="flex items-center justify-between text-sm">
+                            <div className="flex items-center gap-2">
+                                <Wifi className="h-4 w-4 text-muted-foreground" />
+                                <span>{network.ssid}</span>
+                                <span className="text-muted-foreground">{network.signal}% · {network.security}</span>
+                                {network.profile && <Badge variant="secondary">{network.profile}</Badge>}
+                            </div>
+                            {network.connected ? (
+                                <Badge variant="success">Bağlı</Badge>
+                            ) : (
+                                <Button
+                                    size="sm"
+                                    variant="outline"
+                                    disabled={!network.known}
+                                    title={network.known ? undefined : "Windows'ta kayıtlı profil yok"}
+                                    onClick={() => handleConnectWifi(network.ssid)}
+                                >
+                                    Bağlan
+                                </Button>
+                            )}
+                        </div>
+                    ))}
+                </div>
+
+                <div className="flex gap-2">
+                    <Button variant="outline" className="flex-1" onClick={handleAddCurrent} disabled={isDetecting}>
+                        {isDetecting ? <Loader2 className="mr-2 h-4 w-4 animate-spin" /> : <Plus className="mr-2 h-4 w-4" />}
+                        Bu Ağı Ekle
+                    </Button>
+                    <Button className="flex-1" onClick={handleSave} disabled={isSaving}>
+                        {isSaving ? <Loader2 className="mr-2 h-4 w-4 animate-spin" /> : <Save className="mr-2 h-4 w-4" />}
+                        Kaydet
+                    </Button>
+                </div>
+            </CardContent>
+        </Card>
+    )
+}
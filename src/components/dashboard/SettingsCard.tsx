@@ -4,10 +4,27 @@ import { Switch } from '@/components/ui/switch'
 import { Button } from '@/components/ui/button'
 import { Badge } from '@/components/ui/badge'
 import { RefreshCw, Loader2 } from 'lucide-react'
-import { useHotspotStore } from '@/store/hotspot'
+import { Input } from '@/components/ui/input'
+import { useHotspotStore, StartupError, StartupMethod, ProxyMode, ProxySettings } from '@/store/hotspot'
 
 
 
+const STARTUP_METHODS: { value: StartupMethod; label: string }[] = [
+    { value: 'service', label: 'Hizmet' },
+    { value: 'task', label: 'Zamanlanmış Görev' },
+    { value: 'runKey', label: 'Kullanıcı Kaydı' }
+]
+
+// Windows always gives the mobile hotspot adapter this address
+const HOTSPOT_ADDRESS = '192.168.137.1'
+
+const PROXY_MODES: { value: ProxyMode; label: string }[] = [
+    { value: 'system', label: 'Sistem' },
+    { value: 'none', label: 'Doğrudan' },
+    { value: 'manual', label: 'Elle' },
+    { value: 'pac', label: 'PAC' }
+]
+
 interface SettingsCardProps {
     appVersion: string
 }
@@ -17,12 +34,19 @@ export function SettingsCard({ appVersion }: SettingsCardProps) {
         settings,
         setSettings,
         saveSettings,
+        loadSettings,
         updateInfo,
         checkForUpdates,
         installUpdate,
         restartApp,
         autoStartEnabled,
+        autoStartMethod,
+        canElevate,
         toggleAutoStart,
+        setStartupMethod,
+        startupDiagnosis,
+        repairStartup,
+        applyStartupOptions,
         userManuallyDisabledHotspot
     } = useHotspotStore()
 
@@ -31,16 +55,62 @@ export function SettingsCard({ appVersion }: SettingsCardProps) {
         setTimeout(() => saveSettings(), 100)
     }
 
+    const handleLocalApiChange = async (key: 'enabled' | 'bindHotspot', value: boolean) => {
+        setSettings({ ...settings, localApi: { ...settings.localApi, [key]: value } })
+        await saveSettings()
+        await loadSettings()
+    }
+
     const handleAutoStartChange = async (checked: boolean) => {
         try {
             await toggleAutoStart(checked)
         } catch (error) {
-            alert(`Başlangıç ayarı değiştirilemedi: ${error}`)
+            const startupError = error as StartupError
+            if (startupError?.kind === 'cancelled') return
+            alert(`Başlangıç ayarı değiştirilemedi: ${startupError?.message ?? error}`)
+        }
+    }
+
+    const updateProxy = (patch: Partial<ProxySettings>) => {
+        const proxy = { ...settings.http.proxy, ...patch }
+        if (JSON.stringify(proxy) === JSON.stringify(settings.http.proxy)) return
+        setSettings({ ...settings, http: { ...settings.http, proxy } })
+        setTimeout(() => saveSettings(), 100)
+    }
+
+    const handleStartupMethodChange = async (method: StartupMethod) => {
+        try {
+            await setStartupMethod(method)
+        } catch (error) {
+            const startupError = error as StartupError
+            if (startupError?.kind === 'cancelled') return
+            alert(`Başlangıç yöntemi değiştirilemedi: ${startupError?.message ?? error}`)
+        }
+    }
+
+    const handleRepairStartup = async () => {
+        try {
+            await repairStartup()
+        } catch (error) {
+            const startupError = error as StartupError
+            if (startupError?.kind === 'cancelled') return
+            alert(`Başlangıç kayıtları onarılamadı: ${startupError?.message ?? error}`)
+        }
+    }
+
+    const handleStartupOptionsChange = async (changes: Partial<typeof settings>) => {
+        setSettings({ ...settings, ...changes })
+        try {
+            await applyStartupOptions()
+        } catch (error) {
+            const startupError = error as StartupError
+            if (startupError?.kind === 'cancelled') return
+            alert(`Başlangıç ayarı uygulanamadı: ${startupError?.message ?? error}`)
         }
     }
 
     const handleStartMinimizedChange = async (checked: boolean) => {
-        handleSettingsChange('startInTray', checked)
+        handleStartupOptionsChange({ startInTray: checked })
     }
 
     return (
@@ -64,6 +134,58 @@ export function SettingsCard({ appVersion }: SettingsCardProps) {
                         />
                     </div>
 
+                    <div className="space-y-2">
+                        <div className="space-y-0.5">
+                            <Label>Başlatma Yöntemi</Label>
+                            <p className="text-sm text-muted-foreground">
+                                {canElevate
+                                    ? "Hizmet yönetici izni ister, diğer yöntemler yalnızca bu kullanıcı için çalışır"
+                                    : "Yönetici izni alınamıyor, kullanıcı başlangıç kaydı kullanılacak"}
+                            </p>
+                        </div>
+                        <div className="flex gap-2">
+                            {STARTUP_METHODS.map(({ value, label }) => (
+                                <Button
+                                    key={value}
+                                    size="sm"
+                                    variant={settings.startupMethod === value ? "default" : "outline"}
+                                    disabled={value === 'service' && !canElevate}
+                                    onClick={() => handleStartupMethodChange(value)}
+                                >
+                                    {label}
+                                    {autoStartMethod === value && (
+                                        <Badge variant="secondary" className="ml-2">Etkin</Badge>
+                                    )}
+                                </Button>
+                            ))}
+                        </div>
+                    </div>
+
+                    {startupDiagnosis && !startupDiagnosis.consistent && (
+                        <div className="space-y-2 rounded-md border border-orange-500/40 p-3">
+                            <p className="text-sm text-orange-500 font-medium">
+                                Tutarsız başlangıç kayıtları bulundu
+                            </p>
+                            <ul className="text-xs text-muted-foreground space-y-1">
+                                {startupDiagnosis.artifacts.map((artifact) => (
+                                    <li key={`${artifact.method}-${artifact.name}`} className="break-all">
+                                        {STARTUP_METHODS.find((m) => m.value === artifact.method)?.label ?? artifact.method}
+                                        {' · '}{artifact.name}{' → '}{artifact.target ?? artifact.command}
+                                        {!artifact.targetExists && ' (dosya yok)'}
+                                        {artifact.legacy && ' (eski)'}
+                                        {artifact.outdated && ' (farklı ayarlar)'}
+                                    </li>
+                                ))}
+                                {startupDiagnosis.errors.map((error, index) => (
+                                    <li key={`error-${index}`}>{error.message}</li>
+                                ))}
+                            </ul>
+                            <Button size="sm" variant="outline" onClick={handleRepairStartup}>
+                                Onar
+                            </Button>
+                        </div>
+                    )}
+
                     <div className="flex items-center justify-between opacity-transition transition-opacity duration-200" style={{ opacity: autoStartEnabled ? 1 : 0.5, pointerEvents: autoStartEnabled ? 'auto' : 'none' }}>
                         <div className="space-y-0.5">
                             <Label>Sistem Tepsisinde Başlat</Label>
@@ -78,6 +200,75 @@ export function SettingsCard({ appVersion }: SettingsCardProps) {
                         />
                     </div>
 
+                    <div className="flex items-center justify-between gap-4 transition-opacity duration-200" style={{ opacity: autoStartEnabled ? 1 : 0.5, pointerEvents: autoStartEnabled ? 'auto' : 'none' }}>
+                        <div className="space-y-0.5">
+                            <Label>Başlangıç Gecikmesi (sn)</Label>
+                            <p className="text-sm text-muted-foreground">
+                                Windows açıldıktan sonra ilk kontrolden önce beklenecek süre
+                            </p>
+                        </div>
+                        <Input
+                            type="number"
+                            min={0}
+                            max={600}
+                            className="w-24"
+                            defaultValue={settings.startupDelaySecs}
+                            key={`delay-${settings.startupDelaySecs}`}
+                            disabled={!autoStartEnabled}
+                            onBlur={(e) => {
+                                const value = Math.max(0, Math.min(600, Number(e.target.value) || 0))
+                                if (value !== settings.startupDelaySecs) {
+                                    handleStartupOptionsChange({ startupDelaySecs: value })
+                                }
+                            }}
+                        />
+                    </div>
+
+                    <div className="flex items-center justify-between gap-4">
+                        <div className="space-y-0.5">
+                            <Label>Ağ Bekleme Süresi (sn)</Label>
+                            <p className="text-sm text-muted-foreground">
+                                Açılışta ilk kontrolden önce ağ bağlantısı için en fazla beklenecek süre
+                            </p>
+                        </div>
+                        <Input
+                            type="number"
+                            min={0}
+                            max={300}
+                            className="w-24"
+                            defaultValue={settings.networkWaitTimeoutSecs}
+                            key={`network-wait-${settings.networkWaitTimeoutSecs}`}
+                            onBlur={(e) => {
+                                const value = Math.max(0, Math.min(300, Number(e.target.value) || 0))
+                                if (value !== settings.networkWaitTimeoutSecs) {
+                                    setSettings({ ...settings, networkWaitTimeoutSecs: value })
+                                    setTimeout(() => saveSettings(), 100)
+                                }
+                            }}
+                        />
+                    </div>
+
+                    <div className="flex items-center justify-between gap-4 transition-opacity duration-200" style={{ opacity: autoStartEnabled ? 1 : 0.5, pointerEvents: autoStartEnabled ? 'auto' : 'none' }}>
+                        <div className="space-y-0.5">
+                            <Label>Başlangıç Profili</Label>
+                            <p className="text-sm text-muted-foreground">
+                                Açılışta kullanılacak ağ profilinin adı (boş: otomatik)
+                            </p>
+                        </div>
+                        <Input
+                            className="w-40"
+                            defaultValue={settings.startupProfile ?? ''}
+                            key={`profile-${settings.startupProfile ?? ''}`}
+                            disabled={!autoStartEnabled}
+                            onBlur={(e) => {
+                                const value = e.target.value.trim() || null
+                                if (value !== settings.startupProfile) {
+                                    handleStartupOptionsChange({ startupProfile: value })
+                                }
+                            }}
+                        />
+                    </div>
+
                     <div className="flex items-center justify-between">
                         <div className="space-y-0.5">
                             <Label>Kapatma butonunda sistem tepsisine küçült</Label>
@@ -93,6 +284,256 @@ export function SettingsCard({ appVersion }: SettingsCardProps) {
                 </CardContent>
             </Card>
 
+            <Card className="mt-4">
+                <CardHeader>
+                    <CardTitle>Gecikme İzleme</CardTitle>
+                    <CardDescription>Hedefler: host:port (TCP), icmp:host, gateway, portal</CardDescription>
+                </CardHeader>
+                <CardContent className="space-y-6">
+                    <div className="flex items-center justify-between">
+                        <div className="space-y-0.5">
+                            <Label>Gecikmeyi Ölç</Label>
+                            <p className="text-sm text-muted-foreground">
+                                Hedeflere düzenli aralıklarla bağlanıp gecikme, jitter ve kaybı hesaplar
+                            </p>
+                        </div>
+                        <Switch
+                            checked={settings.latency.enabled}
+                            onCheckedChange={(checked) => {
+                                setSettings({ ...settings, latency: { ...settings.latency, enabled: checked } })
+                                setTimeout(() => saveSettings(), 100)
+                            }}
+                        />
+                    </div>
+
+                    <div className="flex items-center gap-2">
+                        <Input
+                            defaultValue={settings.latency.targets.join(', ')}
+                            key={`latency-targets-${settings.latency.targets.join(',')}`}
+                            onBlur={(e) => {
+                                const targets = e.target.value.split(',').map(t => t.trim()).filter(Boolean)
+                                if (targets.join(',') !== settings.latency.targets.join(',')) {
+                                    setSettings({ ...settings, latency: { ...settings.latency, targets } })
+                                    setTimeout(() => saveSettings(), 100)
+                                }
+                            }}
+                        />
+                        <Input
+                            type="number"
+                            min={1}
+                            max={300}
+                            className="w-24"
+                            title="Ölçüm aralığı (sn)"
+                            defaultValue={settings.latency.intervalSecs}
+                            key={`latency-interval-${settings.latency.intervalSecs}`}
+                            onBlur={(e) => {
+                                const value = Math.max(1, Math.min(300, Number(e.target.value) || 5))
+                                if (value !== settings.latency.intervalSecs) {
+                                    setSettings({ ...settings, latency: { ...settings.latency, intervalSecs: value } })
+                                    setTimeout(() => saveSettings(), 100)
+                                }
+                            }}
+                        />
+                    </div>
+                </CardContent>
+            </Card>
+
+            <Card className="mt-4">
+                <CardHeader>
+                    <CardTitle>Hız Testi</CardTitle>
+                    <CardDescription>Test sunucuları ve paralel bağlantı sayısı</CardDescription>
+                </CardHeader>
+                <CardContent className="space-y-4">
+                    {([
+                        ['downloadUrl', 'İndirme Adresi'],
+                        ['uploadUrl', 'Yükleme Adresi']
+                    ] as const).map(([key, label]) => (
+                        <div key={key} className="space-y-1">
+                            <Label>{label}</Label>
+                            <Input
+                                defaultValue={settings.speedTest[key]}
+                                key={`speed-${key}-${settings.speedTest[key]}`}
+                                onBlur={(e) => {
+                                    const value = e.target.value.trim()
+                                    if (value && value !== settings.speedTest[key]) {
+                                        setSettings({ ...settings, speedTest: { ...settings.speedTest, [key]: value } })
+                                        setTimeout(() => saveSettings(), 100)
+                                    }
+                                }}
+                            />
+                        </div>
+                    ))}
+                    <div className="flex items-center justify-between gap-4">
+                        <Label>Paralel Bağlantı</Label>
+                        <Input
+                            type="number"
+                            min={1}
+                            max={16}
+                            className="w-24"
+                            defaultValue={settings.speedTest.streams}
+                            key={`speed-streams-${settings.speedTest.streams}`}
+                            onBlur={(e) => {
+                                const value = Math.max(1, Math.min(16, Number(e.target.value) || 4))
+                                if (value !== settings.speedTest.streams) {
+                                    setSettings({ ...settings, speedTest: { ...settings.speedTest, streams: value } })
+                                    setTimeout(() => saveSettings(), 100)
+                                }
+                            }}
+                        />
+                    </div>
+                </CardContent>
+            </Card>
+
+            <Card className="mt-4">
+                <CardHeader>
+                    <CardTitle>Oturum Süresi</CardTitle>
+                    <CardDescription>Bilet süresi dolmadan önce yeniden giriş</CardDescription>
+                </CardHeader>
+                <CardContent className="space-y-6">
+                    <div className="flex items-center justify-between">
+                        <div className="space-y-0.5">
+                            <Label>Süre Dolmadan Yeniden Giriş Yap</Label>
+                            <p className="text-sm text-muted-foreground">
+                                İndirmeler kesilmesin diye bilet bitmeden kısa süre önce tekrar giriş yapar
+                            </p>
+                        </div>
+                        <Switch
+                            checked={settings.session.proactiveRelogin}
+                            onCheckedChange={(checked) => {
+                                setSettings({ ...settings, session: { ...settings.session, proactiveRelogin: checked } })
+                                setTimeout(() => saveSettings(), 100)
+                            }}
+                        />
+                    </div>
+                    <div className="flex items-center justify-between gap-4">
+                        <Label>Kaç Saniye Önce</Label>
+                        <Input
+                            type="number"
+                            min={15}
+                            className="w-24"
+                            defaultValue={settings.session.reloginBeforeSecs}
+                            key={`session-before-${settings.session.reloginBeforeSecs}`}
+                            onBlur={(e) => {
+                                const value = Math.max(15, Number(e.target.value) || 120)
+                                if (value !== settings.session.reloginBeforeSecs) {
+                                    setSettings({ ...settings, session: { ...settings.session, reloginBeforeSecs: value } })
+                                    setTimeout(() => saveSettings(), 100)
+                                }
+                            }}
+                        />
+                    </div>
+                    <div className="flex items-center justify-between gap-4">
+                        <div className="space-y-0.5">
+                            <Label>Bilet Süresi (dakika)</Label>
+                            <p className="text-sm text-muted-foreground">
+                                Portal kalan süreyi göstermediğinde kullanılır, 0 bilinmiyor demektir
+                            </p>
+                        </div>
+                        <Input
+                            type="number"
+                            min={0}
+                            className="w-24"
+                            defaultValue={settings.session.ticketMinutes}
+                            key={`session-ticket-${settings.session.ticketMinutes}`}
+                            onBlur={(e) => {
+                                const value = Math.max(0, Math.floor(Number(e.target.value) || 0))
+                                if (value !== settings.session.ticketMinutes) {
+                                    setSettings({ ...settings, session: { ...settings.session, ticketMinutes: value } })
+                                    setTimeout(() => saveSettings(), 100)
+                                }
+                            }}
+                        />
+                    </div>
+                </CardContent>
+            </Card>
+
+            <Card className="mt-4">
+                <CardHeader>
+                    <CardTitle>Bağlantı</CardTitle>
+                    <CardDescription>Portal ve ağ istekleri için ortak ayarlar</CardDescription>
+                </CardHeader>
+                <CardContent className="space-y-4">
+                    {([
+                        ['timeoutSecs', 'İstek Zaman Aşımı (sn)', 15],
+                        ['connectTimeoutSecs', 'Bağlantı Zaman Aşımı (sn)', 5]
+                    ] as const).map(([key, label, fallback]) => (
+                        <div key={key} className="flex items-center justify-between gap-4">
+                            <Label>{label}</Label>
+                            <Input
+                                type="number"
+                                min={1}
+                                className="w-24"
+                                defaultValue={settings.http[key]}
+                                key={`http-${key}-${settings.http[key]}`}
+                                onBlur={(e) => {
+                                    const value = Math.max(1, Number(e.target.value) || fallback)
+                                    if (value !== settings.http[key]) {
+                                        setSettings({ ...settings, http: { ...settings.http, [key]: value } })
+                                        setTimeout(() => saveSettings(), 100)
+                                    }
+                                }}
+                            />
+                        </div>
+                    ))}
+                    <div className="space-y-1">
+                        <Label>User-Agent</Label>
+                        <Input
+                            defaultValue={settings.http.userAgent}
+                            key={`http-ua-${settings.http.userAgent}`}
+                            onBlur={(e) => {
+                                const value = e.target.value.trim()
+                                if (value && value !== settings.http.userAgent) {
+                                    setSettings({ ...settings, http: { ...settings.http, userAgent: value } })
+                                    setTimeout(() => saveSettings(), 100)
+                                }
+                            }}
+                        />
+                    </div>
+                    <div className="space-y-2">
+                        <div className="space-y-0.5">
+                            <Label>Proxy</Label>
+                            <p className="text-sm text-muted-foreground">Giriş, bağlantı kontrolü ve güncellemeler için</p>
+                        </div>
+                        <div className="flex gap-2">
+                            {PROXY_MODES.map(({ value, label }) => (
+                                <Button
+                                    key={value}
+                                    size="sm"
+                                    variant={settings.http.proxy.mode === value ? "default" : "outline"}
+                                    onClick={() => updateProxy({ mode: value })}
+                                >
+                                    {label}
+                                </Button>
+                            ))}
+                        </div>
+                        {settings.http.proxy.mode === 'manual' && (
+                            <>
+                                <Input
+                                    placeholder="http://sunucu:8080"
+                                    defaultValue={settings.http.proxy.url}
+                                    key={`proxy-url-${settings.http.proxy.url}`}
+                                    onBlur={(e) => updateProxy({ url: e.target.value.trim() })}
+                                />
+                                <Input
+                                    placeholder="Proxy kullanmayacak adresler (virgülle), ör. portal adresi"
+                                    defaultValue={settings.http.proxy.bypass}
+                                    key={`proxy-bypass-${settings.http.proxy.bypass}`}
+                                    onBlur={(e) => updateProxy({ bypass: e.target.value.trim() })}
+                                />
+                            </>
+                        )}
+                        {settings.http.proxy.mode === 'pac' && (
+                            <Input
+                                placeholder="http://sunucu/proxy.pac"
+                                defaultValue={settings.http.proxy.pacUrl}
+                                key={`proxy-pac-${settings.http.proxy.pacUrl}`}
+                                onBlur={(e) => updateProxy({ pacUrl: e.target.value.trim() })}
+                            />
+                        )}
+                    </div>
+                </CardContent>
+            </Card>
+
             <Card className="mt-4">
                 <CardHeader>
                     <CardTitle>Mobil Etkin Nokta</CardTitle>
@@ -139,6 +580,52 @@ export function SettingsCard({ appVersion }: SettingsCardProps) {
                 </CardContent>
             </Card>
 
+            <Card className="mt-4">
+                <CardHeader>
+                    <CardTitle>Yerel API</CardTitle>
+                    <CardDescription>Betikler ve otomasyon araçları için HTTP arayüzü</CardDescription>
+                </CardHeader>
+                <CardContent className="space-y-6">
+                    <div className="flex items-center justify-between">
+                        <div className="space-y-0.5">
+                            <Label>Yerel API'yi Etkinleştir</Label>
+                            <p className="text-sm text-muted-foreground">
+                                {settings.localApi.bindHotspot
+                                    ? `127.0.0.1:${settings.localApi.port} ve etkin nokta adresi ${HOTSPOT_ADDRESS}:${settings.localApi.port} üzerinde dinler`
+                                    : `Yalnızca 127.0.0.1:${settings.localApi.port} adresinde dinler`}
+                            </p>
+                        </div>
+                        <Switch
+                            checked={settings.localApi.enabled}
+                            onCheckedChange={(checked) => handleLocalApiChange('enabled', checked)}
+                        />
+                    </div>
+
+                    <div className="flex items-center justify-between transition-opacity duration-200" style={{ opacity: settings.localApi.enabled ? 1 : 0.5, pointerEvents: settings.localApi.enabled ? 'auto' : 'none' }}>
+                        <div className="space-y-0.5">
+                            <Label>Etkin Nokta Ağında da Dinle</Label>
+                            <p className="text-sm text-muted-foreground">
+                                Etkin noktaya bağlı cihazlar da API'ye erişebilir
+                            </p>
+                        </div>
+                        <Switch
+                            checked={settings.localApi.bindHotspot}
+                            onCheckedChange={(checked) => handleLocalApiChange('bindHotspot', checked)}
+                            disabled={!settings.localApi.enabled}
+                        />
+                    </div>
+
+                    {settings.localApi.enabled && settings.localApi.token && (
+                        <div className="space-y-1">
+                            <Label>Token</Label>
+                            <p className="text-xs font-mono break-all rounded-md bg-muted p-2 select-all">
+                                {settings.localApi.token}
+                            </p>
+                        </div>
+                    )}
+                </CardContent>
+            </Card>
+
             <Card className="mt-4">
                 <CardHeader>
                     <CardTitle className="flex items-center gap-2">
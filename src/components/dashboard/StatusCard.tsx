@@ -1,3 +1,4 @@
+import { useEffect } from 'react'
 import { Loader2, Zap } from 'lucide-react'
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card'
 import { Label } from '@/components/ui/label'
@@ -9,12 +10,18 @@ export function StatusCard() {
         status,
         logs,
         ping,
+        latencyStats,
         ipInfo,
+        interfaces,
         networkStats,
         settings,
         lastLogin,
+        session,
+        portalStatus,
+        loadPortalStatus,
         speedTestResult,
-        runSpeedTest
+        runSpeedTest,
+        cancelSpeedTest
     } = useHotspotStore()
 
     const formatBytes = (bytes: number) => {
@@ -25,6 +32,24 @@ export function StatusCard() {
         return parseFloat((bytes / Math.pow(k, i)).toFixed(2)) + ' ' + sizes[i]
     }
 
+    useEffect(() => {
+        if (status === 'connected') loadPortalStatus()
+    }, [status, loadPortalStatus])
+
+    const formatRemaining = (secs: number) => {
+        const hours = Math.floor(secs / 3600)
+        const minutes = Math.floor((secs % 3600) / 60)
+        return hours > 0 ? `${hours} sa ${minutes} dk` : `${Math.max(1, minutes)} dk`
+    }
+
+    const primaryInterface = interfaces.find(i => i.ipv4.includes(ipInfo.local))
+    const interfaceSummary = primaryInterface && [
+        primaryInterface.name,
+        primaryInterface.gateways.length > 0 && `Ağ geçidi: ${primaryInterface.gateways.join(', ')}`,
+        primaryInterface.dnsServers.length > 0 && `DNS: ${primaryInterface.dnsServers.join(', ')}`,
+        primaryInterface.linkSpeedMbps && `Bağlantı hızı: ${primaryInterface.linkSpeedMbps} Mbps`
+    ].filter(Boolean).join('\n')
+
     return (
         <Card>
             <CardHeader>
@@ -39,6 +64,26 @@ export function StatusCard() {
                             <span>{new Date(lastLogin).toLocaleString('tr-TR')}</span>
                         </div>
                     )}
+                    {session?.remainingSecs != null && (
+                        <div className="flex items-center justify-between text-sm">
+                            <span className="text-muted-foreground">Bilet Süresi</span>
+                            <span
+                                className={session.remainingSecs < 600 ? 'text-orange-500' : ''}
+                                title={session.source === 'portal' ? 'Portaldan okundu' : 'Ayarlardaki bilet süresine göre'}
+                            >
+                                {session.remainingSecs > 0 ? `${formatRemaining(session.remainingSecs)} kaldı` : 'Doldu'}
+                            </span>
+                        </div>
+                    )}
+                    {portalStatus?.remainingBytes != null && (
+                        <div className="flex items-center justify-between text-sm">
+                            <span className="text-muted-foreground">Kalan Kota</span>
+                            <span title={portalStatus.validUntil ? `Geçerlilik: ${portalStatus.validUntil}` : undefined}>
+                                {formatBytes(portalStatus.remainingBytes)}
+                                {portalStatus.totalBytes != null && ` / ${formatBytes(portalStatus.totalBytes)}`}
+                            </span>
+                        </div>
+                    )}
                 </div>
 
                 <div className="space-y-2">
@@ -57,9 +102,16 @@ export function StatusCard() {
                     <div className="space-y-1">
                         <p className="text-muted-foreground">Ağ Gecikmesi (Ping)</p>
                         <div className="flex items-center gap-2">
-                            <div className={`h-2 w-2 rounded-full ${ping ? (ping < 100 ? 'bg-green-500' : 'bg-yellow-500') : 'bg-gray-500'}`} />
-                            <span className="font-mono">{ping ? `${ping}ms` : '-'}</span>
+                            <div className={`h-2 w-2 rounded-full ${ping && ping > 0 ? (ping < 100 ? 'bg-green-500' : 'bg-yellow-500') : 'bg-gray-500'}`} />
+                            <span className="font-mono">{ping && ping > 0 ? `${ping}ms` : '-'}</span>
                         </div>
+                        {latencyStats.map(stat => (
+                            <p key={stat.target} className="text-[10px] text-muted-foreground font-mono">
+                                {stat.target}: {stat.avgMs != null ? `ort ${stat.avgMs.toFixed(0)} / p95 ${stat.p95Ms?.toFixed(0)} ms` : '-'}
+                                {stat.jitterMs != null && `, jitter ${stat.jitterMs.toFixed(1)} ms`}
+                                {`, kayıp %${stat.lossPercent.toFixed(0)}`}
+                            </p>
+                        ))}
                     </div>
                     <div className="space-y-1">
                         <p className="text-muted-foreground">Veri İndirme</p>
@@ -67,7 +119,9 @@ export function StatusCard() {
                     </div>
                     <div className="space-y-1">
                         <p className="text-muted-foreground">IP (Yerel)</p>
-                        <span className="font-mono">{settings.privacyMode ? '192.168.x.x' : ipInfo.local}</span>
+                        <span className="font-mono" title={settings.privacyMode ? undefined : interfaceSummary || undefined}>
+                            {settings.privacyMode ? '192.168.x.x' : ipInfo.local}
+                        </span>
                     </div>
                     <div className="space-y-1">
                         <p className="text-muted-foreground">IP (Dış)</p>
@@ -99,9 +153,9 @@ export function StatusCard() {
                             <Button
                                 variant="outline"
                                 size="sm"
-                                onClick={runSpeedTest}
-                                disabled={speedTestResult.isTesting || status !== 'connected'}
-                                title="Hız Testi Başlat"
+                                onClick={speedTestResult.isTesting ? cancelSpeedTest : runSpeedTest}
+                                disabled={!speedTestResult.isTesting && status !== 'connected'}
+                                title={speedTestResult.isTesting ? "Hız Testini İptal Et" : "Hız Testi Başlat"}
                                 className="h-10 px-4"
                             >
                                 {speedTestResult.isTesting ? (
@@ -109,7 +163,7 @@ export function StatusCard() {
                                 ) : (
                                     <Zap className="h-4 w-4 mr-2 text-yellow-500 fill-yellow-500" />
                                 )}
-                                {speedTestResult.isTesting ? 'Test Ediliyor...' : 'Testi Başlat'}
+                                {speedTestResult.isTesting ? `İptal (%${Math.round(speedTestResult.progress * 100)})` : 'Testi Başlat'}
                             </Button>
                         </div>
                     </div>
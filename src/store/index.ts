@@ -2,6 +2,7 @@ import { create } from 'zustand'
 import { HotspotState } from './types'
 import { createAuthSlice } from './slices/authSlice'
 import { createNetworkSlice } from './slices/networkSlice'
+import { createProfileSlice } from './slices/profileSlice'
 import { createSpeedSlice } from './slices/speedSlice'
 import { createUpdateSlice } from './slices/updateSlice'
 import { createUISlice } from './slices/uiSlice'
@@ -9,6 +10,7 @@ import { createUISlice } from './slices/uiSlice'
 export const useHotspotStore = create<HotspotState>()((...a) => ({
     ...createAuthSlice(...a),
     ...createNetworkSlice(...a),
+    ...createProfileSlice(...a),
     ...createSpeedSlice(...a),
     ...createUpdateSlice(...a),
     ...createUISlice(...a),
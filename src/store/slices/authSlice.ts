@@ -1,10 +1,14 @@
 import { StateCreator } from 'zustand'
 import { invoke } from '@tauri-apps/api/core'
-import { HotspotState } from '../types'
+import { HotspotState, LogoutResult, PortalAccountStatus } from '../types'
 
 export interface AuthSlice {
     credentials: HotspotState['credentials']
     lastLogin: HotspotState['lastLogin']
+    session: HotspotState['session']
+    setSession: HotspotState['setSession']
+    portalStatus: HotspotState['portalStatus']
+    loadPortalStatus: HotspotState['loadPortalStatus']
     setCredentials: HotspotState['setCredentials']
     performLogin: HotspotState['performLogin']
     performLogout: HotspotState['performLogout']
@@ -15,9 +19,17 @@ export interface AuthSlice {
 export const createAuthSlice: StateCreator<HotspotState, [], [], AuthSlice> = (set, get) => ({
     credentials: { username: '', password: '' },
     lastLogin: null,
+    session: null,
+    portalStatus: null,
 
     setCredentials: (credentials) => set({ credentials }),
 
+    // Logins from the tray, IPC or the scheduler only reach the UI this way
+    setSession: (session) => set((state) => ({
+        session,
+        lastLogin: session ? new Date(session.loggedInAt) : state.lastLogin
+    })),
+
     performLogin: async () => {
         const { credentials, addLog, setStatus, checkConnection } = get()
 
@@ -57,13 +69,37 @@ export const createAuthSlice: StateCreator<HotspotState, [], [], AuthSlice> = (s
         addLog('Bağlantı kesiliyor...')
 
         try {
-            await invoke('perform_logout')
+            const result = await invoke<LogoutResult>('perform_logout')
+            if (result.ended) {
+                setStatus('disconnected')
+                addLog('Bağlantı kesildi')
+            } else if (result.conflict) {
+                // The portal and the probes disagree, show what the probes saw
+                setStatus(result.stillOnline ? 'connected' : 'disconnected')
+                addLog(result.portalLoggedIn
+                    ? 'Çıkış doğrulanamadı: internet kesildi ama portal oturumu hâlâ açık gösteriyor'
+                    : 'Çıkış doğrulanamadı: portal oturumu kapattı ama internet hâlâ erişilebilir')
+            } else {
+                setStatus('connected')
+                addLog(result.portalLoggedIn
+                    ? 'Oturum kapatılamadı, portal hâlâ oturumu açık gösteriyor'
+                    : 'Oturum kapatılamadı, internet hâlâ erişilebilir')
+            }
         } catch (error) {
-            console.error('Logout hatası:', error)
+            const msg = typeof error === 'string' ? error : 'Çıkış başarısız'
+            set({ errorMessage: msg })
+            addLog(`Hata: ${msg}`)
         }
+    },
 
-        setStatus('disconnected')
-        addLog('Bağlantı kesildi')
+    loadPortalStatus: async () => {
+        try {
+            const portalStatus = await invoke<PortalAccountStatus>('get_portal_account_status')
+            set({ portalStatus })
+        } catch (e) {
+            // Networks without a portal have nothing to show
+            set({ portalStatus: null })
+        }
     },
 
     saveCredentials: async () => {
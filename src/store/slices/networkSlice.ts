@@ -1,11 +1,14 @@
 import { StateCreator } from 'zustand'
 import { invoke } from '@tauri-apps/api/core'
-import { HotspotState } from '../types'
+import { HotspotState, LatencyStats, NetworkInterface, WifiNetwork } from '../types'
+import { effectiveFlags } from './profileSlice'
 
 export interface NetworkSlice {
     status: HotspotState['status']
     ping: HotspotState['ping']
+    latencyStats: HotspotState['latencyStats']
     ipInfo: HotspotState['ipInfo']
+    interfaces: HotspotState['interfaces']
     networkStats: HotspotState['networkStats']
     manualDisconnect: HotspotState['manualDisconnect']
 
@@ -15,17 +18,25 @@ export interface NetworkSlice {
     toggleHotspot: HotspotState['toggleHotspot']
     checkHotspotStatus: HotspotState['checkHotspotStatus']
 
+    wifiNetworks: HotspotState['wifiNetworks']
+    isScanningWifi: HotspotState['isScanningWifi']
+    scanWifi: HotspotState['scanWifi']
+    connectWifi: HotspotState['connectWifi']
+
     setStatus: HotspotState['setStatus']
     checkConnection: HotspotState['checkConnection']
     updateNetworkInfo: HotspotState['updateNetworkInfo']
-    performPingTest: HotspotState['performPingTest']
+    setLatencyStats: HotspotState['setLatencyStats']
     fetchPublicIp: HotspotState['fetchPublicIp']
+    loadInterfaces: HotspotState['loadInterfaces']
 }
 
 export const createNetworkSlice: StateCreator<HotspotState, [], [], NetworkSlice> = (set, get) => ({
     status: 'disconnected',
     ping: null,
+    latencyStats: [],
     ipInfo: { local: '...', public: '...' },
+    interfaces: [],
     networkStats: { received: 0, transmitted: 0 },
     manualDisconnect: false,
 
@@ -33,6 +44,9 @@ export const createNetworkSlice: StateCreator<HotspotState, [], [], NetworkSlice
     isTogglingHotspot: false,
     userManuallyDisabledHotspot: false,
 
+    wifiNetworks: [],
+    isScanningWifi: false,
+
     setStatus: (status) => {
         set({ status })
         let iconStatus = 'disconnected'
@@ -43,7 +57,11 @@ export const createNetworkSlice: StateCreator<HotspotState, [], [], NetworkSlice
     },
 
     checkConnection: async (silent = false) => {
-        const { setStatus, settings, performLogin, status, manualDisconnect } = get()
+        const { setStatus, performLogin, status } = get()
+        const { autoReconnect } = effectiveFlags(get())
+        // The backend owns the flag so a reload doesn't forget a manual logout
+        const manualDisconnect = await invoke<boolean>('get_manual_disconnect').catch(() => get().manualDisconnect)
+        set({ manualDisconnect })
 
         if (!silent) {
             set({ isChecking: true })
@@ -55,8 +73,6 @@ export const createNetworkSlice: StateCreator<HotspotState, [], [], NetworkSlice
             const isConnected = await invoke<boolean>('check_connection')
 
             if (isConnected) {
-                if (manualDisconnect) set({ manualDisconnect: false })
-
                 if (status !== 'connected') {
                     setStatus('connected')
                     get().addLog('Bağlantı doğrulandı - İnternet mevcut')
@@ -68,7 +84,7 @@ export const createNetworkSlice: StateCreator<HotspotState, [], [], NetworkSlice
                 if (status === 'connected') {
                     setStatus('disconnected')
                     get().addLog('Bağlantı koptu')
-                    if (settings.autoReconnect && !manualDisconnect) {
+                    if (autoReconnect && !manualDisconnect) {
                         get().addLog('Otomatik yeniden bağlanılıyor...')
                         await performLogin()
                     }
@@ -78,7 +94,7 @@ export const createNetworkSlice: StateCreator<HotspotState, [], [], NetworkSlice
                         invoke('update_tray_icon', { status: 'disconnected' }).catch(() => { })
                     }
 
-                    if (settings.autoReconnect && !manualDisconnect && status === 'disconnected') {
+                    if (autoReconnect && !manualDisconnect && status === 'disconnected') {
                         get().addLog('Otomatik bağlantı başlatılıyor...')
                         await performLogin()
                     }
@@ -95,38 +111,71 @@ export const createNetworkSlice: StateCreator<HotspotState, [], [], NetworkSlice
     updateNetworkInfo: async () => {
         try {
             const stats = await invoke<{ total_received: number, total_transmitted: number }>('get_network_stats')
-            const { ipInfo } = get()
-            if (ipInfo.local === '...') {
-                const localIp = await invoke<string>('get_ip_info')
-                set((state) => ({ ipInfo: { ...state.ipInfo, local: localIp } }))
-            }
+            if (get().ipInfo.local === '...') await get().loadInterfaces()
             set({ networkStats: { received: stats.total_received, transmitted: stats.total_transmitted } })
         } catch (e) {
             console.error('Network info update failed', e)
         }
     },
 
-    performPingTest: async () => {
-        const start = Date.now()
-        try {
-            await fetch('https://www.google.com/generate_204', { mode: 'no-cors', cache: 'no-store' })
-            const end = Date.now()
-            set({ ping: end - start })
-        } catch {
-            set({ ping: -1 })
-        }
+    setLatencyStats: (latencyStats: LatencyStats[]) => {
+        // The headline ping is the first internet target, not the gateway or portal
+        const internet = latencyStats.find(s => s.target !== 'gateway' && s.target !== 'portal') ?? latencyStats[0]
+        const ping = internet?.lastMs != null ? Math.round(internet.lastMs) : (internet ? -1 : null)
+        set({ latencyStats, ping })
     },
 
     fetchPublicIp: async () => {
         try {
-            const res = await fetch('https://api.ipify.org?format=json')
-            const data = await res.json()
-            set((state) => ({ ipInfo: { ...state.ipInfo, public: data.ip } }))
+            const ip = await invoke<string>('get_public_ip')
+            set((state) => ({ ipInfo: { ...state.ipInfo, public: ip } }))
         } catch {
             set((state) => ({ ipInfo: { ...state.ipInfo, public: 'Hata' } }))
         }
     },
 
+    loadInterfaces: async () => {
+        try {
+            const interfaces = await invoke<NetworkInterface[]>('get_interfaces')
+            // Same pick as the backend: an active, non-hotspot adapter, preferring one with a gateway
+            const primary = interfaces
+                .filter(i => i.up && !i.isHotspot && i.ipv4.length > 0)
+                .sort((a, b) => Number(b.gateways.length > 0) - Number(a.gateways.length > 0) || Number(a.isVirtual) - Number(b.isVirtual))[0]
+            set((state) => ({
+                interfaces,
+                ipInfo: { ...state.ipInfo, local: primary?.ipv4[0] ?? 'Bilinmiyor' }
+            }))
+        } catch (e) {
+            console.error('Interface list failed', e)
+        }
+    },
+
+    scanWifi: async () => {
+        set({ isScanningWifi: true })
+        try {
+            const wifiNetworks = await invoke<WifiNetwork[]>('scan_wifi')
+            set({ wifiNetworks })
+        } catch (e) {
+            console.error('Wi-Fi taraması başarısız', e)
+            get().addLog(`Wi-Fi taraması başarısız: ${e}`)
+        } finally {
+            set({ isScanningWifi: false })
+        }
+    },
+
+    connectWifi: async (ssid) => {
+        get().addLog(`${ssid} ağına bağlanılıyor...`)
+        try {
+            await invoke('connect_wifi', { ssid })
+            get().addLog(`${ssid} ağına bağlanıldı`)
+        } catch (e) {
+            get().addLog(`Wi-Fi bağlantısı başarısız: ${e}`)
+            throw e
+        } finally {
+            await get().scanWifi()
+        }
+    },
+
     checkHotspotStatus: async () => {
         try {
             const enabled = await invoke<boolean>('get_hotspot_status')
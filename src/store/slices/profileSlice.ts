@@ -0,0 +1,65 @@
+import { StateCreator } from 'zustand'
+import { invoke } from '@tauri-apps/api/core'
+import { CurrentNetwork, HotspotState, NetworkProfile } from '../types'
+
+export interface ProfileSlice {
+    profiles: HotspotState['profiles']
+    activeProfile: HotspotState['activeProfile']
+    loadProfiles: HotspotState['loadProfiles']
+    saveProfiles: HotspotState['saveProfiles']
+    detectCurrentNetwork: HotspotState['detectCurrentNetwork']
+    saveProfileCredentials: HotspotState['saveProfileCredentials']
+}
+
+// Once profiles exist, an unknown network gets no automation at all;
+// without profiles the global settings apply as before
+export const effectiveFlags = (state: HotspotState) => {
+    if (state.activeProfile) {
+        return {
+            autoReconnect: state.activeProfile.autoReconnect && state.activeProfile.provider !== 'none',
+            keepHotspotOn: state.activeProfile.keepHotspotOn
+        }
+    }
+    if (state.profiles.length > 0) {
+        return { autoReconnect: false, keepHotspotOn: false }
+    }
+    return { autoReconnect: state.settings.autoReconnect, keepHotspotOn: state.settings.keepHotspotOn }
+}
+
+export const createProfileSlice: StateCreator<HotspotState, [], [], ProfileSlice> = (set, get) => ({
+    profiles: [],
+    activeProfile: null,
+
+    loadProfiles: async () => {
+        try {
+            const [profiles, activeProfile] = await Promise.all([
+                invoke<NetworkProfile[]>('get_profiles'),
+                invoke<NetworkProfile | null>('get_active_profile')
+            ])
+            set({ profiles, activeProfile })
+        } catch (e) {
+            console.error('Profiles load failed', e)
+        }
+    },
+
+    saveProfiles: async (profiles) => {
+        await invoke('save_profiles', { profiles })
+        set({ profiles })
+        const activeProfile = await invoke<NetworkProfile | null>('get_active_profile')
+        set({ activeProfile })
+    },
+
+    detectCurrentNetwork: async () => {
+        try {
+            return await invoke<CurrentNetwork>('detect_current_network')
+        } catch (e) {
+            console.error('Network detection failed', e)
+            return null
+        }
+    },
+
+    saveProfileCredentials: async (credentialsRef, credentials) => {
+        await invoke('save_profile_credentials', { credentialsRef, ...credentials })
+        get().addLog(`"${credentialsRef}" profili için kimlik bilgileri kaydedildi`)
+    }
+})
@@ -1,108 +1,91 @@
 import { StateCreator } from 'zustand'
-import { HotspotState } from '../types'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { HistoryEntry, HotspotState, SpeedTestProgress, SpeedTestReport } from '../types'
 
 export interface SpeedSlice {
     speedTestResult: HotspotState['speedTestResult']
     runSpeedTest: HotspotState['runSpeedTest']
+    cancelSpeedTest: HotspotState['cancelSpeedTest']
+    history: HotspotState['history']
+    loadHistory: HotspotState['loadHistory']
+    exportHistory: HotspotState['exportHistory']
+    clearHistory: HotspotState['clearHistory']
 }
 
-export const createSpeedSlice: StateCreator<HotspotState, [], [], SpeedSlice> = (set) => ({
-    speedTestResult: { download: 0, upload: 0, isTesting: false, lastRun: null },
+export const createSpeedSlice: StateCreator<HotspotState, [], [], SpeedSlice> = (set, get) => ({
+    speedTestResult: { download: 0, upload: 0, isTesting: false, lastRun: null, phase: null, progress: 0 },
 
     runSpeedTest: async () => {
-        set((state) => ({ speedTestResult: { ...state.speedTestResult, download: 0, upload: 0, isTesting: true } }))
+        set((state) => ({ speedTestResult: { ...state.speedTestResult, download: 0, upload: 0, isTesting: true, phase: 'download', progress: 0 } }))
 
-        try {
-            // STEP 1: REAL-TIME DOWNLOAD TEST
-            const downloadUrl = 'https://speed.cloudflare.com/__down?bytes=10000000';
-            const dStart = Date.now();
-            let loadedBytes = 0;
-
-            const response = await fetch(downloadUrl);
-            const reader = response.body?.getReader();
-
-            if (reader) {
-                while (true) {
-                    const { done, value } = await reader.read();
-                    if (done) break;
-
-                    loadedBytes += value.length;
-                    const currentTime = Date.now();
-                    const durationInSec = (currentTime - dStart) / 1000;
-
-                    if (durationInSec > 0.1) {
-                        const currentBps = (loadedBytes * 8) / durationInSec;
-                        const currentMbps = currentBps / 1000000;
-                        set((state) => ({
-                            speedTestResult: {
-                                ...state.speedTestResult,
-                                download: parseFloat(currentMbps.toFixed(2))
-                            }
-                        }));
-                    }
+        const unlisten = await listen<SpeedTestProgress>('speed-test-progress', ({ payload }) => {
+            set((state) => ({
+                speedTestResult: {
+                    ...state.speedTestResult,
+                    [payload.phase]: payload.mbps,
+                    phase: payload.phase,
+                    progress: payload.progress
                 }
-            }
+            }))
+        })
 
-            const dEnd = Date.now();
-            const dSec = (dEnd - dStart) / 1000;
-            const finalDMbps = (loadedBytes * 8) / (dSec * 1000000);
+        try {
+            const report = await invoke<SpeedTestReport>('run_speed_test')
             set((state) => ({
-                speedTestResult: { ...state.speedTestResult, download: parseFloat(finalDMbps.toFixed(2)) }
-            }));
-
-
-            // STEP 2: REAL-TIME UPLOAD TEST
-            await new Promise<void>((resolve, reject) => {
-                const xhr = new XMLHttpRequest();
-                const uStart = Date.now();
-                const uPayload = new Uint8Array(2 * 1024 * 1024);
-
-                xhr.open('POST', 'https://httpbin.org/post', true);
-
-                xhr.upload.onprogress = (event) => {
-                    const currentTime = Date.now();
-                    const durationInSec = (currentTime - uStart) / 1000;
-
-                    if (event.lengthComputable && durationInSec > 0.05) {
-                        const currentBps = (event.loaded * 8) / durationInSec;
-                        const currentMbps = currentBps / 1000000;
-
-                        set((state) => ({
-                            speedTestResult: {
-                                ...state.speedTestResult,
-                                upload: parseFloat(currentMbps.toFixed(2))
-                            }
-                        }));
-                    }
-                };
+                speedTestResult: {
+                    ...state.speedTestResult,
+                    download: report.downloadMbps,
+                    upload: report.uploadMbps,
+                    isTesting: false,
+                    lastRun: Date.now(),
+                    phase: null,
+                    progress: 1
+                }
+            }))
+            // The backend records the entry off-thread
+            setTimeout(() => get().loadHistory(), 1000)
+        } catch (e) {
+            console.error('Speed test failed', e)
+            get().addLog(`Hız testi başarısız: ${e}`)
+            set((state) => ({ speedTestResult: { ...state.speedTestResult, download: 0, upload: 0, isTesting: false, phase: null, progress: 0 } }))
+        } finally {
+            unlisten()
+        }
+    },
 
-                xhr.upload.onloadend = () => {
-                    const endDuration = (Date.now() - uStart) / 1000;
-                    if (endDuration > 0) {
-                        const finalMbps = (uPayload.length * 8) / (endDuration * 1000000);
-                        set((state) => ({
-                            speedTestResult: {
-                                ...state.speedTestResult,
-                                upload: parseFloat(finalMbps.toFixed(2))
-                            }
-                        }));
-                    }
-                    resolve();
-                };
+    cancelSpeedTest: async () => {
+        await invoke<boolean>('cancel_speed_test')
+    },
 
-                xhr.onerror = (e) => {
-                    console.error('XHR Upload Error', e);
-                    reject(new Error('Upload test failed'));
-                };
+    history: [],
 
-                xhr.send(uPayload);
-            });
+    loadHistory: async () => {
+        try {
+            const history = await invoke<HistoryEntry[]>('get_history', { limit: 2000 })
+            set({ history })
+        } catch (e) {
+            console.error('Failed to load history', e)
+        }
+    },
 
-            set((state) => ({ speedTestResult: { ...state.speedTestResult, isTesting: false, lastRun: Date.now() } }));
+    exportHistory: async (format) => {
+        try {
+            const path = await invoke<string>('export_history', { format })
+            get().addLog(`Geçmiş dışa aktarıldı: ${path}`)
+            return path
+        } catch (e) {
+            get().addLog(`Geçmiş dışa aktarılamadı: ${e}`)
+            return null
+        }
+    },
 
+    clearHistory: async () => {
+        try {
+            await invoke('clear_history')
+            set({ history: [] })
         } catch (e) {
-            console.error('Speed test failed', e)
-            set((state) => ({ speedTestResult: { ...state.speedTestResult, download: 0, upload: 0, isTesting: false } }))
+            console.error('Failed to clear history', e)
         }
     }
 })
@@ -1,6 +1,6 @@
 import { StateCreator } from 'zustand'
 import { invoke } from '@tauri-apps/api/core'
-import { HotspotState } from '../types'
+import { HotspotState, StartupDiagnosis, StartupMethod, StartupOptions, StartupStatus } from '../types'
 
 export interface UISlice {
     logs: HotspotState['logs']
@@ -15,8 +15,17 @@ export interface UISlice {
     saveSettings: HotspotState['saveSettings']
     loadSettings: HotspotState['loadSettings']
     autoStartEnabled: HotspotState['autoStartEnabled']
+    autoStartMethod: HotspotState['autoStartMethod']
+    canElevate: HotspotState['canElevate']
     checkAutoStartStatus: HotspotState['checkAutoStartStatus']
     toggleAutoStart: HotspotState['toggleAutoStart']
+    setStartupMethod: HotspotState['setStartupMethod']
+    applyStartupOptions: HotspotState['applyStartupOptions']
+    launchOptions: HotspotState['launchOptions']
+    loadLaunchOptions: HotspotState['loadLaunchOptions']
+    startupDiagnosis: HotspotState['startupDiagnosis']
+    diagnoseStartup: HotspotState['diagnoseStartup']
+    repairStartup: HotspotState['repairStartup']
 }
 
 export const createUISlice: StateCreator<HotspotState, [], [], UISlice> = (set, get) => ({
@@ -28,7 +37,27 @@ export const createUISlice: StateCreator<HotspotState, [], [], UISlice> = (set,
         privacyMode: false,
         startInTray: true,
         connectOnStartup: false,
-        keepHotspotOn: false
+        keepHotspotOn: false,
+        localApi: { enabled: false, port: 47615, token: '', bindHotspot: false },
+        startupMethod: 'service',
+        startupDelaySecs: 0,
+        startupProfile: null,
+        networkWaitTimeoutSecs: 30,
+        latency: { enabled: true, targets: ['gateway', 'portal', '1.1.1.1:443'], intervalSecs: 5 },
+        speedTest: {
+            downloadUrl: 'https://speed.cloudflare.com/__down?bytes=25000000',
+            uploadUrl: 'https://speed.cloudflare.com/__up',
+            streams: 4,
+            warmupSecs: 2,
+            durationSecs: 8
+        },
+        session: { proactiveRelogin: true, reloginBeforeSecs: 120, ticketMinutes: 0 },
+        http: {
+            timeoutSecs: 15,
+            connectTimeoutSecs: 5,
+            userAgent: '',
+            proxy: { mode: 'system', url: '', bypass: '', pacUrl: '' }
+        }
     },
     isChecking: false,
     isSettingsOpen: false,
@@ -47,6 +76,7 @@ export const createUISlice: StateCreator<HotspotState, [], [], UISlice> = (set,
             await invoke('save_settings', { settings })
         } catch (e) {
             console.error('Settings save failed', e)
+            get().addLog(`Ayarlar kaydedilemedi: ${e}`)
         }
     },
 
@@ -62,11 +92,18 @@ export const createUISlice: StateCreator<HotspotState, [], [], UISlice> = (set,
     },
 
     autoStartEnabled: false,
+    autoStartMethod: null,
+    canElevate: true,
 
     checkAutoStartStatus: async () => {
         try {
-            const enabled = await invoke<boolean>('is_startup_enabled')
-            set({ autoStartEnabled: enabled })
+            const status = await invoke<StartupStatus>('is_startup_enabled')
+            set({
+                autoStartEnabled: status.enabled,
+                autoStartMethod: status.method,
+                canElevate: status.elevation.canElevate
+            })
+            get().diagnoseStartup()
         } catch (e) {
             console.error('Failed to check autostart', e)
         }
@@ -78,9 +115,11 @@ export const createUISlice: StateCreator<HotspotState, [], [], UISlice> = (set,
         const { settings } = get()
         try {
             if (checked) {
-                await invoke('enable_startup', { minimized: settings.startInTray })
+                const method = await invoke<StartupMethod>('enable_startup')
+                set({ autoStartMethod: method })
             } else {
                 await invoke('disable_startup')
+                set({ autoStartMethod: null })
             }
         } catch (error) {
             console.error('Failed to change autostart settings:', error)
@@ -88,5 +127,54 @@ export const createUISlice: StateCreator<HotspotState, [], [], UISlice> = (set,
             set({ autoStartEnabled: !checked })
             throw error // Re-throw to handle in UI if needed
         }
+    },
+
+    setStartupMethod: async (method: StartupMethod) => {
+        const { settings, autoStartEnabled } = get()
+        set({ settings: { ...settings, startupMethod: method } })
+        await get().saveSettings()
+        // Re-register so the new method replaces the old one
+        if (autoStartEnabled) {
+            await get().toggleAutoStart(true)
+        }
+        get().diagnoseStartup()
+    },
+
+    applyStartupOptions: async () => {
+        await get().saveSettings()
+        // The registered command line carries the options, so rewrite it
+        if (get().autoStartEnabled) {
+            await get().toggleAutoStart(true)
+        }
+    },
+
+    launchOptions: null,
+
+    loadLaunchOptions: async () => {
+        try {
+            const launchOptions = await invoke<StartupOptions>('get_launch_options')
+            set({ launchOptions })
+            return launchOptions
+        } catch (e) {
+            console.error('Failed to load launch options', e)
+            return null
+        }
+    },
+
+    startupDiagnosis: null,
+
+    diagnoseStartup: async () => {
+        try {
+            const diagnosis = await invoke<StartupDiagnosis>('diagnose_startup')
+            set({ startupDiagnosis: diagnosis })
+        } catch (e) {
+            console.error('Failed to diagnose autostart', e)
+        }
+    },
+
+    repairStartup: async () => {
+        const diagnosis = await invoke<StartupDiagnosis>('repair_startup')
+        set({ startupDiagnosis: diagnosis })
+        await get().checkAutoStartStatus()
     }
 })
@@ -16,6 +16,10 @@ export interface UpdateSlice {
 
 let pendingUpdate: any = null;
 
+// The updater plugin runs its own HTTP client, so it is told about our proxy here
+const updaterProxy = async () =>
+    (await invoke<string | null>('get_updater_proxy').catch(() => null)) ?? undefined;
+
 export const createUpdateSlice: StateCreator<HotspotState, [], [], UpdateSlice> = (set, get) => ({
     updateInfo: {
         status: 'idle',
@@ -92,7 +96,8 @@ export const createUpdateSlice: StateCreator<HotspotState, [], [], UpdateSlice>
                 headers: {
                     'Authorization': `Bearer ${GITHUB_TOKEN}`,
                     'Accept': 'application/octet-stream'
-                }
+                },
+                proxy: await updaterProxy()
             });
 
             if (update) {
@@ -191,7 +196,8 @@ export const createUpdateSlice: StateCreator<HotspotState, [], [], UpdateSlice>
                     headers: {
                         'Authorization': `Bearer ${GITHUB_TOKEN}`,
                         'Accept': 'application/octet-stream'
-                    }
+                    },
+                    proxy: await updaterProxy()
                 });
             }
 
@@ -260,7 +266,8 @@ export const createUpdateSlice: StateCreator<HotspotState, [], [], UpdateSlice>
                     headers: {
                         'Authorization': `Bearer ${GITHUB_TOKEN}`,
                         'Accept': 'application/octet-stream'
-                    }
+                    },
+                    proxy: await updaterProxy()
                 });
 
                 if (update) {
@@ -13,13 +13,201 @@ export interface Settings {
     startInTray: boolean
     connectOnStartup: boolean
     keepHotspotOn: boolean
+    localApi: LocalApiSettings
+    startupMethod: StartupMethod
+    startupDelaySecs: number
+    startupProfile: string | null
+    networkWaitTimeoutSecs: number
+    latency: LatencySettings
+    speedTest: SpeedTestSettings
+    session: SessionSettings
+    http: HttpSettings
 }
 
+export interface HttpSettings {
+    timeoutSecs: number
+    connectTimeoutSecs: number
+    userAgent: string
+    proxy: ProxySettings
+}
+
+export type ProxyMode = 'system' | 'none' | 'manual' | 'pac'
+
+export interface ProxySettings {
+    mode: ProxyMode
+    url: string
+    bypass: string
+    pacUrl: string
+}
+
+export interface SessionSettings {
+    proactiveRelogin: boolean
+    reloginBeforeSecs: number
+    ticketMinutes: number
+}
+
+export interface PortalAccountStatus {
+    provider: string
+    loggedIn: boolean
+    remainingBytes: number | null
+    usedBytes: number | null
+    totalBytes: number | null
+    remainingSecs: number | null
+    validUntil: string | null
+}
+
+export interface SessionInfo {
+    loggedInAt: string
+    expiresAt: string | null
+    remainingSecs: number | null
+    source: 'portal' | 'settings' | null
+}
+
+export interface LogoutResult {
+    portalLoggedIn: boolean | null
+    stillOnline: boolean
+    conflict: boolean
+    ended: boolean
+}
+
+export interface LatencySettings {
+    enabled: boolean
+    targets: string[]
+    intervalSecs: number
+}
+
+export interface LocalApiSettings {
+    enabled: boolean
+    port: number
+    token: string
+    bindHotspot: boolean
+}
+
+export type StartupMethod = 'service' | 'task' | 'runKey'
+
+export interface StartupOptions {
+    minimized: boolean
+    delaySecs: number
+    connect: boolean
+    profile: string | null
+}
+
+export interface StartupStatus {
+    enabled: boolean
+    method: StartupMethod | null
+    elevation: {
+        elevated: boolean
+        canElevate: boolean
+    }
+}
+
+export interface StartupArtifact {
+    method: StartupMethod
+    name: string
+    command: string
+    target: string | null
+    targetExists: boolean
+    legacy: boolean
+    outdated: boolean
+}
+
+export interface StartupDiagnosis {
+    configured: StartupMethod
+    effective: StartupMethod
+    artifacts: StartupArtifact[]
+    errors: StartupError[]
+    consistent: boolean
+}
+
+export interface StartupError {
+    kind: 'cancelled' | 'accessDenied' | 'launcherMissing' | 'failed'
+    method: StartupMethod
+    message: string
+}
+
+export interface NetworkProfile {
+    id: string
+    name: string
+    ssids: string[]
+    gatewayMacs: string[]
+    provider: string
+    credentialsRef: string | null
+    autoLogin: boolean
+    autoReconnect: boolean
+    keepHotspotOn: boolean
+    probes: string[]
+    dnsServers: string[]
+    tls: PortalTls
+}
+
+export interface PortalTls {
+    pinnedSha256: string[]
+    caBundle: string
+    insecureHosts: string[]
+}
+
+export interface CurrentNetwork {
+    ssid: string | null
+    gatewayMac: string | null
+}
+
+export interface WifiNetwork {
+    ssid: string
+    signal: number
+    security: string
+    known: boolean
+    connected: boolean
+    profile: string | null
+}
+
+export type SpeedTestPhase = 'download' | 'upload'
+
 export interface SpeedTestResult {
     download: number
     upload: number
     isTesting: boolean
     lastRun: number | null
+    phase: SpeedTestPhase | null
+    progress: number
+}
+
+export interface SpeedTestProgress {
+    phase: SpeedTestPhase
+    mbps: number
+    progress: number
+    warmingUp: boolean
+}
+
+export interface SpeedTestReport {
+    downloadMbps: number
+    uploadMbps: number
+    downloadBytes: number
+    uploadBytes: number
+    streams: number
+    server: string
+}
+
+export type HistoryKind = 'speedTest' | 'latency'
+
+export interface HistoryEntry {
+    timestamp: string
+    kind: HistoryKind
+    profile: string | null
+    ssid: string | null
+    server: string | null
+    downloadMbps: number | null
+    uploadMbps: number | null
+    latencyMs: number | null
+    jitterMs: number | null
+    lossPercent: number | null
+}
+
+export interface SpeedTestSettings {
+    downloadUrl: string
+    uploadUrl: string
+    streams: number
+    warmupSecs: number
+    durationSecs: number
 }
 
 export interface UpdateInfo {
@@ -42,6 +230,32 @@ export interface IpInfo {
     public: string
 }
 
+export interface NetworkInterface {
+    name: string
+    description: string
+    mac: string | null
+    ipv4: string[]
+    ipv6: string[]
+    gateways: string[]
+    dnsServers: string[]
+    mtu: number | null
+    linkSpeedMbps: number | null
+    up: boolean
+    isHotspot: boolean
+    isVirtual: boolean
+}
+
+export interface LatencyStats {
+    target: string
+    lastMs: number | null
+    minMs: number | null
+    avgMs: number | null
+    p95Ms: number | null
+    jitterMs: number | null
+    lossPercent: number
+    samples: number
+}
+
 export interface NetworkStats {
     received: number
     transmitted: number
@@ -51,6 +265,10 @@ export interface HotspotState {
     // Auth Slice
     credentials: Credentials
     lastLogin: Date | null
+    session: SessionInfo | null
+    setSession: (session: SessionInfo | null) => void
+    portalStatus: PortalAccountStatus | null
+    loadPortalStatus: () => Promise<void>
     setCredentials: (credentials: Credentials) => void
     performLogin: () => Promise<void>
     performLogout: () => Promise<void>
@@ -60,13 +278,16 @@ export interface HotspotState {
     // Network Slice
     status: ConnectionStatus
     ping: number | null
+    latencyStats: LatencyStats[]
     ipInfo: IpInfo
+    interfaces: NetworkInterface[]
     networkStats: NetworkStats
     setStatus: (status: ConnectionStatus) => void
     checkConnection: (silent?: boolean) => Promise<void>
     updateNetworkInfo: () => Promise<void>
-    performPingTest: () => Promise<void>
+    setLatencyStats: (stats: LatencyStats[]) => void
     fetchPublicIp: () => Promise<void>
+    loadInterfaces: () => Promise<void>
     manualDisconnect: boolean
 
     hotspotEnabled: boolean
@@ -75,9 +296,27 @@ export interface HotspotState {
     toggleHotspot: () => Promise<void>
     checkHotspotStatus: () => Promise<void>
 
+    wifiNetworks: WifiNetwork[]
+    isScanningWifi: boolean
+    scanWifi: () => Promise<void>
+    connectWifi: (ssid: string) => Promise<void>
+
     // Speed Slice
     speedTestResult: SpeedTestResult
     runSpeedTest: () => Promise<void>
+    cancelSpeedTest: () => Promise<void>
+    history: HistoryEntry[]
+    loadHistory: () => Promise<void>
+    exportHistory: (format: 'csv' | 'json') => Promise<string | null>
+    clearHistory: () => Promise<void>
+
+    // Profile Slice
+    profiles: NetworkProfile[]
+    activeProfile: NetworkProfile | null
+    loadProfiles: () => Promise<void>
+    saveProfiles: (profiles: NetworkProfile[]) => Promise<void>
+    detectCurrentNetwork: () => Promise<CurrentNetwork | null>
+    saveProfileCredentials: (credentialsRef: string, credentials: Credentials) => Promise<void>
 
     // Update Slice
     updateInfo: UpdateInfo
@@ -102,6 +341,15 @@ export interface HotspotState {
     saveSettings: () => Promise<void>
     loadSettings: () => Promise<void>
     autoStartEnabled: boolean
+    autoStartMethod: StartupMethod | null
+    canElevate: boolean
     checkAutoStartStatus: () => Promise<void>
     toggleAutoStart: (enabled: boolean) => Promise<void>
+    setStartupMethod: (method: StartupMethod) => Promise<void>
+    applyStartupOptions: () => Promise<void>
+    launchOptions: StartupOptions | null
+    loadLaunchOptions: () => Promise<StartupOptions | null>
+    startupDiagnosis: StartupDiagnosis | null
+    diagnoseStartup: () => Promise<void>
+    repairStartup: () => Promise<void>
 }